@@ -5,7 +5,7 @@ use sp_core::{crypto::UncheckedInto};
 use sp_finality_grandpa::AuthorityId as GrandpaId;
 use sp_runtime::FixedPointNumber;
 
-use asset_pool::{AssetPoolInfo, InterestInfo};
+use asset_pool::{AssetPoolInfo, InterestInfo, InterestModel};
 use model::{BTC, DOT, DOT_BTC_PAIR, DOT_ETH_PAIR, DOT_FIL_PAIR, ETH, FIL};
 use model::Ratio;
 use antimatter_network_runtime::{
@@ -190,77 +190,93 @@ fn testnet_genesis(
 					maximum_total_debit_ratio: Ratio::saturating_from_rational(90, 100),
 					minimum_deposit: 10u128.pow(1),
 					minimum_debit: 10u128.pow(1),
-					health_ratio: Ratio::saturating_from_rational(75, 100),
+					supply_cap: 0,
+					borrow_cap: 0,
+					collateral_factor: Ratio::saturating_from_rational(75, 100),
+					liquidation_threshold: Ratio::saturating_from_rational(80, 100),
+					liquidation_bonus: Ratio::saturating_from_rational(5, 100),
 					total_deposit: 0,
 					total_debit: 0,
 					deposit_rate: Ratio::saturating_from_rational(100, 100),
 					debit_rate: Ratio::saturating_from_rational(100, 100),
 					deposit_apy: Ratio::saturating_from_rational(0, 100),
 					debit_apy: Ratio::saturating_from_rational(0, 100),
-					reserve_factor: Ratio::saturating_from_rational(90, 100),
-					interest_info: InterestInfo {
+					reserve_factor: Ratio::saturating_from_rational(10, 100),
+					interest_model: InterestModel::JumpRate(InterestInfo {
 						critical_point: Ratio::saturating_from_rational(90, 100),
 						base: Ratio::saturating_from_rational(0, 100),
 						slope_1: Ratio::saturating_from_rational(4, 100),
 						slope_2: Ratio::saturating_from_rational(100, 100),
-					},
+					}),
 				}),
 				(ETH, AssetPoolInfo {
 					maximum_total_debit_ratio: Ratio::saturating_from_rational(90, 100),
 					minimum_deposit: 10u128.pow(1),
 					minimum_debit: 10u128.pow(1),
-					health_ratio: Ratio::saturating_from_rational(75, 100),
+					supply_cap: 0,
+					borrow_cap: 0,
+					collateral_factor: Ratio::saturating_from_rational(75, 100),
+					liquidation_threshold: Ratio::saturating_from_rational(80, 100),
+					liquidation_bonus: Ratio::saturating_from_rational(5, 100),
 					total_deposit: 0,
 					total_debit: 0,
 					deposit_rate: Ratio::saturating_from_rational(100, 100),
 					debit_rate: Ratio::saturating_from_rational(100, 100),
 					deposit_apy: Ratio::saturating_from_rational(0, 100),
 					debit_apy: Ratio::saturating_from_rational(0, 100),
-					reserve_factor: Ratio::saturating_from_rational(90, 100),
-					interest_info: InterestInfo {
+					reserve_factor: Ratio::saturating_from_rational(10, 100),
+					interest_model: InterestModel::JumpRate(InterestInfo {
 						critical_point: Ratio::saturating_from_rational(90, 100),
 						base: Ratio::saturating_from_rational(0, 100),
 						slope_1: Ratio::saturating_from_rational(4, 100),
 						slope_2: Ratio::saturating_from_rational(100, 100),
-					},
+					}),
 				}),
 				(BTC, AssetPoolInfo {
 					maximum_total_debit_ratio: Ratio::saturating_from_rational(90, 100),
 					minimum_deposit: 10u128.pow(1),
 					minimum_debit: 10u128.pow(1),
-					health_ratio: Ratio::saturating_from_rational(75, 100),
+					supply_cap: 0,
+					borrow_cap: 0,
+					collateral_factor: Ratio::saturating_from_rational(75, 100),
+					liquidation_threshold: Ratio::saturating_from_rational(80, 100),
+					liquidation_bonus: Ratio::saturating_from_rational(5, 100),
 					total_deposit: 0,
 					total_debit: 0,
 					deposit_rate: Ratio::saturating_from_rational(100, 100),
 					debit_rate: Ratio::saturating_from_rational(100, 100),
 					deposit_apy: Ratio::saturating_from_rational(0, 100),
 					debit_apy: Ratio::saturating_from_rational(0, 100),
-					reserve_factor: Ratio::saturating_from_rational(90, 100),
-					interest_info: InterestInfo {
+					reserve_factor: Ratio::saturating_from_rational(10, 100),
+					interest_model: InterestModel::JumpRate(InterestInfo {
 						critical_point: Ratio::saturating_from_rational(90, 100),
 						base: Ratio::saturating_from_rational(0, 100),
 						slope_1: Ratio::saturating_from_rational(4, 100),
 						slope_2: Ratio::saturating_from_rational(100, 100),
-					},
+					}),
 				}),
 				(FIL, AssetPoolInfo {
 					maximum_total_debit_ratio: Ratio::saturating_from_rational(90, 100),
 					minimum_deposit: 10u128.pow(1),
 					minimum_debit: 10u128.pow(1),
-					health_ratio: Ratio::saturating_from_rational(75, 100),
+					supply_cap: 0,
+					borrow_cap: 0,
+					collateral_factor: Ratio::saturating_from_rational(50, 100),
+					liquidation_threshold: Ratio::saturating_from_rational(60, 100),
+					liquidation_bonus: Ratio::saturating_from_rational(10, 100),
 					total_deposit: 0,
 					total_debit: 0,
 					deposit_rate: Ratio::saturating_from_rational(100, 100),
 					debit_rate: Ratio::saturating_from_rational(100, 100),
 					deposit_apy: Ratio::saturating_from_rational(0, 100),
 					debit_apy: Ratio::saturating_from_rational(0, 100),
-					reserve_factor: Ratio::saturating_from_rational(90, 100),
-					interest_info: InterestInfo {
+					reserve_factor: Ratio::saturating_from_rational(10, 100),
+					interest_model: InterestModel::JumpRate(InterestInfo {
 						critical_point: Ratio::saturating_from_rational(90, 100),
 						base: Ratio::saturating_from_rational(0, 100),
 						slope_1: Ratio::saturating_from_rational(4, 100),
 						slope_2: Ratio::saturating_from_rational(100, 100),
-					},
+					}),
 				}),
 			]
 		}),
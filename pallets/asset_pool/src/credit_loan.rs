@@ -0,0 +1,231 @@
+//! Credit loans: the borrower puts up no collateral, guarantors lock collateral on its behalf.
+
+use super::*;
+
+pub type CreditLoanId = u64;
+
+#[derive(Encode, Decode, Eq, PartialEq, Copy, Clone, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub enum CreditLoanStatus {
+	// collecting guarantees, nothing drawn yet
+	Requested,
+	// drawn, due at the unix time in seconds
+	Active(u64),
+}
+
+#[derive(Encode, Decode, Eq, PartialEq, Clone, RuntimeDebug)]
+pub struct CreditLoan<AccountId> {
+	pub borrower: AccountId,
+
+	// asset pool lending the loan, guarantees are locked in the same currency
+	pub currency_id: CurrencyId,
+
+	// maximum real debit the borrower can draw
+	pub limit: Balance,
+
+	// seconds from the first draw until the loan is due
+	pub term: u64,
+
+	// real debit: debit * debit_rate of the asset pool
+	pub debit: Balance,
+
+	// sum of the guarantees of all guarantors
+	pub guaranteed: Balance,
+
+	pub status: CreditLoanStatus,
+}
+
+impl<T: Config> Pallet<T> {
+	/// Holds the guarantees, apart from the liquidity of the asset pools.
+	pub fn guarantee_account_id() -> T::AccountId {
+		T::ModuleId::get().into_sub_account(b"guarantee")
+	}
+
+	/// Open a credit loan of at most `limit`, due `term` seconds after the first draw.
+	#[transactional]
+	pub fn request_credit_loan(
+		borrower: &T::AccountId,
+		currency_id: CurrencyId,
+		limit: Balance,
+		term: u64,
+	) -> result::Result<CreditLoanId, DispatchError> {
+		ensure!(Self::is_listed(currency_id), Error::<T>::AssetPoolNotListed);
+		ensure!(
+			!term.is_zero() && !limit.is_zero() && limit >= Self::asset_pool_infos(currency_id).minimum_debit,
+			Error::<T>::InvalidCreditLoanTerms
+		);
+
+		let loan_id = NextCreditLoanId::<T>::try_mutate(|next_id| -> result::Result<CreditLoanId, DispatchError> {
+			let loan_id = *next_id;
+			*next_id = next_id.checked_add(1).ok_or(Error::<T>::NoAvailableCreditLoanId)?;
+			Ok(loan_id)
+		})?;
+		CreditLoans::<T>::insert(loan_id, CreditLoan {
+			borrower: borrower.clone(),
+			currency_id,
+			limit,
+			term,
+			debit: Zero::zero(),
+			guaranteed: Zero::zero(),
+			status: CreditLoanStatus::Requested,
+		});
+
+		Self::deposit_event(Event::CreditLoanRequested(loan_id, borrower.clone(), currency_id, limit, term));
+		Ok(loan_id)
+	}
+
+	/// Lock `amount` of the loan currency of `guarantor` behind the credit loan.
+	#[transactional]
+	pub fn guarantee_credit_loan(guarantor: &T::AccountId, loan_id: CreditLoanId, amount: Balance) -> DispatchResult {
+		ensure!(!amount.is_zero(), Error::<T>::NoGuarantee);
+		CreditLoans::<T>::try_mutate(loan_id, |maybe_loan| -> DispatchResult {
+			let loan = maybe_loan.as_mut().ok_or(Error::<T>::CreditLoanNotFound)?;
+			loan.guaranteed = loan.guaranteed.checked_add(amount).ok_or(Error::<T>::DepositOverflow)?;
+			Guarantees::<T>::mutate(loan_id, guarantor, |guarantee| *guarantee = guarantee.saturating_add(amount));
+			T::Currency::transfer(loan.currency_id, guarantor, &Self::guarantee_account_id(), amount)?;
+
+			Self::deposit_event(Event::CreditLoanGuaranteed(loan_id, guarantor.clone(), amount));
+			Ok(())
+		})
+	}
+
+	/// Take back the whole guarantee, only before the loan is drawn. Returns the unlocked amount.
+	#[transactional]
+	pub fn withdraw_guarantee(guarantor: &T::AccountId, loan_id: CreditLoanId) -> result::Result<Balance, DispatchError> {
+		CreditLoans::<T>::try_mutate(loan_id, |maybe_loan| -> result::Result<Balance, DispatchError> {
+			let loan = maybe_loan.as_mut().ok_or(Error::<T>::CreditLoanNotFound)?;
+			ensure!(loan.status == CreditLoanStatus::Requested, Error::<T>::GuaranteeLocked);
+
+			let amount = Guarantees::<T>::take(loan_id, guarantor);
+			ensure!(!amount.is_zero(), Error::<T>::NoGuarantee);
+			loan.guaranteed = loan.guaranteed.saturating_sub(amount);
+			T::Currency::transfer(loan.currency_id, &Self::guarantee_account_id(), guarantor, amount)?;
+
+			Self::deposit_event(Event::GuaranteeWithdrawn(loan_id, guarantor.clone(), amount));
+			Ok(amount)
+		})
+	}
+
+	/// Borrow `amount` from the asset pool, the first draw starts the term.
+	/// The guarantees must cover the whole limit.
+	#[transactional]
+	pub fn draw_credit_loan(borrower: &T::AccountId, loan_id: CreditLoanId, amount: Balance) -> DispatchResult {
+		let mut loan = Self::credit_loans(loan_id).ok_or(Error::<T>::CreditLoanNotFound)?;
+		ensure!(loan.borrower == *borrower, Error::<T>::NotCreditLoanBorrower);
+		ensure!(Self::is_listed(loan.currency_id), Error::<T>::AssetPoolNotListed);
+		Self::ensure_not_paused(loan.currency_id, AssetPoolOperation::Borrow)?;
+		ensure!(loan.guaranteed >= loan.limit, Error::<T>::InsufficientGuarantee);
+
+		let now = T::UnixTime::now().as_secs();
+		match loan.status {
+			CreditLoanStatus::Requested => loan.status = CreditLoanStatus::Active(now.saturating_add(loan.term)),
+			CreditLoanStatus::Active(due) => ensure!(now < due, Error::<T>::CreditLoanOverdue),
+		}
+
+		Self::accrue_interest(loan.currency_id);
+		AssetPoolInfos::<T>::try_mutate(loan.currency_id, |asset_pool_info| -> DispatchResult {
+			Self::update_total_debit(loan.currency_id, asset_pool_info, Self::amount_try_from_balance(amount)?, false)?;
+
+			let increase = asset_pool_info.debit_rate.reciprocal().unwrap_or_default().saturating_mul_int(amount);
+			loan.debit = loan.debit.checked_add(increase).ok_or(Error::<T>::DebitOverflow)?;
+			let debit = asset_pool_info.debit_rate.saturating_mul_int(loan.debit);
+			ensure!(debit <= loan.limit, Error::<T>::CreditLimitExceeded);
+			ensure!(debit >= asset_pool_info.minimum_debit, Error::<T>::DebitTooLow);
+			Ok(())
+		})?;
+
+		T::Currency::transfer(loan.currency_id, &Self::account_id(), borrower, amount)?;
+		CreditLoans::<T>::insert(loan_id, loan);
+
+		Self::deposit_event(Event::CreditLoanDrawn(loan_id, amount));
+		Ok(())
+	}
+
+	/// Repay `amount` of the credit loan with funds of `payer`.
+	#[transactional]
+	pub fn repay_credit_loan(payer: &T::AccountId, loan_id: CreditLoanId, amount: Balance) -> DispatchResult {
+		let mut loan = Self::credit_loans(loan_id).ok_or(Error::<T>::CreditLoanNotFound)?;
+		Self::ensure_not_paused(loan.currency_id, AssetPoolOperation::Repay)?;
+
+		Self::accrue_interest(loan.currency_id);
+		AssetPoolInfos::<T>::try_mutate(loan.currency_id, |asset_pool_info| -> DispatchResult {
+			let repay_all = amount == asset_pool_info.debit_rate.saturating_mul_int(loan.debit);
+			Self::update_total_debit(loan.currency_id, asset_pool_info, Self::amount_try_from_balance(amount)?.saturating_neg(), repay_all)?;
+
+			loan.debit = if repay_all {
+				// repay all, no rounding dust left
+				Zero::zero()
+			} else {
+				let decrease = asset_pool_info.debit_rate.reciprocal().unwrap_or_default().saturating_mul_int(amount);
+				loan.debit.checked_sub(decrease).ok_or(Error::<T>::DebitTooLow)?
+			};
+			let debit = asset_pool_info.debit_rate.saturating_mul_int(loan.debit);
+			ensure!(debit.is_zero() || debit >= asset_pool_info.minimum_debit, Error::<T>::DebitTooLow);
+			Ok(())
+		})?;
+
+		T::Currency::transfer(loan.currency_id, payer, &Self::account_id(), amount)?;
+		CreditLoans::<T>::insert(loan_id, loan);
+
+		Self::deposit_event(Event::CreditLoanRepaid(loan_id, payer.clone(), amount));
+		Ok(())
+	}
+
+	/// Close a repaid or never drawn credit loan and unlock all guarantees.
+	#[transactional]
+	pub fn close_credit_loan(borrower: &T::AccountId, loan_id: CreditLoanId) -> DispatchResult {
+		let loan = Self::credit_loans(loan_id).ok_or(Error::<T>::CreditLoanNotFound)?;
+		ensure!(loan.borrower == *borrower, Error::<T>::NotCreditLoanBorrower);
+		ensure!(loan.debit.is_zero(), Error::<T>::CreditLoanNotRepaid);
+
+		let guarantee_account = Self::guarantee_account_id();
+		for (guarantor, guarantee) in Guarantees::<T>::drain_prefix(loan_id) {
+			T::Currency::transfer(loan.currency_id, &guarantee_account, &guarantor, guarantee)?;
+		}
+		CreditLoans::<T>::remove(loan_id);
+
+		Self::deposit_event(Event::CreditLoanClosed(loan_id));
+		Ok(())
+	}
+
+	/// Settle an overdue credit loan. The guarantors cover the debit in proportion to their
+	/// guarantees, any shortfall is written off against the reserves and depositors.
+	#[transactional]
+	pub fn default_credit_loan(loan_id: CreditLoanId) -> DispatchResult {
+		let loan = Self::credit_loans(loan_id).ok_or(Error::<T>::CreditLoanNotFound)?;
+		let due = match loan.status {
+			CreditLoanStatus::Active(due) => due,
+			CreditLoanStatus::Requested => return Err(Error::<T>::CreditLoanNotActive.into()),
+		};
+		ensure!(T::UnixTime::now().as_secs() >= due, Error::<T>::CreditLoanNotOverdue);
+
+		Self::accrue_interest(loan.currency_id);
+		let debit_rate = Self::asset_pool_infos(loan.currency_id).debit_rate;
+		let debit = debit_rate.saturating_mul_int(loan.debit);
+		let to_cover = debit.min(loan.guaranteed);
+
+		let guarantee_account = Self::guarantee_account_id();
+		let mut covered: Balance = Zero::zero();
+		for (guarantor, guarantee) in Guarantees::<T>::drain_prefix(loan_id) {
+			let share = Ratio::checked_from_rational(guarantee, loan.guaranteed)
+				.unwrap_or_default()
+				.saturating_mul_int(to_cover)
+				.min(guarantee);
+			covered = covered.saturating_add(share);
+			T::Currency::transfer(loan.currency_id, &guarantee_account, &guarantor, guarantee.saturating_sub(share))?;
+		}
+		T::Currency::transfer(loan.currency_id, &guarantee_account, &Self::account_id(), covered)?;
+
+		// the whole loan leaves the pool, what the guarantors could not cover is a loss
+		let shortfall = debit.saturating_sub(covered);
+		AssetPoolInfos::<T>::try_mutate(loan.currency_id, |asset_pool_info| -> DispatchResult {
+			Self::update_total_debit(loan.currency_id, asset_pool_info, Self::amount_try_from_balance(debit)?.saturating_neg(), true)?;
+			Self::write_off(loan.currency_id, asset_pool_info, shortfall);
+			Ok(())
+		})?;
+		CreditLoans::<T>::remove(loan_id);
+
+		Self::deposit_event(Event::CreditLoanDefaulted(loan_id, covered, shortfall));
+		Ok(())
+	}
+}
@@ -0,0 +1,166 @@
+//! Fixed-term loans: the rate is locked at origination and a penalty rate is added once overdue.
+
+use super::*;
+
+#[derive(Encode, Decode, Eq, PartialEq, Clone, RuntimeDebug, Default)]
+pub struct FixedTermLoan {
+	// outstanding principal, lent outside the variable rate total_debit
+	pub principal: Balance,
+
+	// principal plus interest accrued up to `accrued_at`
+	pub debit: Balance,
+
+	// debit apy locked at origination
+	pub rate: Ratio,
+
+	// unix time in seconds
+	pub maturity: u64,
+
+	pub accrued_at: u64,
+}
+
+impl<T: Config> Pallet<T> {
+	/// Debit of the fixed-term loan with interest accrued up to now, the overdue period
+	/// accrues at the locked rate plus `T::OverduePenaltyRate`.
+	pub fn current_fixed_term_debit(loan: &FixedTermLoan) -> Balance {
+		let (interest, penalty) = Self::fixed_term_interest(loan);
+		loan.debit.saturating_add(interest).saturating_add(penalty)
+	}
+
+	/// Interest at the locked rate and the overdue penalty of the fixed-term loan since
+	/// `accrued_at`. The asset pool accrues the former over time, the penalty is booked once the
+	/// loan is touched.
+	fn fixed_term_interest(loan: &FixedTermLoan) -> (Balance, Balance) {
+		let now = T::UnixTime::now().as_secs();
+		if now <= loan.accrued_at {
+			return (Zero::zero(), Zero::zero());
+		}
+
+		let overdue_start = loan.maturity.max(loan.accrued_at);
+		let overdue = now.saturating_sub(overdue_start);
+		let interest = Ratio::saturating_from_rational(now - loan.accrued_at, SECONDS_PER_YEAR)
+			.saturating_mul(loan.rate)
+			.saturating_mul_int(loan.debit);
+		let penalty = Ratio::saturating_from_rational(overdue, SECONDS_PER_YEAR)
+			.saturating_mul(T::OverduePenaltyRate::get())
+			.saturating_mul_int(loan.debit);
+		(interest, penalty)
+	}
+
+	/// Whether the fixed-term loan is past maturity and the grace period.
+	pub fn is_fixed_term_loan_overdue(asset_pool_id: CurrencyId, who: &T::AccountId) -> bool {
+		Self::fixed_term_loans(asset_pool_id, who).map_or(false, |loan| {
+			T::UnixTime::now().as_secs() > loan.maturity.saturating_add(T::OverdueGracePeriod::get())
+		})
+	}
+
+	/// Borrow `amount` at the current debit apy of the asset pool, locked for `term` seconds.
+	#[transactional]
+	pub fn borrow_fixed_term(who: &T::AccountId, asset_pool_id: CurrencyId, amount: Balance, term: u64) -> DispatchResult {
+		ensure!(Self::is_listed(asset_pool_id), Error::<T>::AssetPoolNotListed);
+		Self::ensure_not_paused(asset_pool_id, AssetPoolOperation::Borrow)?;
+		ensure!(!term.is_zero(), Error::<T>::InvalidFixedTerm);
+		ensure!(!FixedTermLoans::<T>::contains_key(asset_pool_id, who), Error::<T>::FixedTermLoanExists);
+
+		Self::accrue_interest(asset_pool_id);
+		let asset_pool_info = Self::asset_pool_infos(asset_pool_id);
+		ensure!(amount >= asset_pool_info.minimum_debit, Error::<T>::DebitTooLow);
+		let total_fixed_term_debit = Self::total_fixed_term_debits(asset_pool_id)
+			.checked_add(amount)
+			.ok_or(Error::<T>::DebitOverflow)?;
+		let lent = asset_pool_info.total_debit.saturating_add(total_fixed_term_debit);
+		Self::ensure_lendable(&asset_pool_info, lent)?;
+
+		let now = T::UnixTime::now().as_secs();
+		let utilisation_rate = Ratio::saturating_from_rational(lent, asset_pool_info.total_deposit);
+		let loan = FixedTermLoan {
+			principal: amount,
+			debit: amount,
+			rate: Self::calculate_debit_apy(utilisation_rate, asset_pool_info.interest_model),
+			maturity: now.saturating_add(term),
+			accrued_at: now,
+		};
+		TotalFixedTermDebits::<T>::insert(asset_pool_id, total_fixed_term_debit);
+		FixedTermYearlyInterests::<T>::mutate(asset_pool_id, |total| {
+			*total = total.saturating_add(loan.rate.saturating_mul_int(loan.debit))
+		});
+		FixedTermLoans::<T>::insert(asset_pool_id, who, loan.clone());
+
+		T::Currency::transfer(asset_pool_id, &Self::account_id(), who, amount)?;
+		Self::ensure_account_healthy(who)?;
+
+		Self::deposit_event(Event::FixedTermLoanOpened(who.clone(), asset_pool_id, amount, loan.rate, loan.maturity));
+		Ok(())
+	}
+
+	/// Repay `amount` of the fixed-term loan, accrued interest first.
+	#[transactional]
+	pub fn repay_fixed_term(who: &T::AccountId, asset_pool_id: CurrencyId, amount: Balance) -> DispatchResult {
+		Self::ensure_not_paused(asset_pool_id, AssetPoolOperation::Repay)?;
+		Self::_repay_fixed_term(who, asset_pool_id, amount)?;
+		T::Currency::transfer(asset_pool_id, who, &Self::account_id(), amount)?;
+
+		Self::deposit_event(Event::FixedTermLoanRepaid(who.clone(), asset_pool_id, amount));
+		Ok(())
+	}
+
+	/// Book a repayment whose funds are already in the module account.
+	pub(crate) fn _repay_fixed_term(who: &T::AccountId, asset_pool_id: CurrencyId, amount: Balance) -> DispatchResult {
+		let mut loan = Self::fixed_term_loans(asset_pool_id, who).ok_or(Error::<T>::NoDebit)?;
+		let (interest, penalty) = Self::fixed_term_interest(&loan);
+		let debit = loan.debit.saturating_add(interest).saturating_add(penalty);
+		ensure!(amount <= debit, Error::<T>::DebitTooLow);
+
+		let interest_paid = amount.min(debit.saturating_sub(loan.principal));
+		let principal_paid = amount.saturating_sub(interest_paid);
+
+		// the asset pool accrued the interest at the locked rate already, the penalty is booked now
+		Self::accrue_interest(asset_pool_id);
+		Self::distribute_fixed_term_interest(asset_pool_id, penalty);
+		let repay_all = amount == debit;
+		TotalFixedTermDebits::<T>::try_mutate(asset_pool_id, |total| -> DispatchResult {
+			*total = Self::sub_from_total(total.saturating_add(penalty), amount, repay_all)
+				.ok_or(Error::<T>::DebitTooLow)?;
+			Ok(())
+		})?;
+
+		let new_debit = debit.saturating_sub(amount);
+		FixedTermYearlyInterests::<T>::mutate(asset_pool_id, |total| {
+			*total = total
+				.saturating_sub(loan.rate.saturating_mul_int(loan.debit))
+				.saturating_add(loan.rate.saturating_mul_int(new_debit))
+		});
+
+		loan.principal = loan.principal.saturating_sub(principal_paid);
+		loan.debit = new_debit;
+		loan.accrued_at = T::UnixTime::now().as_secs();
+		if loan.debit.is_zero() {
+			FixedTermLoans::<T>::remove(asset_pool_id, who);
+		} else {
+			FixedTermLoans::<T>::insert(asset_pool_id, who, loan);
+		}
+		Ok(())
+	}
+
+	// the overdue penalty is shared between reserves and depositors like the interest
+	fn distribute_fixed_term_interest(asset_pool_id: CurrencyId, interest: Balance) {
+		if interest.is_zero() {
+			return;
+		}
+
+		let reserves = AssetPoolInfos::<T>::mutate(asset_pool_id, |asset_pool_info| {
+			let reserves = asset_pool_info.reserve_factor.saturating_mul_int(interest);
+			if asset_pool_info.total_deposit.is_zero() {
+				return interest;
+			}
+
+			let deposit_interest = interest.saturating_sub(reserves);
+			let deposit_increment = Ratio::saturating_from_rational(deposit_interest, asset_pool_info.total_deposit);
+			asset_pool_info.deposit_rate = asset_pool_info.deposit_rate
+				.saturating_mul(Ratio::one().saturating_add(deposit_increment));
+			asset_pool_info.total_deposit = asset_pool_info.total_deposit.saturating_add(deposit_interest);
+			reserves
+		});
+		TotalReserves::<T>::mutate(asset_pool_id, |total_reserves| *total_reserves = total_reserves.saturating_add(reserves));
+	}
+}
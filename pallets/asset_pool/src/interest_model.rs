@@ -0,0 +1,91 @@
+use codec::{Decode, Encode};
+#[cfg(feature = "std")]
+use serde::{Deserialize, Serialize};
+use sp_runtime::{RuntimeDebug, traits::{One, Saturating, Zero}};
+
+use model::Ratio;
+
+/// Debit apy of an asset pool as a function of its utilisation rate.
+pub trait InterestRateModel {
+	fn debit_apy(&self, utilisation_rate: Ratio) -> Ratio;
+
+	/// Whether the params can be used for `debit_apy`.
+	fn is_valid(&self) -> bool {
+		true
+	}
+}
+
+/// Jump rate model: `slope_1` up to `critical_point`, the steeper `slope_2` above it.
+#[derive(Encode, Decode, Eq, PartialEq, Copy, Clone, RuntimeDebug, Default)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct InterestInfo {
+	pub critical_point: Ratio,
+	pub base: Ratio,
+	pub slope_1: Ratio,
+	pub slope_2: Ratio,
+}
+
+impl InterestRateModel for InterestInfo {
+	fn debit_apy(&self, utilisation_rate: Ratio) -> Ratio {
+		let critical_point = self.critical_point;
+		if utilisation_rate < critical_point {
+			self.base + (self.slope_1) * (utilisation_rate / critical_point)
+		} else {
+			self.base + self.slope_1 + self.slope_2 *
+				((utilisation_rate - critical_point) / (Ratio::from(1) - critical_point))
+		}
+	}
+
+	// debit_apy divides by critical_point and by (1 - critical_point)
+	fn is_valid(&self) -> bool {
+		!self.critical_point.is_zero() && self.critical_point < Ratio::one()
+	}
+}
+
+/// Linear model: `base + slope * utilisation_rate`.
+#[derive(Encode, Decode, Eq, PartialEq, Copy, Clone, RuntimeDebug, Default)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct LinearInterest {
+	pub base: Ratio,
+	pub slope: Ratio,
+}
+
+impl InterestRateModel for LinearInterest {
+	fn debit_apy(&self, utilisation_rate: Ratio) -> Ratio {
+		self.base.saturating_add(self.slope.saturating_mul(utilisation_rate))
+	}
+}
+
+/// Interest rate model of an asset pool.
+#[derive(Encode, Decode, Eq, PartialEq, Copy, Clone, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub enum InterestModel {
+	JumpRate(InterestInfo),
+	Linear(LinearInterest),
+	// same debit apy at any utilisation, e.g. for stablecoin pools
+	Fixed(Ratio),
+}
+
+impl Default for InterestModel {
+	fn default() -> Self {
+		InterestModel::JumpRate(Default::default())
+	}
+}
+
+impl InterestRateModel for InterestModel {
+	fn debit_apy(&self, utilisation_rate: Ratio) -> Ratio {
+		match self {
+			InterestModel::JumpRate(model) => model.debit_apy(utilisation_rate),
+			InterestModel::Linear(model) => model.debit_apy(utilisation_rate),
+			InterestModel::Fixed(debit_apy) => *debit_apy,
+		}
+	}
+
+	fn is_valid(&self) -> bool {
+		match self {
+			InterestModel::JumpRate(model) => model.is_valid(),
+			InterestModel::Linear(model) => model.is_valid(),
+			InterestModel::Fixed(_) => true,
+		}
+	}
+}
@@ -0,0 +1,95 @@
+//! Leveraged debit: lent to traders through the dex, backed by the position collateral the dex
+//! holds in custody. It is kept apart from `LoanInfos` and never counted in account health.
+
+use super::*;
+
+impl<T: Config> Pallet<T> {
+	/// Borrow (positive adjustment) or repay (negative adjustment) leveraged debit of `who`.
+	#[transactional]
+	pub fn update_leverage_debit(who: &T::AccountId, asset_pool_id: CurrencyId, debit_adjustment: Amount) -> DispatchResult {
+		if debit_adjustment.is_positive() {
+			ensure!(Self::is_listed(asset_pool_id), Error::<T>::AssetPoolNotListed);
+			Self::ensure_not_paused(asset_pool_id, AssetPoolOperation::Borrow)?;
+		} else if debit_adjustment.is_negative() {
+			Self::ensure_not_paused(asset_pool_id, AssetPoolOperation::Repay)?;
+		}
+
+		Self::accrue_interest(asset_pool_id);
+		AssetPoolInfos::<T>::try_mutate(asset_pool_id, |asset_pool_info| -> DispatchResult {
+			Self::_update_leverage_debit(who, asset_pool_id, asset_pool_info, debit_adjustment)
+		})?;
+
+		let debit_balance_adjustment = Self::balance_try_from_amount_abs(debit_adjustment)?;
+		let module_account = Self::account_id();
+		if debit_adjustment.is_positive() {
+			T::Currency::transfer(asset_pool_id, &module_account, who, debit_balance_adjustment)?;
+		} else if debit_adjustment.is_negative() {
+			T::Currency::transfer(asset_pool_id, who, &module_account, debit_balance_adjustment)?;
+		}
+
+		Self::deposit_event(Event::LeverageDebitUpdated(who.clone(), asset_pool_id, debit_adjustment));
+		Ok(())
+	}
+
+	/// Drop `amount` of leveraged debit of `who` left unpaid after its position was liquidated.
+	#[transactional]
+	pub fn write_off_leverage_debit(who: &T::AccountId, asset_pool_id: CurrencyId, amount: Balance) -> DispatchResult {
+		Self::accrue_interest(asset_pool_id);
+		AssetPoolInfos::<T>::try_mutate(asset_pool_id, |asset_pool_info| -> DispatchResult {
+			Self::_update_leverage_debit(who, asset_pool_id, asset_pool_info, Self::amount_try_from_balance(amount)?.saturating_neg())?;
+			Self::write_off(asset_pool_id, asset_pool_info, amount);
+			Ok(())
+		})?;
+
+		Self::deposit_event(Event::DebitWrittenOff(asset_pool_id, amount));
+		Ok(())
+	}
+
+	fn _update_leverage_debit(
+		who: &T::AccountId,
+		asset_pool_id: CurrencyId,
+		asset_pool_info: &mut AssetPoolInfo,
+		debit_adjustment: Amount,
+	) -> DispatchResult {
+		let debit_balance = Self::balance_try_from_amount_abs(debit_adjustment)?;
+		let repay_all = debit_adjustment.is_negative()
+			&& debit_balance == asset_pool_info.debit_rate.saturating_mul_int(Self::leverage_debits(asset_pool_id, who));
+		Self::update_total_debit(asset_pool_id, asset_pool_info, debit_adjustment, repay_all)?;
+
+		LeverageDebits::<T>::try_mutate_exists(asset_pool_id, who, |maybe_debit| -> DispatchResult {
+			let debit = maybe_debit.unwrap_or_default();
+			let new_debit = if debit_adjustment.is_positive() {
+				let increase = asset_pool_info.debit_rate.reciprocal().unwrap_or_default().saturating_mul_int(debit_balance);
+				debit.checked_add(increase).ok_or(Error::<T>::DebitOverflow)?
+			} else if debit_balance == asset_pool_info.debit_rate.saturating_mul_int(debit) {
+				// repay all, no rounding dust left
+				Zero::zero()
+			} else {
+				let decrease = asset_pool_info.debit_rate.reciprocal().unwrap_or_default().saturating_mul_int(debit_balance);
+				debit.checked_sub(decrease).ok_or(Error::<T>::DebitTooLow)?
+			};
+
+			*maybe_debit = if new_debit.is_zero() { None } else { Some(new_debit) };
+			Ok(())
+		})
+	}
+
+	/// Book `loss` of lent funds that will never be repaid. Reserves absorb it first, the rest is
+	/// shared by the depositors through the deposit rate.
+	pub(crate) fn write_off(asset_pool_id: CurrencyId, asset_pool_info: &mut AssetPoolInfo, loss: Balance) {
+		let from_reserves = TotalReserves::<T>::mutate(asset_pool_id, |total_reserves| {
+			let from_reserves = loss.min(*total_reserves);
+			*total_reserves = total_reserves.saturating_sub(from_reserves);
+			from_reserves
+		});
+
+		let total_deposit = asset_pool_info.total_deposit;
+		let from_deposits = loss.saturating_sub(from_reserves).min(total_deposit);
+		if !from_deposits.is_zero() {
+			let deposit_decrement = Ratio::saturating_from_rational(from_deposits, total_deposit);
+			asset_pool_info.deposit_rate = asset_pool_info.deposit_rate
+				.saturating_mul(Ratio::one().saturating_sub(deposit_decrement));
+			asset_pool_info.total_deposit = total_deposit.saturating_sub(from_deposits);
+		}
+	}
+}
@@ -2,7 +2,7 @@
 #![allow(clippy::unused_unit)]
 #![allow(clippy::upper_case_acronyms)]
 
-use frame_support::{pallet_prelude::*, transactional};
+use frame_support::{debug, pallet_prelude::*, traits::UnixTime, transactional};
 use orml_traits::{Happened, MultiCurrency, MultiCurrencyExtended};
 use orml_utilities::{IterableStorageDoubleMapExtended, OffchainErr};
 #[cfg(feature = "std")]
@@ -18,28 +18,42 @@ use sp_runtime::{
 		storage_lock::{StorageLock, Time},
 		Duration,
 	},
-	DispatchResult, RandomNumberGenerator,
+	DispatchError, DispatchResult, RandomNumberGenerator,
 	FixedPointNumber, ModuleId, RuntimeDebug,
-	traits::{AccountIdConversion, Saturating, Zero, StaticLookup, BlakeTwo256, Hash},
+	traits::{AccountIdConversion, CheckedDiv, One, Saturating, Zero, StaticLookup, BlakeTwo256, Hash},
 	transaction_validity::{
 		InvalidTransaction, TransactionPriority, TransactionSource, TransactionValidity, ValidTransaction,
 	},
 };
 use sp_std::{convert::TryInto, result, vec::Vec};
 
-use model::{Amount, Balance, CurrencyId, Ratio};
+use model::{Amount, AssetPoolManager, Balance, CurrencyId, DEXManager, FlashLoanReceiver, Price, PriceOrigin, PriceProvider, Ratio};
 
 pub use module::*;
-
+pub use interest_model::{InterestInfo, InterestModel, InterestRateModel, LinearInterest};
+pub use credit_loan::{CreditLoan, CreditLoanId, CreditLoanStatus};
+pub use fixed_term::FixedTermLoan;
+
+pub mod interest_model;
+mod credit_loan;
+mod fixed_term;
+mod leverage;
+pub mod migrations;
 mod mock;
 mod test;
 
 
 pub const OFFCHAIN_WORKER_DATA: &[u8] = b"antimatter/liquidation/data/";
+pub const OFFCHAIN_WORKER_FIXED_TERM_DATA: &[u8] = b"antimatter/liquidation/fixed-term-data/";
 pub const OFFCHAIN_WORKER_LOCK: &[u8] = b"antimatter/liquidation/lock/";
 pub const OFFCHAIN_WORKER_MAX_ITERATIONS: &[u8] = b"antimatter/liquidation/max-iterations/";
 pub const LOCK_DURATION: u64 = 100;
 pub const DEFAULT_MAX_ITERATIONS: u32 = 1000;
+pub const SECONDS_PER_YEAR: u64 = 365 * 24 * 60 * 60;
+
+/// What a flash loan borrower asks `Config::FlashLoanReceiver` to do with the funds.
+pub type FlashLoanPayloadOf<T> =
+	<<T as Config>::FlashLoanReceiver as FlashLoanReceiver<<T as frame_system::Config>::AccountId>>::Payload;
 
 
 #[derive(Encode, Decode, Eq, PartialEq, Copy, Clone, RuntimeDebug, Default)]
@@ -52,15 +66,6 @@ pub struct LoanInfo {
 	pub debit: Balance,
 }
 
-#[derive(Encode, Decode, Eq, PartialEq, Copy, Clone, RuntimeDebug, Default)]
-#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
-pub struct InterestInfo {
-	pub critical_point: Ratio,
-	pub base: Ratio,
-	pub slope_1: Ratio,
-	pub slope_2: Ratio,
-}
-
 #[derive(Encode, Decode, Clone, RuntimeDebug, PartialEq, Eq, Default)]
 #[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
 pub struct AssetPoolInfo {
@@ -70,11 +75,26 @@ pub struct AssetPoolInfo {
 
 	pub minimum_debit: Balance,
 
-	// health ratio > liquidation ratio
-	pub health_ratio: Ratio,
+	// maximum total_deposit, 0 for no cap
+	pub supply_cap: Balance,
 
+	// maximum total_debit, 0 for no cap
+	pub borrow_cap: Balance,
+
+	// borrow limit: deposit value * collateral_factor
+	pub collateral_factor: Ratio,
+
+	// loans are liquidated once debit value > deposit value * liquidation_threshold
+	// collateral_factor <= liquidation_threshold <= 1
+	pub liquidation_threshold: Ratio,
+
+	// extra collateral seized on top of the repaid debit
+	pub liquidation_bonus: Ratio,
+
+	// real deposits, including the depositors' share of accrued interest
 	pub total_deposit: Balance,
 
+	// real debits, including accrued interest
 	pub total_debit: Balance,
 
 	pub deposit_rate: Ratio,
@@ -85,9 +105,44 @@ pub struct AssetPoolInfo {
 
 	pub debit_apy: Ratio,
 
+	// share of the interest paid by borrowers kept as protocol reserves
 	pub reserve_factor: Ratio,
 
-	pub interest_info: InterestInfo,
+	pub interest_model: InterestModel,
+}
+
+#[derive(Encode, Decode, Eq, PartialEq, Copy, Clone, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub enum AssetPoolStatus {
+	Listed,
+	// no new deposits or debits, withdraw, repay and liquidation keep working
+	Delisted,
+}
+
+/// Storage layout versions of the pallet, used by the runtime upgrade migrations.
+#[derive(Encode, Decode, Eq, PartialEq, Copy, Clone, RuntimeDebug)]
+pub enum Releases {
+	// asset pools from the AssetPoolIds constant, health_ratio and a jump rate interest_info
+	V1_0_0,
+	// asset pools listed on chain, collateral params, caps and a selectable interest_model
+	V2_0_0,
+}
+
+impl Default for Releases {
+	fn default() -> Self {
+		Releases::V1_0_0
+	}
+}
+
+/// Operations of an asset pool that can be paused independently.
+#[derive(Encode, Decode, Eq, PartialEq, Copy, Clone, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub enum AssetPoolOperation {
+	Deposit,
+	Withdraw,
+	Borrow,
+	Repay,
+	Liquidate,
 }
 
 #[frame_support::pallet]
@@ -103,26 +158,125 @@ pub mod module {
 		#[pallet::constant]
 		type ModuleId: Get<ModuleId>;
 
+		type UnixTime: UnixTime;
+
+		type OnUpdateLoan: Happened<(Self::AccountId, CurrencyId, Amount, Balance)>;
+
+		type PriceSource: PriceProvider;
+
+		type DEX: DEXManager<Self::AccountId>;
+
+		/// Receives the liquidation bonus of liquidated loans.
 		#[pallet::constant]
-		type AssetPoolIds: Get<Vec<CurrencyId>>;
+		type LiquidationPenaltyReceiver: Get<Self::AccountId>;
+
+		type UpdateOrigin: EnsureOrigin<Self::Origin>;
+
+		/// Runs the borrower's code while it holds a flash loan.
+		type FlashLoanReceiver: FlashLoanReceiver<Self::AccountId>;
 
+		/// Fee on flash loans, below 100% and rounded up, paid into the pool reserves.
 		#[pallet::constant]
-		type BlockPercentEachYear: Get<Ratio>;
+		type FlashLoanFee: Get<Ratio>;
 
-		type OnUpdateLoan: Happened<(Self::AccountId, CurrencyId, Amount, Balance)>;
+		/// Added to the locked rate of fixed-term loans once they are overdue.
+		#[pallet::constant]
+		type OverduePenaltyRate: Get<Ratio>;
+
+		/// Seconds after maturity before an unpaid fixed-term loan can be liquidated.
+		#[pallet::constant]
+		type OverdueGracePeriod: Get<u64>;
+
+		/// The share of the variable debit a single liquidation repays at most.
+		#[pallet::constant]
+		type LiquidationCloseFactor: Get<Ratio>;
+
+		/// The liquidation bonus of an asset pool can't be set above this.
+		#[pallet::constant]
+		type MaxLiquidationBonus: Get<Ratio>;
 
 		#[pallet::constant]
 		type UnsignedPriority: Get<TransactionPriority>;
 	}
 
+	/// All asset pools, listed or delisted.
+	#[pallet::storage]
+	#[pallet::getter(fn asset_pool_ids)]
+	pub type AssetPoolIds<T: Config> = StorageValue<_, Vec<CurrencyId>, ValueQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn asset_pool_statuses)]
+	pub type AssetPoolStatuses<T: Config> = StorageMap<_, Twox64Concat, CurrencyId, AssetPoolStatus, OptionQuery>;
+
 	#[pallet::storage]
 	#[pallet::getter(fn asset_pool_infos)]
 	pub type AssetPoolInfos<T: Config> = StorageMap<_, Twox64Concat, CurrencyId, AssetPoolInfo, ValueQuery>;
 
+	/// Protocol reserves accumulated from the reserve_factor share of interest.
+	#[pallet::storage]
+	#[pallet::getter(fn total_reserves)]
+	pub type TotalReserves<T: Config> = StorageMap<_, Twox64Concat, CurrencyId, Balance, ValueQuery>;
+
+	/// Unix time in seconds when interest of the asset pool was last accrued.
+	#[pallet::storage]
+	#[pallet::getter(fn last_accrued_timestamps)]
+	pub type LastAccruedTimestamps<T: Config> = StorageMap<_, Twox64Concat, CurrencyId, u64, OptionQuery>;
+
 	#[pallet::storage]
 	#[pallet::getter(fn loans)]
 	pub type LoanInfos<T: Config> = StorageDoubleMap<_, Twox64Concat, CurrencyId, Twox64Concat, T::AccountId, LoanInfo, ValueQuery>;
 
+	/// Deposits only back debit once the account opted in to use them as collateral.
+	#[pallet::storage]
+	#[pallet::getter(fn collaterals)]
+	pub type Collaterals<T: Config> = StorageDoubleMap<_, Twox64Concat, T::AccountId, Twox64Concat, CurrencyId, bool, ValueQuery>;
+
+	/// Paused operations of each asset pool.
+	#[pallet::storage]
+	#[pallet::getter(fn paused_operations)]
+	pub type PausedOperations<T: Config> = StorageDoubleMap<_, Twox64Concat, CurrencyId, Twox64Concat, AssetPoolOperation, bool, ValueQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn fixed_term_loans)]
+	pub type FixedTermLoans<T: Config> = StorageDoubleMap<_, Twox64Concat, CurrencyId, Twox64Concat, T::AccountId, FixedTermLoan, OptionQuery>;
+
+	/// Outstanding debit of the fixed-term loans of each asset pool, principal plus interest
+	/// accrued at the locked rates.
+	#[pallet::storage]
+	#[pallet::getter(fn total_fixed_term_debits)]
+	pub type TotalFixedTermDebits<T: Config> = StorageMap<_, Twox64Concat, CurrencyId, Balance, ValueQuery>;
+
+	/// Yearly interest of the fixed-term loans of each asset pool at their locked rates, the sum
+	/// of debit * rate. Lets the pool accrue fixed-term interest without visiting every loan.
+	#[pallet::storage]
+	#[pallet::getter(fn fixed_term_yearly_interests)]
+	pub type FixedTermYearlyInterests<T: Config> = StorageMap<_, Twox64Concat, CurrencyId, Balance, ValueQuery>;
+
+	/// Debit borrowed by the dex for leveraged positions, kept apart from `LoanInfos` as it is
+	/// backed by the position collateral instead of deposits.
+	/// real debit: debit * debit_rate
+	#[pallet::storage]
+	#[pallet::getter(fn leverage_debits)]
+	pub type LeverageDebits<T: Config> = StorageDoubleMap<_, Twox64Concat, CurrencyId, Twox64Concat, T::AccountId, Balance, ValueQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn next_credit_loan_id)]
+	pub type NextCreditLoanId<T: Config> = StorageValue<_, CreditLoanId, ValueQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn credit_loans)]
+	pub type CreditLoans<T: Config> = StorageMap<_, Twox64Concat, CreditLoanId, CreditLoan<T::AccountId>, OptionQuery>;
+
+	/// Collateral locked by each guarantor of a credit loan, in the loan currency.
+	#[pallet::storage]
+	#[pallet::getter(fn guarantees)]
+	pub type Guarantees<T: Config> = StorageDoubleMap<_, Twox64Concat, CreditLoanId, Twox64Concat, T::AccountId, Balance, ValueQuery>;
+
+	/// Storage layout version, new chains start at the latest.
+	#[pallet::storage]
+	#[pallet::getter(fn storage_version)]
+	pub type StorageVersion<T: Config> = StorageValue<_, Releases, ValueQuery>;
+
 	#[pallet::pallet]
 	pub struct Pallet<T>(PhantomData<T>);
 
@@ -160,8 +314,10 @@ pub mod module {
 			match call {
 				Call::liquidate(currency_id, who) => {
 					let account = T::Lookup::lookup(who.clone())?;
-					// TODO: check unsafe
-					if !Self::is_debit_unsafe() {
+					// re-check the specific loan, the account may have been topped up or liquidated meanwhile
+					if Self::paused_operations(currency_id, AssetPoolOperation::Liquidate)
+						|| !Self::is_liquidatable(currency_id, &account)
+					{
 						return InvalidTransaction::Stale.into();
 					}
 					ValidTransaction::with_tag_prefix("AssetPoolLiquidationOffchainWorker")
@@ -182,42 +338,31 @@ pub mod module {
 			self.asset_pool_params.iter().for_each(
 				|(asset_pool_id, asset_pool_param)| {
 					AssetPoolInfos::<T>::insert(asset_pool_id, asset_pool_param);
+					AssetPoolIds::<T>::mutate(|asset_pool_ids| if !asset_pool_ids.contains(asset_pool_id) {
+						asset_pool_ids.push(*asset_pool_id);
+					});
+					AssetPoolStatuses::<T>::insert(asset_pool_id, AssetPoolStatus::Listed);
 				}
 			);
+			StorageVersion::<T>::put(Releases::V2_0_0);
 		}
 	}
 
 	#[pallet::hooks]
 	impl<T: Config> Hooks<T::BlockNumber> for Pallet<T> {
-		fn on_finalize(_now: T::BlockNumber) {
-			for asset_pool_id in T::AssetPoolIds::get() {
-				let mut asset_pool_info: AssetPoolInfo = Self::asset_pool_infos(asset_pool_id);
-				let total_debit = asset_pool_info.total_debit;
-				let total_deposit = asset_pool_info.total_deposit;
-				if total_debit.is_zero() || total_deposit.is_zero() {
-					continue;
-				}
-
-				let utilisation_rate = Ratio::saturating_from_rational(total_debit, total_deposit);
-				asset_pool_info.debit_apy = Self::calculate_debit_apy(utilisation_rate, asset_pool_info.interest_info);
-				asset_pool_info.deposit_apy = asset_pool_info.debit_apy
-					.saturating_mul(asset_pool_info.reserve_factor)
-					.saturating_mul(utilisation_rate);
-
-				let loan_increment = T::BlockPercentEachYear::get().saturating_mul(asset_pool_info.debit_apy);
-
-				asset_pool_info.debit_rate = asset_pool_info.debit_rate.saturating_add(loan_increment);
-				asset_pool_info.deposit_rate = asset_pool_info.deposit_rate
-					.saturating_add(loan_increment.saturating_mul(asset_pool_info.reserve_factor));
-
-				AssetPoolInfos::<T>::insert(asset_pool_id, asset_pool_info);
-			}
+		fn on_runtime_upgrade() -> Weight {
+			migrations::migrate_to_v2::<T>()
 		}
 
 		/// Runs after every block. Check debit-ratio and submit unsigned tx to trigger liquidation.
 		fn offchain_worker(now: T::BlockNumber) {
 			if let Err(e) = Self::_offchain_worker() {
-				debug::info!(target: "liquidation off chain worker", "block number:{:?}", now);
+				debug::info!(
+					target: "liquidation off chain worker",
+					"cannot run offchain worker at {:?}: {:?}",
+					now,
+					e,
+				);
 			}
 		}
 
@@ -237,6 +382,197 @@ pub mod module {
 			Self::liquidate_unsafe_debit(who, currency_id)?;
 			Ok(().into())
 		}
+
+		/// List a new asset pool, or list a delisted pool again with new params and its accounting kept.
+		#[pallet::weight(100_000_000)]
+		#[transactional]
+		pub fn list_asset_pool(
+			origin: OriginFor<T>,
+			currency_id: CurrencyId,
+			asset_pool_info: AssetPoolInfo,
+		) -> DispatchResultWithPostInfo {
+			T::UpdateOrigin::ensure_origin(origin)?;
+			Self::ensure_valid_asset_pool_params(&asset_pool_info)?;
+
+			match Self::asset_pool_statuses(currency_id) {
+				Some(AssetPoolStatus::Listed) => return Err(Error::<T>::AssetPoolAlreadyListed.into()),
+				Some(AssetPoolStatus::Delisted) => {
+					// interest so far accrues with the old params
+					Self::accrue_interest(currency_id);
+					AssetPoolInfos::<T>::mutate(currency_id, |info| {
+						*info = AssetPoolInfo {
+							total_deposit: info.total_deposit,
+							total_debit: info.total_debit,
+							deposit_rate: info.deposit_rate,
+							debit_rate: info.debit_rate,
+							deposit_apy: info.deposit_apy,
+							debit_apy: info.debit_apy,
+							..asset_pool_info
+						};
+					});
+				}
+				None => {
+					AssetPoolInfos::<T>::insert(currency_id, AssetPoolInfo {
+						total_deposit: Zero::zero(),
+						total_debit: Zero::zero(),
+						deposit_rate: Ratio::one(),
+						debit_rate: Ratio::one(),
+						deposit_apy: Zero::zero(),
+						debit_apy: Zero::zero(),
+						..asset_pool_info
+					});
+					AssetPoolIds::<T>::append(currency_id);
+					LastAccruedTimestamps::<T>::insert(currency_id, T::UnixTime::now().as_secs());
+				}
+			}
+
+			AssetPoolStatuses::<T>::insert(currency_id, AssetPoolStatus::Listed);
+			Self::deposit_event(Event::AssetPoolListed(currency_id));
+			Ok(().into())
+		}
+
+		#[pallet::weight(100_000_000)]
+		#[transactional]
+		pub fn delist_asset_pool(origin: OriginFor<T>, currency_id: CurrencyId) -> DispatchResultWithPostInfo {
+			T::UpdateOrigin::ensure_origin(origin)?;
+			ensure!(Self::is_listed(currency_id), Error::<T>::AssetPoolNotListed);
+
+			AssetPoolStatuses::<T>::insert(currency_id, AssetPoolStatus::Delisted);
+			Self::deposit_event(Event::AssetPoolDelisted(currency_id));
+			Ok(().into())
+		}
+
+		/// Emergency brake, pause or resume one operation of an asset pool.
+		#[pallet::weight(100_000_000)]
+		#[transactional]
+		pub fn set_operation_paused(
+			origin: OriginFor<T>,
+			currency_id: CurrencyId,
+			operation: AssetPoolOperation,
+			paused: bool,
+		) -> DispatchResultWithPostInfo {
+			T::UpdateOrigin::ensure_origin(origin)?;
+			ensure!(Self::asset_pool_ids().contains(&currency_id), Error::<T>::InvalidAssetPool);
+
+			if paused {
+				PausedOperations::<T>::insert(currency_id, operation, true);
+			} else {
+				PausedOperations::<T>::remove(currency_id, operation);
+			}
+			Self::deposit_event(Event::OperationPausedUpdated(currency_id, operation, paused));
+			Ok(().into())
+		}
+
+		/// Move protocol reserves out of the module account, depositor funds are never touched.
+		/// Reserves are only booked, so no more than the cash not lent out can be withdrawn.
+		#[pallet::weight(100_000_000)]
+		#[transactional]
+		pub fn withdraw_reserves(
+			origin: OriginFor<T>,
+			currency_id: CurrencyId,
+			#[pallet::compact] amount: Balance,
+			dest: <T::Lookup as StaticLookup>::Source,
+		) -> DispatchResultWithPostInfo {
+			T::UpdateOrigin::ensure_origin(origin)?;
+			let dest = T::Lookup::lookup(dest)?;
+
+			Self::accrue_interest(currency_id);
+			let asset_pool_info = Self::asset_pool_infos(currency_id);
+			let lent = asset_pool_info.total_debit.saturating_add(Self::total_fixed_term_debits(currency_id));
+			TotalReserves::<T>::try_mutate(currency_id, |total_reserves| -> DispatchResult {
+				let free_cash = asset_pool_info.total_deposit.saturating_add(*total_reserves).saturating_sub(lent);
+				*total_reserves = total_reserves.checked_sub(amount).ok_or(Error::<T>::InsufficientReserves)?;
+				ensure!(amount <= free_cash, Error::<T>::InsufficientCash);
+				Ok(())
+			})?;
+			T::Currency::transfer(currency_id, &Self::account_id(), &dest, amount)?;
+
+			Self::deposit_event(Event::ReservesWithdrawn(currency_id, amount, dest));
+			Ok(().into())
+		}
+
+		#[pallet::weight(100_000_000)]
+		#[transactional]
+		#[allow(clippy::too_many_arguments)]
+		pub fn update_asset_pool_params(
+			origin: OriginFor<T>,
+			currency_id: CurrencyId,
+			maximum_total_debit_ratio: Option<Ratio>,
+			minimum_deposit: Option<Balance>,
+			minimum_debit: Option<Balance>,
+			supply_cap: Option<Balance>,
+			borrow_cap: Option<Balance>,
+			reserve_factor: Option<Ratio>,
+			interest_model: Option<InterestModel>,
+		) -> DispatchResultWithPostInfo {
+			T::UpdateOrigin::ensure_origin(origin)?;
+			ensure!(Self::asset_pool_ids().contains(&currency_id), Error::<T>::InvalidAssetPool);
+
+			// interest so far accrues with the old params
+			Self::accrue_interest(currency_id);
+			AssetPoolInfos::<T>::try_mutate(currency_id, |asset_pool_info| -> DispatchResult {
+				if let Some(maximum_total_debit_ratio) = maximum_total_debit_ratio {
+					ensure!(maximum_total_debit_ratio <= Ratio::one(), Error::<T>::InvalidAssetPoolParams);
+					asset_pool_info.maximum_total_debit_ratio = maximum_total_debit_ratio;
+				}
+				if let Some(minimum_deposit) = minimum_deposit {
+					asset_pool_info.minimum_deposit = minimum_deposit;
+				}
+				if let Some(minimum_debit) = minimum_debit {
+					asset_pool_info.minimum_debit = minimum_debit;
+				}
+				if let Some(supply_cap) = supply_cap {
+					asset_pool_info.supply_cap = supply_cap;
+				}
+				if let Some(borrow_cap) = borrow_cap {
+					asset_pool_info.borrow_cap = borrow_cap;
+				}
+				if let Some(reserve_factor) = reserve_factor {
+					ensure!(reserve_factor <= Ratio::one(), Error::<T>::InvalidAssetPoolParams);
+					asset_pool_info.reserve_factor = reserve_factor;
+				}
+				if let Some(interest_model) = interest_model {
+					ensure!(interest_model.is_valid(), Error::<T>::InvalidAssetPoolParams);
+					asset_pool_info.interest_model = interest_model;
+				}
+				Ok(())
+			})?;
+
+			Self::deposit_event(Event::AssetPoolParamsUpdated(currency_id));
+			Ok(().into())
+		}
+
+		#[pallet::weight(100_000_000)]
+		#[transactional]
+		pub fn set_collateral_params(
+			origin: OriginFor<T>,
+			currency_id: CurrencyId,
+			collateral_factor: Ratio,
+			liquidation_threshold: Ratio,
+			liquidation_bonus: Ratio,
+		) -> DispatchResultWithPostInfo {
+			T::UpdateOrigin::ensure_origin(origin)?;
+			ensure!(Self::asset_pool_ids().contains(&currency_id), Error::<T>::InvalidAssetPool);
+			ensure!(
+				collateral_factor <= liquidation_threshold
+					&& liquidation_threshold <= Ratio::one()
+					&& liquidation_bonus <= T::MaxLiquidationBonus::get(),
+				Error::<T>::InvalidCollateralParams
+			);
+
+			AssetPoolInfos::<T>::mutate(currency_id, |asset_pool_info| {
+				asset_pool_info.collateral_factor = collateral_factor;
+				asset_pool_info.liquidation_threshold = liquidation_threshold;
+				asset_pool_info.liquidation_bonus = liquidation_bonus;
+			});
+			Self::deposit_event(Event::CollateralParamsUpdated(
+				currency_id,
+				collateral_factor,
+				liquidation_threshold,
+				liquidation_bonus,
+			));
+			Ok(().into())
+		}
 	}
 
 	#[pallet::error]
@@ -252,6 +588,68 @@ pub mod module {
 		AmountConvertFailed,
 
 		DepositNotEnough,
+
+		InvalidPrice,
+
+		MustBeUnsafe,
+
+		NoDebit,
+
+		NoDeposit,
+
+		NoCollateral,
+
+		InsufficientCollateral,
+
+		InvalidAssetPool,
+
+		InvalidCollateralParams,
+
+		InvalidAssetPoolParams,
+
+		AssetPoolAlreadyListed,
+
+		AssetPoolNotListed,
+
+		InsufficientReserves,
+
+		InsufficientCash,
+
+		SupplyCapExceeded,
+
+		BorrowCapExceeded,
+
+		OperationPaused,
+
+		FlashLoanNotRepaid,
+
+		InvalidCreditLoanTerms,
+
+		NoAvailableCreditLoanId,
+
+		CreditLoanNotFound,
+
+		NotCreditLoanBorrower,
+
+		NoGuarantee,
+
+		GuaranteeLocked,
+
+		InsufficientGuarantee,
+
+		CreditLimitExceeded,
+
+		CreditLoanOverdue,
+
+		CreditLoanNotRepaid,
+
+		CreditLoanNotActive,
+
+		CreditLoanNotOverdue,
+
+		InvalidFixedTerm,
+
+		FixedTermLoanExists,
 	}
 
 	#[pallet::event]
@@ -259,6 +657,44 @@ pub mod module {
 	pub enum Event<T: Config> {
 		DepositUpdated(T::AccountId, CurrencyId, Amount),
 		DebitUpdated(T::AccountId, CurrencyId, Amount),
+		/// Unsafe loan liquidated. [who, currency_id, repaid_debit, penalty]
+		Liquidated(T::AccountId, CurrencyId, Balance, Balance),
+		/// Deposit used as collateral or not. [who, currency_id, enabled]
+		CollateralUpdated(T::AccountId, CurrencyId, bool),
+		/// [currency_id, collateral_factor, liquidation_threshold, liquidation_bonus]
+		CollateralParamsUpdated(CurrencyId, Ratio, Ratio, Ratio),
+		AssetPoolParamsUpdated(CurrencyId),
+		AssetPoolListed(CurrencyId),
+		AssetPoolDelisted(CurrencyId),
+		/// [currency_id, amount, dest]
+		ReservesWithdrawn(CurrencyId, Balance, T::AccountId),
+		/// [currency_id, operation, paused]
+		OperationPausedUpdated(CurrencyId, AssetPoolOperation, bool),
+		/// [who, currency_id, amount, fee]
+		FlashLoan(T::AccountId, CurrencyId, Balance, Balance),
+		/// [loan_id, borrower, currency_id, limit, term]
+		CreditLoanRequested(CreditLoanId, T::AccountId, CurrencyId, Balance, u64),
+		/// [loan_id, guarantor, amount]
+		CreditLoanGuaranteed(CreditLoanId, T::AccountId, Balance),
+		/// [loan_id, guarantor, amount]
+		GuaranteeWithdrawn(CreditLoanId, T::AccountId, Balance),
+		/// [loan_id, amount]
+		CreditLoanDrawn(CreditLoanId, Balance),
+		/// [loan_id, payer, amount]
+		CreditLoanRepaid(CreditLoanId, T::AccountId, Balance),
+		CreditLoanClosed(CreditLoanId),
+		/// [loan_id, covered_by_guarantors, shortfall]
+		CreditLoanDefaulted(CreditLoanId, Balance, Balance),
+		/// [who, currency_id, amount, rate, maturity]
+		FixedTermLoanOpened(T::AccountId, CurrencyId, Balance, Ratio, u64),
+		/// [who, currency_id, amount]
+		FixedTermLoanRepaid(T::AccountId, CurrencyId, Balance),
+		/// [who, currency_id, debit_adjustment]
+		LeverageDebitUpdated(T::AccountId, CurrencyId, Amount),
+		/// Unrecoverable debit booked as a loss of the asset pool. [currency_id, loss]
+		DebitWrittenOff(CurrencyId, Balance),
+		/// The dex TWAP, not the oracle, priced a currency for a change of loans. [currency_id]
+		FallbackPriceUsed(CurrencyId),
 	}
 }
 
@@ -267,19 +703,87 @@ impl<T: Config> Pallet<T> {
 		T::ModuleId::get().into_account()
 	}
 
-	pub fn calculate_debit_apy(utilisation_rate: Ratio, interest_info: InterestInfo) -> Ratio {
-		let critical_point = interest_info.critical_point;
-		if utilisation_rate < critical_point {
-			interest_info.base + (interest_info.slope_1) * (utilisation_rate / critical_point)
-		} else {
-			interest_info.base + interest_info.slope_1 + interest_info.slope_2 *
-				((utilisation_rate - critical_point) / (Ratio::from(1) - critical_point))
+	pub fn calculate_debit_apy(utilisation_rate: Ratio, interest_model: InterestModel) -> Ratio {
+		interest_model.debit_apy(utilisation_rate)
+	}
+
+	/// Asset pool info with interest accrued up to now, without writing it.
+	pub fn current_asset_pool_info(asset_pool_id: CurrencyId) -> AssetPoolInfo {
+		Self::accrued_asset_pool_info(asset_pool_id).0
+	}
+
+	/// Interest paid by borrowers goes to depositors, except the reserve_factor share kept as reserves.
+	/// Fixed-term loans accrue at their locked rates, but count in the utilisation of the pool.
+	/// Returns the accrued asset pool info, the reserves increment and the fixed-term interest.
+	fn accrued_asset_pool_info(asset_pool_id: CurrencyId) -> (AssetPoolInfo, Balance, Balance) {
+		let mut asset_pool_info = Self::asset_pool_infos(asset_pool_id);
+		let now = T::UnixTime::now().as_secs();
+		let elapsed = match Self::last_accrued_timestamps(asset_pool_id) {
+			Some(last_accrued) if now > last_accrued => now - last_accrued,
+			_ => return (asset_pool_info, Zero::zero(), Zero::zero()),
+		};
+
+		let total_debit = asset_pool_info.total_debit;
+		let total_deposit = asset_pool_info.total_deposit;
+		let lent = total_debit.saturating_add(Self::total_fixed_term_debits(asset_pool_id));
+		if lent.is_zero() || total_deposit.is_zero() {
+			return (asset_pool_info, Zero::zero(), Zero::zero());
+		}
+
+		let utilisation_rate = Ratio::saturating_from_rational(lent, total_deposit);
+		asset_pool_info.debit_apy = Self::calculate_debit_apy(utilisation_rate, asset_pool_info.interest_model);
+		asset_pool_info.deposit_apy = asset_pool_info.debit_apy
+			.saturating_mul(utilisation_rate)
+			.saturating_mul(Ratio::one().saturating_sub(asset_pool_info.reserve_factor));
+
+		let elapsed_years = Ratio::saturating_from_rational(elapsed, SECONDS_PER_YEAR);
+		let debit_increment = elapsed_years.saturating_mul(asset_pool_info.debit_apy);
+		let variable_interest = debit_increment.saturating_mul_int(total_debit);
+		let fixed_term_interest = elapsed_years.saturating_mul_int(Self::fixed_term_yearly_interests(asset_pool_id));
+		let interest = variable_interest.saturating_add(fixed_term_interest);
+		let reserves = asset_pool_info.reserve_factor.saturating_mul_int(interest);
+		let deposit_interest = interest.saturating_sub(reserves);
+		let deposit_increment = Ratio::saturating_from_rational(deposit_interest, total_deposit);
+
+		asset_pool_info.debit_rate = asset_pool_info.debit_rate
+			.saturating_mul(Ratio::one().saturating_add(debit_increment));
+		asset_pool_info.deposit_rate = asset_pool_info.deposit_rate
+			.saturating_mul(Ratio::one().saturating_add(deposit_increment));
+		asset_pool_info.total_debit = total_debit.saturating_add(variable_interest);
+		asset_pool_info.total_deposit = total_deposit.saturating_add(deposit_interest);
+
+		(asset_pool_info, reserves, fixed_term_interest)
+	}
+
+	/// Accrue interest of the asset pool since the last accrual.
+	pub fn accrue_interest(asset_pool_id: CurrencyId) {
+		let now = T::UnixTime::now().as_secs();
+		if !AssetPoolInfos::<T>::contains_key(asset_pool_id)
+			|| Self::last_accrued_timestamps(asset_pool_id).map_or(false, |last_accrued| now <= last_accrued) {
+			return;
 		}
+
+		let (asset_pool_info, reserves, fixed_term_interest) = Self::accrued_asset_pool_info(asset_pool_id);
+		AssetPoolInfos::<T>::insert(asset_pool_id, asset_pool_info);
+		TotalReserves::<T>::mutate(asset_pool_id, |total_reserves| *total_reserves = total_reserves.saturating_add(reserves));
+		TotalFixedTermDebits::<T>::mutate(asset_pool_id, |total| *total = total.saturating_add(fixed_term_interest));
+		LastAccruedTimestamps::<T>::insert(asset_pool_id, now);
 	}
 
 	#[transactional]
 	pub fn update_deposit(who: &T::AccountId, asset_pool_id: CurrencyId, deposit_adjustment: Amount) -> DispatchResult {
+		if deposit_adjustment.is_positive() {
+			Self::ensure_not_paused(asset_pool_id, AssetPoolOperation::Deposit)?;
+		} else if deposit_adjustment.is_negative() {
+			Self::ensure_not_paused(asset_pool_id, AssetPoolOperation::Withdraw)?;
+		}
 		Self::_update_deposit(who, asset_pool_id, deposit_adjustment)?;
+		if deposit_adjustment.is_negative() {
+			Self::ensure_account_healthy(who)?;
+		}
+
+		Self::ensure_deposit_above_minimum(who, asset_pool_id)?;
+
 		let deposit_balance_adjustment = Self::balance_try_from_amount_abs(deposit_adjustment)?;
 		let module_account = Self::account_id();
 
@@ -296,13 +800,22 @@ impl<T: Config> Pallet<T> {
 
 	fn _update_deposit(who: &T::AccountId, asset_pool_id: CurrencyId, deposit_adjustment: Amount) -> DispatchResult {
 		let deposit_balance = Self::balance_try_from_amount_abs(deposit_adjustment)?;
+		if deposit_adjustment.is_positive() {
+			ensure!(Self::is_listed(asset_pool_id), Error::<T>::AssetPoolNotListed);
+		}
+		Self::accrue_interest(asset_pool_id);
 
 		AssetPoolInfos::<T>::try_mutate(asset_pool_id, |asset_pool_info| -> DispatchResult {
 			asset_pool_info.total_deposit = if deposit_adjustment.is_positive() {
-				asset_pool_info.total_deposit.checked_add(deposit_balance).ok_or(Error::<T>::DepositOverflow)
+				asset_pool_info.total_deposit.checked_add(deposit_balance).ok_or(Error::<T>::DepositOverflow)?
 			} else {
-				asset_pool_info.total_deposit.checked_sub(deposit_balance).ok_or(Error::<T>::DepositTooLow)
-			}?;
+				let withdraw_all = deposit_balance == asset_pool_info.deposit_rate.saturating_mul_int(Self::loans(asset_pool_id, who).deposit);
+				Self::sub_from_total(asset_pool_info.total_deposit, deposit_balance, withdraw_all)
+					.ok_or(Error::<T>::DepositTooLow)?
+			};
+			if deposit_adjustment.is_positive() && !asset_pool_info.supply_cap.is_zero() {
+				ensure!(asset_pool_info.total_deposit <= asset_pool_info.supply_cap, Error::<T>::SupplyCapExceeded);
+			}
 
 			<LoanInfos<T>>::try_mutate_exists(asset_pool_id, who, |loan| -> DispatchResult{
 				let mut l = loan.take().unwrap_or_default();
@@ -310,12 +823,13 @@ impl<T: Config> Pallet<T> {
 				let new_deposit = if deposit_adjustment.is_positive() {
 					let increase = asset_pool_info.deposit_rate.reciprocal().unwrap_or_default().saturating_mul_int(deposit_balance);
 					l.deposit.checked_add(increase).ok_or(Error::<T>::DepositOverflow)
+				} else if deposit_balance == asset_pool_info.deposit_rate.saturating_mul_int(l.deposit) {
+					// withdraw all, no rounding dust left
+					Ok(Zero::zero())
 				} else {
 					let decrease = asset_pool_info.deposit_rate.reciprocal().unwrap_or_default().saturating_mul_int(deposit_balance);
 					l.deposit.checked_sub(decrease).ok_or(Error::<T>::DepositTooLow)
 				}?;
-				// TODO
-				// ensure!(new_deposit >= asset_pool_info.minimum_deposit, Error::<T>::DepositTooLow);
 				T::OnUpdateLoan::happened(&(who.clone(), asset_pool_id, deposit_adjustment, l.deposit));
 				l.deposit = new_deposit;
 
@@ -334,6 +848,110 @@ impl<T: Config> Pallet<T> {
 
 	#[transactional]
 	pub fn update_debit(who: &T::AccountId, asset_pool_id: CurrencyId, debit_adjustment: Amount) -> DispatchResult {
+		Self::do_update_debit(who, asset_pool_id, debit_adjustment)?;
+		if debit_adjustment.is_positive() {
+			Self::ensure_account_healthy(who)?;
+		}
+
+		Self::ensure_debit_above_minimum(who, asset_pool_id)
+	}
+
+	/// Increase the deposit of `who` with funds of `payer`.
+	#[transactional]
+	pub fn deposit_for(payer: &T::AccountId, who: &T::AccountId, asset_pool_id: CurrencyId, amount: Balance) -> DispatchResult {
+		Self::ensure_not_paused(asset_pool_id, AssetPoolOperation::Deposit)?;
+		let deposit_adjustment = Self::amount_try_from_balance(amount)?;
+		Self::_update_deposit(who, asset_pool_id, deposit_adjustment)?;
+		Self::ensure_deposit_above_minimum(who, asset_pool_id)?;
+
+		T::Currency::transfer(asset_pool_id, payer, &Self::account_id(), amount)?;
+		Self::deposit_event(Event::DepositUpdated(who.clone(), asset_pool_id, deposit_adjustment));
+		Ok(())
+	}
+
+	/// Repay the debit of `who` with funds of `payer`.
+	#[transactional]
+	pub fn repay_on_behalf(payer: &T::AccountId, who: &T::AccountId, asset_pool_id: CurrencyId, amount: Balance) -> DispatchResult {
+		Self::ensure_not_paused(asset_pool_id, AssetPoolOperation::Repay)?;
+		let debit_adjustment = Self::amount_try_from_balance(amount)?.saturating_neg();
+		Self::_update_debit(who, asset_pool_id, debit_adjustment)?;
+		Self::ensure_debit_above_minimum(who, asset_pool_id)?;
+
+		T::Currency::transfer(asset_pool_id, payer, &Self::account_id(), amount)?;
+		Self::deposit_event(Event::DebitUpdated(who.clone(), asset_pool_id, debit_adjustment));
+		Ok(())
+	}
+
+	// a deposit is either closed or above the minimum
+	fn ensure_deposit_above_minimum(who: &T::AccountId, asset_pool_id: CurrencyId) -> DispatchResult {
+		let asset_pool_info = Self::asset_pool_infos(asset_pool_id);
+		let deposit = asset_pool_info.deposit_rate.saturating_mul_int(Self::loans(asset_pool_id, who).deposit);
+		ensure!(deposit.is_zero() || deposit >= asset_pool_info.minimum_deposit, Error::<T>::DepositTooLow);
+		Ok(())
+	}
+
+	// a debit is either repaid or above the minimum
+	fn ensure_debit_above_minimum(who: &T::AccountId, asset_pool_id: CurrencyId) -> DispatchResult {
+		let asset_pool_info = Self::asset_pool_infos(asset_pool_id);
+		let debit = asset_pool_info.debit_rate.saturating_mul_int(Self::loans(asset_pool_id, who).debit);
+		ensure!(debit.is_zero() || debit >= asset_pool_info.minimum_debit, Error::<T>::DebitTooLow);
+		Ok(())
+	}
+
+	/// Withdraw the whole deposit including accrued interest, returns the withdrawn amount.
+	#[transactional]
+	pub fn withdraw_all(who: &T::AccountId, asset_pool_id: CurrencyId) -> result::Result<Balance, DispatchError> {
+		let deposit = Self::current_asset_pool_info(asset_pool_id).deposit_rate
+			.saturating_mul_int(Self::loans(asset_pool_id, who).deposit);
+		ensure!(!deposit.is_zero(), Error::<T>::NoDeposit);
+		Self::update_deposit(who, asset_pool_id, Self::amount_try_from_balance(deposit)?.saturating_neg())?;
+		Ok(deposit)
+	}
+
+	/// Repay the whole debit including accrued interest, returns the repaid amount.
+	#[transactional]
+	pub fn repay_all(who: &T::AccountId, asset_pool_id: CurrencyId) -> result::Result<Balance, DispatchError> {
+		let debit = Self::current_asset_pool_info(asset_pool_id).debit_rate
+			.saturating_mul_int(Self::loans(asset_pool_id, who).debit);
+		ensure!(!debit.is_zero(), Error::<T>::NoDebit);
+		Self::update_debit(who, asset_pool_id, Self::amount_try_from_balance(debit)?.saturating_neg())?;
+		Ok(debit)
+	}
+
+	/// Lend `amount` to `who` while `T::FlashLoanReceiver` runs `payload`, which must leave `who`
+	/// able to pay back `amount` plus the fee, otherwise everything is reverted. Returns the fee.
+	#[transactional]
+	pub fn flash_loan(
+		who: &T::AccountId,
+		asset_pool_id: CurrencyId,
+		amount: Balance,
+		payload: FlashLoanPayloadOf<T>,
+	) -> result::Result<Balance, DispatchError> {
+		ensure!(Self::is_listed(asset_pool_id), Error::<T>::AssetPoolNotListed);
+		Self::ensure_not_paused(asset_pool_id, AssetPoolOperation::Borrow)?;
+		Self::accrue_interest(asset_pool_id);
+
+		// rounded up, splitting a loan into small ones must not dodge the fee
+		let fee = amount.saturating_sub(Ratio::one().saturating_sub(T::FlashLoanFee::get()).saturating_mul_int(amount));
+		let module_account = Self::account_id();
+
+		T::Currency::transfer(asset_pool_id, &module_account, who, amount)?;
+		T::FlashLoanReceiver::on_flash_loan(who, asset_pool_id, amount, fee, payload)?;
+		T::Currency::transfer(asset_pool_id, who, &module_account, amount.saturating_add(fee))
+			.map_err(|_| Error::<T>::FlashLoanNotRepaid)?;
+
+		TotalReserves::<T>::mutate(asset_pool_id, |total_reserves| *total_reserves = total_reserves.saturating_add(fee));
+		Self::deposit_event(Event::FlashLoan(who.clone(), asset_pool_id, amount, fee));
+		Ok(fee)
+	}
+
+	#[transactional]
+	fn do_update_debit(who: &T::AccountId, asset_pool_id: CurrencyId, debit_adjustment: Amount) -> DispatchResult {
+		if debit_adjustment.is_positive() {
+			Self::ensure_not_paused(asset_pool_id, AssetPoolOperation::Borrow)?;
+		} else if debit_adjustment.is_negative() {
+			Self::ensure_not_paused(asset_pool_id, AssetPoolOperation::Repay)?;
+		}
 		Self::_update_debit(who, asset_pool_id, debit_adjustment)?;
 		let debit_balance_adjustment = Self::balance_try_from_amount_abs(debit_adjustment)?;
 		let module_account = Self::account_id();
@@ -350,24 +968,15 @@ impl<T: Config> Pallet<T> {
 
 	fn _update_debit(who: &T::AccountId, asset_pool_id: CurrencyId, debit_adjustment: Amount) -> DispatchResult {
 		let debit_balance = Self::balance_try_from_amount_abs(debit_adjustment)?;
+		if debit_adjustment.is_positive() {
+			ensure!(Self::is_listed(asset_pool_id), Error::<T>::AssetPoolNotListed);
+		}
+		Self::accrue_interest(asset_pool_id);
 
 		AssetPoolInfos::<T>::try_mutate(asset_pool_id, |asset_pool_info| -> DispatchResult {
-			let total_debit = asset_pool_info.total_debit;
-			let total_deposit = asset_pool_info.total_deposit;
-
-			ensure!(!total_deposit.is_zero(), Error::<T>::DepositNotEnough);
-			let new_total_debit = if debit_adjustment.is_positive() {
-				total_debit.checked_add(debit_balance).ok_or(Error::<T>::DebitOverflow)
-			} else {
-				total_debit.checked_sub(debit_balance).ok_or(Error::<T>::DebitTooLow)
-			}?;
-
-			// println!("maximum_total_debit_ratio: {:?}, new_total_debit: {:?}, total_deposit: {:?}",
-			// 			 asset_pool_info.maximum_total_debit_ratio, new_total_debit, total_deposit);
-			// check new_total_debit / total_deposit <= maximum_total_debit_ratio
-			ensure!(Ratio::saturating_from_rational(new_total_debit, total_deposit)
-					<= asset_pool_info.maximum_total_debit_ratio, Error::<T>::DepositNotEnough);
-			asset_pool_info.total_debit = new_total_debit;
+			let repay_all = debit_adjustment.is_negative()
+				&& debit_balance == asset_pool_info.debit_rate.saturating_mul_int(Self::loans(asset_pool_id, who).debit);
+			Self::update_total_debit(asset_pool_id, asset_pool_info, debit_adjustment, repay_all)?;
 
 			<LoanInfos<T>>::try_mutate_exists(asset_pool_id, who, |loan| -> DispatchResult{
 				let mut l = loan.take().unwrap_or_default();
@@ -375,16 +984,15 @@ impl<T: Config> Pallet<T> {
 				let new_debit = if debit_adjustment.is_positive() {
 					let increase = asset_pool_info.debit_rate.reciprocal().unwrap().saturating_mul_int(debit_balance);
 					l.debit.checked_add(increase).ok_or(Error::<T>::DebitOverflow)
+				} else if debit_balance == asset_pool_info.debit_rate.saturating_mul_int(l.debit) {
+					// repay all, no rounding dust left
+					Ok(Zero::zero())
 				} else {
 					// debit balance > personal debit
 					let decrease = asset_pool_info.debit_rate.reciprocal().unwrap().saturating_mul_int(debit_balance);
 					l.debit.checked_sub(decrease).ok_or(Error::<T>::DebitTooLow)
 				}?;
 
-				// TODO how to handle this corner in front-end (force add?)
-				// ensure!(new_debit >= asset_pool_info.minimum_debit, Error::<T>::DebitTooLow);
-				// TODO check health ratio(need to depend PriceManager)
-
 				T::OnUpdateLoan::happened(&(who.clone(), asset_pool_id, debit_adjustment, l.debit));
 				l.debit = new_debit;
 
@@ -401,16 +1009,303 @@ impl<T: Config> Pallet<T> {
 		})
 	}
 
+	fn update_total_debit(
+		asset_pool_id: CurrencyId,
+		asset_pool_info: &mut AssetPoolInfo,
+		debit_adjustment: Amount,
+		repay_all: bool,
+	) -> DispatchResult {
+		let debit_balance = Self::balance_try_from_amount_abs(debit_adjustment)?;
+		let total_debit = asset_pool_info.total_debit;
+
+		let new_total_debit = if debit_adjustment.is_positive() {
+			total_debit.checked_add(debit_balance).ok_or(Error::<T>::DebitOverflow)?
+		} else {
+			Self::sub_from_total(total_debit, debit_balance, repay_all).ok_or(Error::<T>::DebitTooLow)?
+		};
+
+		// only new debit is limited, repaying must stay possible after collateral is seized
+		if debit_adjustment.is_positive() {
+			let lent = new_total_debit.saturating_add(Self::total_fixed_term_debits(asset_pool_id));
+			Self::ensure_lendable(asset_pool_info, lent)?;
+		}
+		asset_pool_info.total_debit = new_total_debit;
+		Ok(())
+	}
+
+	/// `total - amount` for a pool total. The interest indices round, so an account clearing its
+	/// whole ledger may find the total short of `amount` by rounding dust, which is clamped to zero.
+	/// Any other shortfall is `None`.
+	fn sub_from_total(total: Balance, amount: Balance, clears_ledger: bool) -> Option<Balance> {
+		total.checked_sub(amount).or_else(|| if clears_ledger { Some(Zero::zero()) } else { None })
+	}
+
+	/// Ensure `lent`, variable and fixed-term debit together, is within the caps of the asset pool.
+	fn ensure_lendable(asset_pool_info: &AssetPoolInfo, lent: Balance) -> DispatchResult {
+		if !asset_pool_info.borrow_cap.is_zero() {
+			ensure!(lent <= asset_pool_info.borrow_cap, Error::<T>::BorrowCapExceeded);
+		}
+		let total_deposit = asset_pool_info.total_deposit;
+		ensure!(!total_deposit.is_zero(), Error::<T>::DepositNotEnough);
+		// check lent / total_deposit <= maximum_total_debit_ratio
+		ensure!(Ratio::saturating_from_rational(lent, total_deposit)
+				<= asset_pool_info.maximum_total_debit_ratio, Error::<T>::DepositNotEnough);
+		Ok(())
+	}
+
+	#[transactional]
+	pub fn set_collateral(who: &T::AccountId, asset_pool_id: CurrencyId, enabled: bool) -> DispatchResult {
+		ensure!(Self::asset_pool_ids().contains(&asset_pool_id), Error::<T>::InvalidAssetPool);
+
+		if enabled {
+			Collaterals::<T>::insert(who, asset_pool_id, true);
+		} else {
+			Collaterals::<T>::remove(who, asset_pool_id);
+			Self::ensure_account_healthy(who)?;
+		}
+
+		Self::deposit_event(Event::CollateralUpdated(who.clone(), asset_pool_id, enabled));
+		Ok(())
+	}
+
+	pub fn is_listed(asset_pool_id: CurrencyId) -> bool {
+		Self::asset_pool_statuses(asset_pool_id) == Some(AssetPoolStatus::Listed)
+	}
+
+	fn ensure_not_paused(asset_pool_id: CurrencyId, operation: AssetPoolOperation) -> DispatchResult {
+		ensure!(!Self::paused_operations(asset_pool_id, operation), Error::<T>::OperationPaused);
+		Ok(())
+	}
+
+	fn ensure_valid_asset_pool_params(asset_pool_info: &AssetPoolInfo) -> DispatchResult {
+		ensure!(
+			asset_pool_info.maximum_total_debit_ratio <= Ratio::one()
+				&& asset_pool_info.reserve_factor <= Ratio::one()
+				&& asset_pool_info.interest_model.is_valid(),
+			Error::<T>::InvalidAssetPoolParams
+		);
+		ensure!(
+			asset_pool_info.collateral_factor <= asset_pool_info.liquidation_threshold
+				&& asset_pool_info.liquidation_threshold <= Ratio::one()
+				&& asset_pool_info.liquidation_bonus <= T::MaxLiquidationBonus::get(),
+			Error::<T>::InvalidCollateralParams
+		);
+		Ok(())
+	}
+
 	fn balance_try_from_amount_abs(a: Amount) -> result::Result<Balance, Error<T>> {
 		TryInto::<Balance>::try_into(a.saturating_abs()).map_err(|_| Error::<T>::AmountConvertFailed)
 	}
 
+	fn amount_try_from_balance(b: Balance) -> result::Result<Amount, Error<T>> {
+		TryInto::<Amount>::try_into(b).map_err(|_| Error::<T>::AmountConvertFailed)
+	}
+
+	/// Borrow limit (deposit value * collateral_factor) and total debit value of `who`
+	/// over all asset pools. Only deposits enabled as collateral are counted.
+	pub fn get_account_liquidity(who: &T::AccountId) -> result::Result<(Balance, Balance), Error<T>> {
+		Self::weighted_collateral_and_debit(who, |asset_pool_info| asset_pool_info.collateral_factor, false)
+	}
+
+	/// Price of `currency_id` for a change of loans, with `FallbackPriceUsed` deposited when the
+	/// dex TWAP priced it.
+	fn price_for_update(currency_id: CurrencyId) -> Option<Price> {
+		let (price, origin) = T::PriceSource::get_price_with_origin(currency_id)?;
+		if origin == PriceOrigin::Dex {
+			Self::deposit_event(Event::FallbackPriceUsed(currency_id));
+		}
+		Some(price)
+	}
+
+	fn relative_price_for_update(base_currency_id: CurrencyId, quote_currency_id: CurrencyId) -> Option<Price> {
+		Self::price_for_update(base_currency_id)?.checked_div(&Self::price_for_update(quote_currency_id)?)
+	}
+
+	/// Collateral value weighted by `weight` and debit value of `who`. Prices read for a change of
+	/// loans are `for_update`, which notes those the dex TWAP gave.
+	fn weighted_collateral_and_debit(
+		who: &T::AccountId,
+		weight: impl Fn(&AssetPoolInfo) -> Ratio,
+		for_update: bool,
+	) -> result::Result<(Balance, Balance), Error<T>> {
+		let mut collateral_value: Balance = Zero::zero();
+		let mut debit_value: Balance = Zero::zero();
+
+		for asset_pool_id in Self::asset_pool_ids() {
+			let loan = Self::loans(asset_pool_id, who);
+			let is_collateral = Self::collaterals(who, asset_pool_id);
+			let fixed_term_debit = Self::fixed_term_loans(asset_pool_id, who)
+				.map(|loan| Self::current_fixed_term_debit(&loan))
+				.unwrap_or_default();
+			if (loan.deposit.is_zero() || !is_collateral) && loan.debit.is_zero() && fixed_term_debit.is_zero() {
+				continue;
+			}
+
+			let asset_pool_info = Self::current_asset_pool_info(asset_pool_id);
+			let price = if for_update {
+				Self::price_for_update(asset_pool_id)
+			} else {
+				T::PriceSource::get_price(asset_pool_id)
+			}.ok_or(Error::<T>::InvalidPrice)?;
+
+			if is_collateral {
+				let deposit_value = price.saturating_mul_int(asset_pool_info.deposit_rate.saturating_mul_int(loan.deposit));
+				collateral_value = collateral_value.saturating_add(weight(&asset_pool_info).saturating_mul_int(deposit_value));
+			}
+			let debit = asset_pool_info.debit_rate.saturating_mul_int(loan.debit).saturating_add(fixed_term_debit);
+			debit_value = debit_value.saturating_add(price.saturating_mul_int(debit));
+		}
+
+		Ok((collateral_value, debit_value))
+	}
+
+	/// Ensure the debit value of `who` is covered by its collateral value.
+	fn ensure_account_healthy(who: &T::AccountId) -> DispatchResult {
+		let has_debit = Self::asset_pool_ids().into_iter().any(|asset_pool_id| {
+			!Self::loans(asset_pool_id, who).debit.is_zero() || FixedTermLoans::<T>::contains_key(asset_pool_id, who)
+		});
+		if !has_debit {
+			return Ok(());
+		}
+
+		let (collateral_value, debit_value) =
+			Self::weighted_collateral_and_debit(who, |asset_pool_info| asset_pool_info.collateral_factor, true)?;
+		ensure!(debit_value <= collateral_value, Error::<T>::InsufficientCollateral);
+		Ok(())
+	}
+
 
 }
 
 impl<T: Config> Pallet<T> {
-	// TODO: call dex
+	/// Seize `who`'s collateral deposits to repay its `currency_id` debit plus the liquidation bonus
+	/// of each seized collateral. Deposits of `currency_id` are used first, other deposits are sold on the dex.
+	/// A fixed-term loan overdue past the grace period is liquidated even if the account is not unsafe.
 	pub fn liquidate_unsafe_debit(who: T::AccountId, currency_id: CurrencyId) -> DispatchResult {
+		Self::ensure_not_paused(currency_id, AssetPoolOperation::Liquidate)?;
+		let variable_debit = if Self::debit_unsafe(&who, true) {
+			let asset_pool_info = Self::current_asset_pool_info(currency_id);
+			let debit = asset_pool_info.debit_rate.saturating_mul_int(Self::loans(currency_id, &who).debit);
+			// close the debit partly, unless what is left would fall below the minimum
+			let closed = T::LiquidationCloseFactor::get().saturating_mul_int(debit);
+			if debit.saturating_sub(closed) < asset_pool_info.minimum_debit {
+				debit
+			} else {
+				closed
+			}
+		} else {
+			ensure!(Self::is_fixed_term_loan_overdue(currency_id, &who), Error::<T>::MustBeUnsafe);
+			Zero::zero()
+		};
+		let fixed_term_debit = Self::fixed_term_loans(currency_id, &who)
+			.map(|loan| Self::current_fixed_term_debit(&loan))
+			.unwrap_or_default();
+
+		let debit_balance = variable_debit.saturating_add(fixed_term_debit);
+		ensure!(!debit_balance.is_zero(), Error::<T>::NoDebit);
+
+		let module_account = Self::account_id();
+
+		let mut collateral_ids = Self::asset_pool_ids();
+		collateral_ids.sort_by_key(|collateral_id| *collateral_id != currency_id);
+
+		let mut remaining_debit = debit_balance;
+		let mut collected: Balance = Zero::zero();
+		for collateral_id in collateral_ids {
+			if remaining_debit.is_zero() {
+				break;
+			}
+			if !Self::collaterals(&who, collateral_id) {
+				continue;
+			}
+
+			let collateral_info = Self::current_asset_pool_info(collateral_id);
+			let deposit_balance = collateral_info.deposit_rate
+				.saturating_mul_int(Self::loans(collateral_id, &who).deposit);
+			if deposit_balance.is_zero() {
+				continue;
+			}
+
+			// seize the remaining debit plus the bonus of this collateral
+			let bonus_rate = Ratio::one().saturating_add(collateral_info.liquidation_bonus);
+			let target_amount = bonus_rate.saturating_mul_int(remaining_debit);
+			let (seized, received) = if collateral_id == currency_id {
+				let seized = deposit_balance.min(target_amount);
+				(seized, seized)
+			} else {
+				// the dex must pay at least the oracle price less the bonus of this collateral
+				let min_price = Self::relative_price_for_update(collateral_id, currency_id)
+					.ok_or(Error::<T>::InvalidPrice)?
+					.saturating_mul(Ratio::one().saturating_sub(collateral_info.liquidation_bonus));
+				let path = [collateral_id, currency_id];
+				match T::DEX::get_swap_supply_amount(&path, target_amount, None) {
+					Some(supply_amount)
+						if supply_amount <= deposit_balance && min_price.saturating_mul_int(supply_amount) <= target_amount =>
+					{
+						T::DEX::swap_with_exact_target(&module_account, &path, target_amount, supply_amount, None)?;
+						(supply_amount, target_amount)
+					}
+					_ => {
+						let min_target_amount = min_price.saturating_mul_int(deposit_balance);
+						let received = T::DEX::swap_with_exact_supply(&module_account, &path, deposit_balance, min_target_amount, None)?;
+						(deposit_balance, received)
+					}
+				}
+			};
+
+			let covered = if received >= target_amount {
+				remaining_debit
+			} else {
+				bonus_rate.reciprocal().unwrap_or_default().saturating_mul_int(received).min(remaining_debit)
+			};
+			remaining_debit = remaining_debit.saturating_sub(covered);
+			collected = collected.saturating_add(received);
+
+			Self::_update_deposit(&who, collateral_id, Self::amount_try_from_balance(seized)?.saturating_neg())?;
+		}
+
+		// variable debit is repaid first, then the fixed-term loan
+		let repaid = debit_balance.saturating_sub(remaining_debit);
+		ensure!(!repaid.is_zero(), Error::<T>::NoCollateral);
+		let variable_repaid = repaid.min(variable_debit);
+		Self::_update_debit(&who, currency_id, Self::amount_try_from_balance(variable_repaid)?.saturating_neg())?;
+		let fixed_term_repaid = repaid.saturating_sub(variable_repaid);
+		if !fixed_term_repaid.is_zero() {
+			Self::_repay_fixed_term(&who, currency_id, fixed_term_repaid)?;
+		}
+
+		let penalty_paid = collected.saturating_sub(repaid);
+		if !penalty_paid.is_zero() {
+			T::Currency::transfer(currency_id, &module_account, &T::LiquidationPenaltyReceiver::get(), penalty_paid)?;
+		}
+
+		// all collateral was seized, whatever the account still owes is bad debt
+		if !remaining_debit.is_zero() {
+			Self::write_off_bad_debit(&who, currency_id)?;
+		}
+
+		Self::deposit_event(Event::Liquidated(who, currency_id, repaid, penalty_paid));
+		Ok(())
+	}
+
+	/// Drop the whole debit of `who` in `asset_pool_id` as a loss of the asset pool.
+	fn write_off_bad_debit(who: &T::AccountId, asset_pool_id: CurrencyId) -> DispatchResult {
+		let variable_debit = Self::current_asset_pool_info(asset_pool_id)
+			.debit_rate
+			.saturating_mul_int(Self::loans(asset_pool_id, who).debit);
+		if !variable_debit.is_zero() {
+			Self::_update_debit(who, asset_pool_id, Self::amount_try_from_balance(variable_debit)?.saturating_neg())?;
+		}
+		let fixed_term_debit = Self::fixed_term_loans(asset_pool_id, who)
+			.map(|loan| Self::current_fixed_term_debit(&loan))
+			.unwrap_or_default();
+		if !fixed_term_debit.is_zero() {
+			Self::_repay_fixed_term(who, asset_pool_id, fixed_term_debit)?;
+		}
+
+		let loss = variable_debit.saturating_add(fixed_term_debit);
+		AssetPoolInfos::<T>::mutate(asset_pool_id, |asset_pool_info| Self::write_off(asset_pool_id, asset_pool_info, loss));
+		Self::deposit_event(Event::DebitWrittenOff(asset_pool_id, loss));
 		Ok(())
 	}
 
@@ -418,12 +1313,16 @@ impl<T: Config> Pallet<T> {
 		let who = T::Lookup::unlookup(who);
 		let call = Call::<T>::liquidate(currency_id, who.clone());
 		if SubmitTransaction::<T, Call<T>>::submit_unsigned_transaction(call.into()).is_err() {
-			debug::info!("failed");
+			debug::info!(
+				target: "liquidation off chain worker",
+				"submit unsigned liquidation tx for \nCurrencyId {:?} and \nAccount {:?} \nfailed!",
+				currency_id, who,
+			);
 		}
 	}
 
 	fn _offchain_worker() -> Result<(), OffchainErr> {
-		let asset_pool_ids = T::AssetPoolIds::get();
+		let asset_pool_ids = Self::asset_pool_ids();
 		if asset_pool_ids.len().is_zero() {
 			return Ok(());
 		}
@@ -456,21 +1355,37 @@ impl<T: Config> Pallet<T> {
 			.unwrap_or(Some(DEFAULT_MAX_ITERATIONS));
 
 		let currency_id = asset_pool_ids[(asset_pool_id as usize)];
+		let liquidation_paused = Self::paused_operations(currency_id, AssetPoolOperation::Liquidate);
 		let mut map_iterator =  <LoanInfos<T> as
 		IterableStorageDoubleMapExtended<_, _, _>>::iter_prefix(currency_id, max_iterations, start_key.clone());
 
-		let mut iteration_count = 0;
-		while let Some(((who, LoanInfo {deposit, debit}))) = map_iterator.next() {
-			// if Self::is_debit_unsafe() {
-			// 	Self::submit_unsigned_liquidation_tx(currency_id, who);
-			// }
+		while let Some((who, LoanInfo { debit, .. })) = map_iterator.next() {
+			if !liquidation_paused && !debit.is_zero() && Self::is_debit_unsafe(&who) {
+				Self::submit_unsigned_liquidation_tx(currency_id, who);
+			}
 
-			iteration_count += 1;
+			guard.extend_lock().map_err(|_| OffchainErr::OffchainLock)?;
+		}
+
+		// fixed-term loans are kept apart from LoanInfos and have their own cursor in the same asset pool
+		let fixed_term_to_be_continue = StorageValueRef::persistent(&OFFCHAIN_WORKER_FIXED_TERM_DATA);
+		let fixed_term_start_key = match fixed_term_to_be_continue.get::<(u32, Option<Vec<u8>>)>() {
+			Some(Some((last_asset_pool_id, maybe_last_iterator_previous_key))) if last_asset_pool_id == asset_pool_id =>
+				maybe_last_iterator_previous_key,
+			_ => None,
+		};
+		let mut fixed_term_map_iterator = <FixedTermLoans<T> as
+		IterableStorageDoubleMapExtended<_, _, _>>::iter_prefix(currency_id, max_iterations, fixed_term_start_key);
+
+		while let Some((who, _)) = fixed_term_map_iterator.next() {
+			if !liquidation_paused && Self::is_liquidatable(currency_id, &who) {
+				Self::submit_unsigned_liquidation_tx(currency_id, who);
+			}
 
 			guard.extend_lock().map_err(|_| OffchainErr::OffchainLock)?;
 		}
 
-		if map_iterator.finished {
+		if map_iterator.finished && fixed_term_map_iterator.finished {
 			let next_asset_pool_id =
 				if asset_pool_id < asset_pool_ids.len().saturating_sub(1) as u32 {
 					asset_pool_id + 1
@@ -478,8 +1393,11 @@ impl<T: Config> Pallet<T> {
 					0
 				};
 			to_be_continue.set(&(next_asset_pool_id, Option::<Vec<u8>>::None));
+			fixed_term_to_be_continue.set(&(next_asset_pool_id, Option::<Vec<u8>>::None));
 		} else {
+			// resuming a finished map from its last key yields nothing more
 			to_be_continue.set(&(asset_pool_id, Some(map_iterator.map_iterator.previous_key)));
+			fixed_term_to_be_continue.set(&(asset_pool_id, Some(fixed_term_map_iterator.map_iterator.previous_key)));
 		}
 
 		guard.forget();
@@ -487,8 +1405,42 @@ impl<T: Config> Pallet<T> {
 		Ok(())
 	}
 
-	// TODO: impl unsafe check
-	pub fn is_debit_unsafe() -> bool {
-		return true;
+	/// A loan is unsafe once the debit value exceeds the collateral value weighted by liquidation_threshold.
+	/// Loans can't be judged without prices, so they are treated as safe.
+	pub fn is_debit_unsafe(who: &T::AccountId) -> bool {
+		Self::debit_unsafe(who, false)
+	}
+
+	fn debit_unsafe(who: &T::AccountId, for_update: bool) -> bool {
+		match Self::weighted_collateral_and_debit(who, |asset_pool_info| asset_pool_info.liquidation_threshold, for_update) {
+			Ok((collateral_value, debit_value)) => !debit_value.is_zero() && debit_value > collateral_value,
+			Err(_) => false,
+		}
+	}
+
+	/// Whether `liquidate` can repay debit of `who` in `currency_id`. Without collateral to seize
+	/// nothing can be repaid.
+	pub fn is_liquidatable(currency_id: CurrencyId, who: &T::AccountId) -> bool {
+		let has_debit = !Self::loans(currency_id, who).debit.is_zero()
+			|| FixedTermLoans::<T>::contains_key(currency_id, who);
+		let has_collateral = Self::asset_pool_ids().into_iter().any(|collateral_id| {
+			Self::collaterals(who, collateral_id) && !Self::loans(collateral_id, who).deposit.is_zero()
+		});
+		has_debit && has_collateral && (Self::is_debit_unsafe(who) || Self::is_fixed_term_loan_overdue(currency_id, who))
+	}
+}
+
+impl<T: Config> AssetPoolManager<T::AccountId> for Pallet<T> {
+	// leveraged positions are backed by the collateral held in custody by the dex, not by deposits
+	fn update_debit(who: &T::AccountId, currency_id: CurrencyId, debit_adjustment: Amount) -> DispatchResult {
+		Self::update_leverage_debit(who, currency_id, debit_adjustment)
+	}
+
+	fn write_off_debit(who: &T::AccountId, currency_id: CurrencyId, amount: Balance) -> DispatchResult {
+		Self::write_off_leverage_debit(who, currency_id, amount)
+	}
+
+	fn debit_rate(currency_id: CurrencyId) -> Ratio {
+		Self::current_asset_pool_info(currency_id).debit_rate
 	}
 }
@@ -0,0 +1,91 @@
+//! Storage migrations run on runtime upgrade.
+
+use super::*;
+
+/// `AssetPoolInfo` before the collateral params, caps and interest models.
+#[derive(Encode, Decode, Clone, RuntimeDebug, PartialEq, Eq, Default)]
+pub struct AssetPoolInfoV1 {
+	pub maximum_total_debit_ratio: Ratio,
+	pub minimum_deposit: Balance,
+	pub minimum_debit: Balance,
+	pub health_ratio: Ratio,
+	pub total_deposit: Balance,
+	pub total_debit: Balance,
+	pub deposit_rate: Ratio,
+	pub debit_rate: Ratio,
+	pub deposit_apy: Ratio,
+	pub debit_apy: Ratio,
+	pub reserve_factor: Ratio,
+	pub interest_info: InterestInfo,
+}
+
+/// Liquidation bonus given to asset pools that had none before.
+pub fn default_liquidation_bonus<T: Config>() -> Ratio {
+	Ratio::saturating_from_rational(5, 100).min(T::MaxLiquidationBonus::get())
+}
+
+/// Collateral factor and liquidation threshold derived from the old health_ratio. Borrowing stops
+/// at the health_ratio as before, liquidation only starts halfway from there to 100%, so loans
+/// opened at the limit are not liquidatable right after the upgrade.
+pub fn collateral_params_from_health_ratio(health_ratio: Ratio) -> (Ratio, Ratio) {
+	let collateral_factor = health_ratio.min(Ratio::saturating_from_rational(95, 100));
+	let margin = Ratio::one().saturating_sub(collateral_factor);
+	let liquidation_threshold = collateral_factor.saturating_add(margin.saturating_mul(Ratio::saturating_from_rational(1, 2)));
+	(collateral_factor, liquidation_threshold)
+}
+
+/// Move the asset pools of the `AssetPoolIds` constant to on chain storage and convert their
+/// params, see `collateral_params_from_health_ratio`. Caps start unset and the jump rate
+/// interest_info is kept as the interest model. Every deposit backed debit before, so all
+/// existing deposits are enabled as collateral.
+pub fn migrate_to_v2<T: Config>() -> Weight {
+	if StorageVersion::<T>::get() != Releases::V1_0_0 {
+		return 0;
+	}
+
+	let mut asset_pool_ids: Vec<CurrencyId> = Vec::new();
+	AssetPoolInfos::<T>::translate::<AssetPoolInfoV1, _>(|asset_pool_id, old| {
+		asset_pool_ids.push(asset_pool_id);
+		let (collateral_factor, liquidation_threshold) = collateral_params_from_health_ratio(old.health_ratio);
+		Some(AssetPoolInfo {
+			maximum_total_debit_ratio: old.maximum_total_debit_ratio,
+			minimum_deposit: old.minimum_deposit,
+			minimum_debit: old.minimum_debit,
+			supply_cap: Zero::zero(),
+			borrow_cap: Zero::zero(),
+			collateral_factor,
+			liquidation_threshold,
+			liquidation_bonus: default_liquidation_bonus::<T>(),
+			total_deposit: old.total_deposit,
+			total_debit: old.total_debit,
+			deposit_rate: old.deposit_rate,
+			debit_rate: old.debit_rate,
+			deposit_apy: old.deposit_apy,
+			debit_apy: old.debit_apy,
+			reserve_factor: old.reserve_factor,
+			interest_model: InterestModel::JumpRate(old.interest_info),
+		})
+	});
+
+	// interest accrued by block before, it accrues by unix time from now on
+	let now = T::UnixTime::now().as_secs();
+	for asset_pool_id in asset_pool_ids.iter() {
+		AssetPoolStatuses::<T>::insert(asset_pool_id, AssetPoolStatus::Listed);
+		LastAccruedTimestamps::<T>::insert(asset_pool_id, now);
+	}
+	let migrated = asset_pool_ids.len() as Weight;
+	AssetPoolIds::<T>::put(asset_pool_ids);
+
+	let mut loans: Weight = 0;
+	let mut collaterals: Weight = 0;
+	for (asset_pool_id, who, loan) in LoanInfos::<T>::iter() {
+		loans += 1;
+		if !loan.deposit.is_zero() {
+			Collaterals::<T>::insert(who, asset_pool_id, true);
+			collaterals += 1;
+		}
+	}
+	StorageVersion::<T>::put(Releases::V2_0_0);
+
+	T::DbWeight::get().reads_writes(migrated + loans + 1, migrated * 3 + collaterals + 2)
+}
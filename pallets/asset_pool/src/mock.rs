@@ -4,12 +4,15 @@
 
 use frame_support::{construct_runtime, parameter_types};
 use frame_support::{pallet_prelude::*};
+use frame_system::EnsureRoot;
 use orml_traits::parameter_type_with_key;
 use sp_core::H256;
 use sp_runtime::{ModuleId, testing::Header, testing::TestXt, traits::IdentityLookup};
-use sp_runtime::FixedPointNumber;
+use sp_runtime::{DispatchError, FixedPointNumber, traits::CheckedDiv};
+use sp_std::cell::RefCell;
+use std::collections::BTreeMap;
 
-use model::Ratio;
+use model::{Price, PriceOrigin, Ratio};
 
 use super::*;
 
@@ -18,8 +21,12 @@ pub type BlockNumber = u64;
 
 pub const ALICE: AccountId = 1;
 pub const BOB: AccountId = 2;
+pub const TREASURY: AccountId = 3;
+pub const CHARLIE: AccountId = 5;
+pub const DEX_ACCOUNT: AccountId = 10;
 
 pub const DOT: CurrencyId = CurrencyId::Token(1);
+pub const ETH: CurrencyId = CurrencyId::Token(2);
 pub const BTC: CurrencyId = CurrencyId::Token(3);
 
 mod loans {
@@ -85,11 +92,119 @@ impl pallet_balances::Config for Runtime {
 	type WeightInfo = ();
 }
 
+parameter_types! {
+	pub const MinimumPeriod: u64 = 1000;
+}
+
+impl pallet_timestamp::Config for Runtime {
+	type Moment = u64;
+	type OnTimestampSet = ();
+	type MinimumPeriod = MinimumPeriod;
+	type WeightInfo = ();
+}
+
 parameter_types! {
 	pub const LoansModuleId: ModuleId = ModuleId(*b"antimatterdc");
-	pub AssetPoolIds: Vec<CurrencyId> = vec![DOT];
-	pub BlockPercentEachYear: Ratio = Ratio::one();
 	pub const UnsignedPriority: u64 = 1 << 20;
+	pub const LiquidationPenaltyReceiver: AccountId = TREASURY;
+	pub FlashLoanFee: Ratio = Ratio::saturating_from_rational(1, 100);
+	pub OverduePenaltyRate: Ratio = Ratio::saturating_from_rational(10, 100);
+	pub const OverdueGracePeriod: u64 = 1000;
+	pub LiquidationCloseFactor: Ratio = Ratio::saturating_from_rational(50, 100);
+	pub MaxLiquidationBonus: Ratio = Ratio::saturating_from_rational(20, 100);
+}
+
+thread_local! {
+	static PRICES: RefCell<BTreeMap<CurrencyId, (Price, PriceOrigin)>> = RefCell::new(BTreeMap::new());
+	static DEX_DISCOUNT: RefCell<Ratio> = RefCell::new(Ratio::zero());
+}
+
+pub struct MockPriceSource;
+impl MockPriceSource {
+	pub fn set_price(currency_id: CurrencyId, price: Option<Price>) {
+		PRICES.with(|prices| match price {
+			Some(price) => prices.borrow_mut().insert(currency_id, (price, PriceOrigin::Oracle)),
+			None => prices.borrow_mut().remove(&currency_id),
+		});
+	}
+
+	/// Price `currency_id` as if the oracle had none and the dex TWAP stood in.
+	pub fn set_dex_price(currency_id: CurrencyId, price: Price) {
+		PRICES.with(|prices| prices.borrow_mut().insert(currency_id, (price, PriceOrigin::Dex)));
+	}
+}
+impl PriceProvider for MockPriceSource {
+	fn get_price(currency_id: CurrencyId) -> Option<Price> {
+		Self::get_price_with_origin(currency_id).map(|(price, _)| price)
+	}
+
+	fn get_price_with_origin(currency_id: CurrencyId) -> Option<(Price, PriceOrigin)> {
+		PRICES.with(|prices| prices.borrow().get(&currency_id).copied())
+	}
+}
+
+/// Swaps at the mock prices, less the configured discount, against the funded `DEX_ACCOUNT`.
+pub struct MockDEX;
+impl MockDEX {
+	pub fn set_discount(discount: Ratio) {
+		DEX_DISCOUNT.with(|v| *v.borrow_mut() = discount);
+	}
+
+	fn discounted() -> Ratio {
+		Ratio::one().saturating_sub(DEX_DISCOUNT.with(|v| *v.borrow()))
+	}
+}
+impl DEXManager<AccountId> for MockDEX {
+	fn get_swap_target_amount(path: &[CurrencyId], supply_amount: Balance, _: Option<Ratio>) -> Option<Balance> {
+		MockPriceSource::get_relative_price(path[0], path[path.len() - 1])
+			.map(|price| price.saturating_mul(Self::discounted()).saturating_mul_int(supply_amount))
+	}
+
+	fn get_swap_supply_amount(path: &[CurrencyId], target_amount: Balance, _: Option<Ratio>) -> Option<Balance> {
+		MockPriceSource::get_relative_price(path[path.len() - 1], path[0])
+			.and_then(|price| price.checked_div(&Self::discounted()))
+			.map(|price| price.saturating_mul_int(target_amount))
+	}
+
+	fn swap_with_exact_supply(
+		who: &AccountId,
+		path: &[CurrencyId],
+		supply_amount: Balance,
+		min_target_amount: Balance,
+		_: Option<Ratio>,
+	) -> Result<Balance, DispatchError> {
+		let target_amount = Self::get_swap_target_amount(path, supply_amount, None)
+			.ok_or(DispatchError::Other("no price"))?;
+		ensure!(target_amount >= min_target_amount, DispatchError::Other("insufficient target amount"));
+		Tokens::transfer(path[0], who, &DEX_ACCOUNT, supply_amount)?;
+		Tokens::transfer(path[path.len() - 1], &DEX_ACCOUNT, who, target_amount)?;
+		Ok(target_amount)
+	}
+
+	fn swap_with_exact_target(
+		who: &AccountId,
+		path: &[CurrencyId],
+		target_amount: Balance,
+		max_supply_amount: Balance,
+		_: Option<Ratio>,
+	) -> Result<Balance, DispatchError> {
+		let supply_amount = Self::get_swap_supply_amount(path, target_amount, None)
+			.ok_or(DispatchError::Other("no price"))?;
+		ensure!(supply_amount <= max_supply_amount, DispatchError::Other("excessive supply amount"));
+		Tokens::transfer(path[0], who, &DEX_ACCOUNT, supply_amount)?;
+		Tokens::transfer(path[path.len() - 1], &DEX_ACCOUNT, who, target_amount)?;
+		Ok(supply_amount)
+	}
+}
+
+/// Arbitrage stand-in, pays the profit given as payload from `DEX_ACCOUNT` to the flash loan borrower.
+pub struct MockFlashLoanReceiver;
+impl FlashLoanReceiver<AccountId> for MockFlashLoanReceiver {
+	type Payload = Balance;
+
+	fn on_flash_loan(who: &AccountId, currency_id: CurrencyId, _amount: Balance, _fee: Balance, profit: Balance) -> DispatchResult {
+		Tokens::transfer(currency_id, &DEX_ACCOUNT, who, profit)
+	}
 }
 
 pub type Extrinsic = TestXt<Call, ()>;
@@ -106,11 +221,20 @@ impl Config for Runtime {
 	type Currency = Tokens;
 	type ModuleId = LoansModuleId;
 
-	type AssetPoolIds = AssetPoolIds;
-	type BlockPercentEachYear = BlockPercentEachYear;
+	type UnixTime = Timestamp;
 	type UnsignedPriority = UnsignedPriority;
 
 	type OnUpdateLoan = ();
+	type PriceSource = MockPriceSource;
+	type DEX = MockDEX;
+	type LiquidationPenaltyReceiver = LiquidationPenaltyReceiver;
+	type UpdateOrigin = EnsureRoot<AccountId>;
+	type FlashLoanReceiver = MockFlashLoanReceiver;
+	type FlashLoanFee = FlashLoanFee;
+	type OverduePenaltyRate = OverduePenaltyRate;
+	type OverdueGracePeriod = OverdueGracePeriod;
+	type LiquidationCloseFactor = LiquidationCloseFactor;
+	type MaxLiquidationBonus = MaxLiquidationBonus;
 }
 
 type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Runtime>;
@@ -126,6 +250,7 @@ construct_runtime!(
 		LoansModule: loans::{Module, Storage, Call, Event<T>},
 		Tokens: orml_tokens::{Module, Storage, Event<T>, Config<T>},
 		PalletBalances: pallet_balances::{Module, Call, Storage, Config<T>, Event<T>},
+		Timestamp: pallet_timestamp::{Module, Call, Storage, Inherent},
 	}
 );
 
@@ -140,28 +265,84 @@ impl Default for ExtBuilder {
 			endowed_accounts: vec![
 				(ALICE, DOT, 1000),
 				(ALICE, BTC, 1000),
+				(ALICE, ETH, 1000),
 				(BOB, DOT, 1000),
 				(BOB, BTC, 1000),
+				(BOB, ETH, 1000),
+				(CHARLIE, DOT, 1000),
+				(DEX_ACCOUNT, DOT, 1000),
+				(DEX_ACCOUNT, BTC, 1000),
+				(DEX_ACCOUNT, ETH, 1000),
 			],
 			asset_pool_params: vec![
 				(DOT, AssetPoolInfo {
 					maximum_total_debit_ratio: Ratio::saturating_from_rational(90, 100),
 					minimum_deposit: 10u128.pow(1),
 					minimum_debit: 10u128.pow(1),
-					health_ratio: Ratio::saturating_from_rational(75, 100),
+					supply_cap: 0,
+					borrow_cap: 0,
+					collateral_factor: Ratio::saturating_from_rational(75, 100),
+					liquidation_threshold: Ratio::saturating_from_rational(80, 100),
+					liquidation_bonus: Ratio::saturating_from_rational(10, 100),
+					total_deposit: 0,
+					total_debit: 0,
+					deposit_rate: Ratio::saturating_from_rational(100, 100),
+					debit_rate: Ratio::saturating_from_rational(100, 100),
+					deposit_apy: Ratio::saturating_from_rational(0, 100),
+					debit_apy: Ratio::saturating_from_rational(0, 100),
+					reserve_factor: Ratio::saturating_from_rational(10, 100),
+					interest_model: InterestModel::JumpRate(InterestInfo {
+						critical_point: Ratio::saturating_from_rational(90, 100),
+						base: Ratio::saturating_from_rational(0, 100),
+						slope_1: Ratio::saturating_from_rational(4, 100),
+						slope_2: Ratio::saturating_from_rational(100, 100),
+					}),
+				}),
+				(BTC, AssetPoolInfo {
+					maximum_total_debit_ratio: Ratio::saturating_from_rational(90, 100),
+					minimum_deposit: 10u128.pow(1),
+					minimum_debit: 10u128.pow(1),
+					supply_cap: 0,
+					borrow_cap: 0,
+					collateral_factor: Ratio::saturating_from_rational(75, 100),
+					liquidation_threshold: Ratio::saturating_from_rational(80, 100),
+					liquidation_bonus: Ratio::saturating_from_rational(10, 100),
 					total_deposit: 0,
 					total_debit: 0,
 					deposit_rate: Ratio::saturating_from_rational(100, 100),
 					debit_rate: Ratio::saturating_from_rational(100, 100),
 					deposit_apy: Ratio::saturating_from_rational(0, 100),
 					debit_apy: Ratio::saturating_from_rational(0, 100),
-					reserve_factor: Ratio::saturating_from_rational(90, 100),
-					interest_info: InterestInfo {
+					reserve_factor: Ratio::saturating_from_rational(10, 100),
+					interest_model: InterestModel::JumpRate(InterestInfo {
 						critical_point: Ratio::saturating_from_rational(90, 100),
 						base: Ratio::saturating_from_rational(0, 100),
 						slope_1: Ratio::saturating_from_rational(4, 100),
 						slope_2: Ratio::saturating_from_rational(100, 100),
-					},
+					}),
+				}),
+				(ETH, AssetPoolInfo {
+					maximum_total_debit_ratio: Ratio::saturating_from_rational(90, 100),
+					minimum_deposit: 10u128.pow(1),
+					minimum_debit: 10u128.pow(1),
+					supply_cap: 0,
+					borrow_cap: 0,
+					collateral_factor: Ratio::saturating_from_rational(75, 100),
+					liquidation_threshold: Ratio::saturating_from_rational(80, 100),
+					liquidation_bonus: Ratio::saturating_from_rational(10, 100),
+					total_deposit: 0,
+					total_debit: 0,
+					deposit_rate: Ratio::saturating_from_rational(100, 100),
+					debit_rate: Ratio::saturating_from_rational(100, 100),
+					deposit_apy: Ratio::saturating_from_rational(0, 100),
+					debit_apy: Ratio::saturating_from_rational(0, 100),
+					reserve_factor: Ratio::saturating_from_rational(10, 100),
+					interest_model: InterestModel::JumpRate(InterestInfo {
+						critical_point: Ratio::saturating_from_rational(90, 100),
+						base: Ratio::saturating_from_rational(0, 100),
+						slope_1: Ratio::saturating_from_rational(4, 100),
+						slope_2: Ratio::saturating_from_rational(100, 100),
+					}),
 				}),
 			]
 		}
@@ -170,6 +351,11 @@ impl Default for ExtBuilder {
 
 impl ExtBuilder {
 	pub fn build(self) -> sp_io::TestExternalities {
+		MockPriceSource::set_price(DOT, Some(Price::one()));
+		MockPriceSource::set_price(BTC, Some(Price::one()));
+		MockPriceSource::set_price(ETH, Some(Price::one()));
+		MockDEX::set_discount(Ratio::zero());
+
 		let mut t = frame_system::GenesisConfig::default()
 			.build_storage::<Runtime>()
 			.unwrap();
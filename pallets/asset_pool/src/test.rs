@@ -1,8 +1,12 @@
 #![cfg(test)]
 
+use codec::Decode;
 use frame_support::{assert_noop, assert_ok};
+use sp_core::offchain::{testing, OffchainExt, TransactionPoolExt};
 
 use mock::{*};
+use model::Price;
+use sp_runtime::DispatchError;
 
 use super::*;
 
@@ -30,6 +34,7 @@ fn update_debit_work() {
 		assert_eq!(LoansModule::asset_pool_infos(DOT).total_deposit, 0);
 
 		assert_ok!(LoansModule::update_deposit(&ALICE, DOT, 500));
+		assert_ok!(LoansModule::set_collateral(&ALICE, DOT, true));
 		// new debit
 		assert_noop!(
 			LoansModule::update_debit(&ALICE, DOT, 490),
@@ -48,30 +53,1300 @@ fn calculate_debit_apy_work() {
 	ExtBuilder::default().build().execute_with(|| {
 		assert_eq!(
 			LoansModule::calculate_debit_apy(Ratio::saturating_from_rational(950, 1000),
-											 LoansModule::asset_pool_infos(DOT).interest_info),
+											 LoansModule::asset_pool_infos(DOT).interest_model),
 			Ratio::saturating_from_rational(54, 100)
 		);
 	});
 }
 
 #[test]
-fn on_finalize_work() {
+fn accrue_interest_by_elapsed_time_work() {
 	ExtBuilder::default().build().execute_with(|| {
+		Timestamp::set_timestamp(6_000);
 		// provide deposit
 		assert_ok!(LoansModule::update_deposit(&ALICE, DOT, 100));
 		assert_eq!(Tokens::free_balance(DOT, &ALICE), 900);
+		assert_ok!(LoansModule::set_collateral(&ALICE, DOT, true));
 		// new debit
 		assert_ok!(LoansModule::update_debit(&ALICE, DOT, 50));
 		assert_eq!(Tokens::free_balance(DOT, &ALICE), 950);
-		LoansModule::on_finalize(1);
+		assert_eq!(LoansModule::last_accrued_timestamps(DOT), Some(6));
+
+		// half a year, accrued lazily
+		Timestamp::set_timestamp(6_000 + SECONDS_PER_YEAR * 500);
+		assert_eq!(LoansModule::asset_pool_infos(DOT).debit_rate, Ratio::one());
+		assert_eq!(
+			LoansModule::current_asset_pool_info(DOT).debit_rate,
+			Ratio::saturating_from_rational(1011111111111111111u128, 1000000000000000000u128)
+		);
+
+		// one year
+		Timestamp::set_timestamp(6_000 + SECONDS_PER_YEAR * 1000);
+		LoansModule::accrue_interest(DOT);
 		assert_eq!(
 			LoansModule::asset_pool_infos(DOT).debit_rate,
 			Ratio::saturating_from_rational(1022222222222222222u128, 1000000000000000000u128)
 		);
-		LoansModule::on_finalize(2);
+		assert_eq!(LoansModule::last_accrued_timestamps(DOT), Some(6 + SECONDS_PER_YEAR));
+
+		// no time elapsed, nothing accrued
+		LoansModule::accrue_interest(DOT);
 		assert_eq!(
 			LoansModule::asset_pool_infos(DOT).debit_rate,
-			Ratio::saturating_from_rational(1044444444444444444u128, 1000000000000000000u128)
+			Ratio::saturating_from_rational(1022222222222222222u128, 1000000000000000000u128)
+		);
+
+		// interest is compounded into total_debit and depositors earn it
+		assert_eq!(LoansModule::asset_pool_infos(DOT).total_debit, 51);
+		assert_eq!(LoansModule::asset_pool_infos(DOT).total_deposit, 101);
+
+		// two years, accrued on interaction
+		Timestamp::set_timestamp(6_000 + SECONDS_PER_YEAR * 2000);
+		assert_ok!(LoansModule::update_deposit(&BOB, DOT, 100));
+		assert_eq!(
+			LoansModule::asset_pool_infos(DOT).debit_rate,
+			Ratio::saturating_from_rational(1045163182984965162u128, 1000000000000000000u128)
+		);
+	});
+}
+
+#[test]
+fn total_debit_short_of_repayment_work() {
+	ExtBuilder::default().build().execute_with(|| {
+		assert_ok!(LoansModule::update_deposit(&ALICE, DOT, 500));
+		assert_ok!(LoansModule::set_collateral(&ALICE, DOT, true));
+		assert_ok!(LoansModule::update_debit(&ALICE, DOT, 100));
+		AssetPoolInfos::<Runtime>::mutate(DOT, |asset_pool_info| asset_pool_info.total_debit = 50);
+
+		// only clearing the whole debit may clamp what total_debit is short of
+		assert_noop!(LoansModule::update_debit(&ALICE, DOT, -60), Error::<Runtime>::DebitTooLow);
+		assert_ok!(LoansModule::update_debit(&ALICE, DOT, -100));
+		assert_eq!(LoansModule::asset_pool_infos(DOT).total_debit, 0);
+		assert_eq!(LoansModule::loans(DOT, &ALICE).debit, 0);
+	});
+}
+
+#[test]
+fn is_debit_unsafe_work() {
+	ExtBuilder::default().build().execute_with(|| {
+		assert_ok!(LoansModule::update_deposit(&ALICE, BTC, 100));
+		assert_ok!(LoansModule::set_collateral(&ALICE, BTC, true));
+		assert_ok!(LoansModule::update_deposit(&BOB, DOT, 500));
+		assert_ok!(LoansModule::update_debit(&ALICE, DOT, 70));
+		assert_eq!(LoansModule::get_account_liquidity(&ALICE).ok(), Some((75, 70)));
+		assert!(!LoansModule::is_debit_unsafe(&ALICE));
+
+		// below the borrow limit but still above the liquidation threshold
+		MockPriceSource::set_price(BTC, Some(Price::saturating_from_rational(9, 10)));
+		assert!(!LoansModule::is_debit_unsafe(&ALICE));
+		MockPriceSource::set_price(BTC, Some(Price::saturating_from_rational(8, 10)));
+		assert!(LoansModule::is_debit_unsafe(&ALICE));
+
+		// can't judge without a price
+		MockPriceSource::set_price(BTC, None);
+		assert!(!LoansModule::is_debit_unsafe(&ALICE));
+	});
+}
+
+#[test]
+fn health_check_on_borrow_and_withdraw_work() {
+	ExtBuilder::default().build().execute_with(|| {
+		assert_ok!(LoansModule::update_deposit(&ALICE, BTC, 100));
+		assert_ok!(LoansModule::set_collateral(&ALICE, BTC, true));
+		assert_ok!(LoansModule::update_deposit(&BOB, DOT, 500));
+		assert_noop!(
+			LoansModule::update_debit(&ALICE, DOT, 76),
+			Error::<Runtime>::InsufficientCollateral
+		);
+		assert_ok!(LoansModule::update_debit(&ALICE, DOT, 60));
+
+		assert_noop!(
+			LoansModule::update_deposit(&ALICE, BTC, -30),
+			Error::<Runtime>::InsufficientCollateral
+		);
+		assert_ok!(LoansModule::update_deposit(&ALICE, BTC, -20));
+		assert_eq!(LoansModule::get_account_liquidity(&ALICE).ok(), Some((60, 60)));
+
+		// repaying is always possible
+		assert_ok!(LoansModule::update_debit(&ALICE, DOT, -10));
+
+		MockPriceSource::set_price(DOT, None);
+		assert_noop!(
+			LoansModule::update_debit(&ALICE, DOT, 1),
+			Error::<Runtime>::InvalidPrice
+		);
+		// accounts without debit don't need prices
+		assert_ok!(LoansModule::update_deposit(&BOB, DOT, -100));
+	});
+}
+
+#[test]
+fn liquidate_unsafe_debit_seizes_same_currency_first_work() {
+	ExtBuilder::default().build().execute_with(|| {
+		System::set_block_number(1);
+		assert_ok!(LoansModule::update_deposit(&ALICE, DOT, 100));
+		assert_ok!(LoansModule::update_deposit(&ALICE, BTC, 100));
+		assert_ok!(LoansModule::set_collateral(&ALICE, DOT, true));
+		assert_ok!(LoansModule::set_collateral(&ALICE, BTC, true));
+		assert_ok!(LoansModule::update_deposit(&BOB, DOT, 500));
+		assert_ok!(LoansModule::update_debit(&ALICE, DOT, 140));
+		assert_noop!(
+			LoansModule::liquidate_unsafe_debit(ALICE, DOT),
+			Error::<Runtime>::MustBeUnsafe
 		);
+
+		MockPriceSource::set_price(BTC, Some(Price::saturating_from_rational(50, 100)));
+		assert!(LoansModule::is_debit_unsafe(&ALICE));
+		assert_ok!(LoansModule::liquidate(Origin::none(), DOT, ALICE));
+
+		// half of the 140 debit is closed, 77 of the DOT deposit cover 70 debit with 10% bonus
+		let event = Event::loans(crate::Event::Liquidated(ALICE, DOT, 70, 7));
+		assert!(System::events().iter().any(|record| record.event == event));
+		assert_eq!(LoansModule::loans(DOT, &ALICE), LoanInfo { deposit: 23, debit: 70 });
+		assert_eq!(LoansModule::loans(BTC, &ALICE), LoanInfo { deposit: 100, debit: 0 });
+		assert_eq!(LoansModule::asset_pool_infos(DOT).total_deposit, 523);
+		assert_eq!(LoansModule::asset_pool_infos(DOT).total_debit, 70);
+		assert_eq!(Tokens::free_balance(DOT, &TREASURY), 7);
+		assert_eq!(Tokens::free_balance(DOT, &LoansModule::account_id()), 453);
+		assert_eq!(Tokens::free_balance(BTC, &DEX_ACCOUNT), 1000);
+		assert_eq!(Tokens::free_balance(DOT, &DEX_ACCOUNT), 1000);
+		assert_eq!(Tokens::free_balance(DOT, &ALICE), 1040);
+	});
+}
+
+#[test]
+fn liquidate_unsafe_debit_through_dex_work() {
+	ExtBuilder::default().build().execute_with(|| {
+		System::set_block_number(1);
+		assert_ok!(LoansModule::update_deposit(&ALICE, BTC, 100));
+		assert_ok!(LoansModule::set_collateral(&ALICE, BTC, true));
+		assert_ok!(LoansModule::update_deposit(&BOB, DOT, 500));
+		assert_ok!(LoansModule::update_debit(&ALICE, DOT, 70));
+		assert_noop!(
+			LoansModule::liquidate_unsafe_debit(ALICE, BTC),
+			Error::<Runtime>::MustBeUnsafe
+		);
+
+		MockPriceSource::set_price(BTC, Some(Price::saturating_from_rational(80, 100)));
+		assert_noop!(
+			LoansModule::liquidate_unsafe_debit(ALICE, BTC),
+			Error::<Runtime>::NoDebit
+		);
+		assert_ok!(LoansModule::liquidate(Origin::none(), DOT, ALICE));
+
+		// half of the debit is closed, 38 DOT (35 debit + 3 bonus) bought with 47 BTC at 0.8
+		let event = Event::loans(crate::Event::Liquidated(ALICE, DOT, 35, 3));
+		assert!(System::events().iter().any(|record| record.event == event));
+		assert_eq!(LoansModule::loans(BTC, &ALICE), LoanInfo { deposit: 53, debit: 0 });
+		assert_eq!(LoansModule::loans(DOT, &ALICE), LoanInfo { deposit: 0, debit: 35 });
+		assert_eq!(LoansModule::asset_pool_infos(BTC).total_deposit, 53);
+		assert_eq!(LoansModule::asset_pool_infos(DOT).total_debit, 35);
+		assert_eq!(Tokens::free_balance(BTC, &DEX_ACCOUNT), 1047);
+		assert_eq!(Tokens::free_balance(DOT, &DEX_ACCOUNT), 962);
+		assert_eq!(Tokens::free_balance(DOT, &TREASURY), 3);
+		assert_eq!(Tokens::free_balance(DOT, &LoansModule::account_id()), 465);
+		assert_eq!(Tokens::free_balance(BTC, &LoansModule::account_id()), 53);
+
+		// the dex pays far below the oracle price, nothing is sold
+		MockPriceSource::set_price(BTC, Some(Price::saturating_from_rational(60, 100)));
+		assert!(LoansModule::is_debit_unsafe(&ALICE));
+		MockDEX::set_discount(Ratio::saturating_from_rational(20, 100));
+		assert_noop!(
+			LoansModule::liquidate_unsafe_debit(ALICE, DOT),
+			DispatchError::Other("insufficient target amount")
+		);
+		MockDEX::set_discount(Ratio::zero());
+		assert_ok!(LoansModule::liquidate_unsafe_debit(ALICE, DOT));
+	});
+}
+
+#[test]
+fn liquidate_underwater_debit_writes_off_bad_debt_work() {
+	ExtBuilder::default().build().execute_with(|| {
+		System::set_block_number(1);
+		assert_ok!(LoansModule::update_deposit(&ALICE, BTC, 100));
+		assert_ok!(LoansModule::set_collateral(&ALICE, BTC, true));
+		assert_ok!(LoansModule::update_deposit(&BOB, DOT, 500));
+		assert_ok!(LoansModule::update_debit(&ALICE, DOT, 70));
+
+		// the whole 100 BTC only buy 30 DOT, covering 27 debit and 3 bonus
+		MockPriceSource::set_price(BTC, Some(Price::saturating_from_rational(30, 100)));
+		assert_ok!(LoansModule::liquidate(Origin::none(), DOT, ALICE));
+		let liquidated_event = Event::loans(crate::Event::Liquidated(ALICE, DOT, 27, 3));
+		assert!(System::events().iter().any(|record| record.event == liquidated_event));
+
+		// nothing is left to seize, the other 43 debit are a loss of the depositors
+		let written_off_event = Event::loans(crate::Event::DebitWrittenOff(DOT, 43));
+		assert!(System::events().iter().any(|record| record.event == written_off_event));
+		assert_eq!(LoansModule::loans(BTC, &ALICE), LoanInfo { deposit: 0, debit: 0 });
+		assert_eq!(LoansModule::loans(DOT, &ALICE), LoanInfo { deposit: 0, debit: 0 });
+		assert_eq!(LoansModule::asset_pool_infos(DOT).total_debit, 0);
+		assert_eq!(LoansModule::asset_pool_infos(DOT).total_deposit, 457);
+		assert_eq!(Tokens::free_balance(DOT, &TREASURY), 3);
+		assert_eq!(Tokens::free_balance(DOT, &LoansModule::account_id()), 457);
+		assert!(!LoansModule::is_liquidatable(DOT, &ALICE));
+	});
+}
+
+#[test]
+fn liquidate_without_collateral_fails() {
+	ExtBuilder::default().build().execute_with(|| {
+		assert_ok!(LoansModule::update_deposit(&ALICE, BTC, 100));
+		assert_ok!(LoansModule::set_collateral(&ALICE, BTC, true));
+		assert_ok!(LoansModule::update_deposit(&BOB, DOT, 500));
+		assert_ok!(LoansModule::update_debit(&ALICE, DOT, 70));
+		Collaterals::<Runtime>::remove(ALICE, BTC);
+		assert!(LoansModule::is_debit_unsafe(&ALICE));
+
+		// nothing can be seized, the liquidation would be a no-op
+		assert!(!LoansModule::is_liquidatable(DOT, &ALICE));
+		assert_eq!(
+			LoansModule::validate_unsigned(TransactionSource::External, &crate::Call::<Runtime>::liquidate(DOT, ALICE)),
+			InvalidTransaction::Stale.into(),
+		);
+		assert_noop!(
+			LoansModule::liquidate_unsafe_debit(ALICE, DOT),
+			Error::<Runtime>::NoCollateral
+		);
+	});
+}
+
+#[test]
+fn fallback_price_used_work() {
+	ExtBuilder::default().build().execute_with(|| {
+		System::set_block_number(1);
+		assert_ok!(LoansModule::update_deposit(&ALICE, BTC, 100));
+		assert_ok!(LoansModule::set_collateral(&ALICE, BTC, true));
+		assert_ok!(LoansModule::update_deposit(&BOB, DOT, 500));
+		let fallback_event = Event::loans(crate::Event::FallbackPriceUsed(BTC));
+		let fallback_noted = || System::events().iter().any(|record| record.event == fallback_event);
+
+		assert_ok!(LoansModule::update_debit(&ALICE, DOT, 10));
+		assert!(!fallback_noted());
+
+		// borrowing against collateral the dex priced
+		MockPriceSource::set_dex_price(BTC, Price::one());
+		assert_ok!(LoansModule::update_debit(&ALICE, DOT, 10));
+		assert!(fallback_noted());
+
+		// reading prices writes nothing, withdrawing is noted
+		System::reset_events();
+		assert!(!LoansModule::is_debit_unsafe(&ALICE));
+		assert!(LoansModule::get_account_liquidity(&ALICE).is_ok());
+		assert!(System::events().is_empty());
+		assert_ok!(LoansModule::update_deposit(&ALICE, BTC, -10));
+		assert!(fallback_noted());
+
+		// liquidating
+		System::reset_events();
+		MockPriceSource::set_dex_price(BTC, Price::saturating_from_rational(20, 100));
+		assert_ok!(LoansModule::liquidate(Origin::none(), DOT, ALICE));
+		assert!(fallback_noted());
+	});
+}
+
+#[test]
+fn validate_unsigned_liquidation_work() {
+	ExtBuilder::default().build().execute_with(|| {
+		assert_ok!(LoansModule::update_deposit(&ALICE, BTC, 100));
+		assert_ok!(LoansModule::set_collateral(&ALICE, BTC, true));
+		assert_ok!(LoansModule::update_deposit(&BOB, DOT, 500));
+		assert_ok!(LoansModule::update_debit(&ALICE, DOT, 70));
+
+		let call = crate::Call::<Runtime>::liquidate(DOT, ALICE);
+		assert_eq!(
+			LoansModule::validate_unsigned(TransactionSource::External, &call),
+			InvalidTransaction::Stale.into(),
+		);
+
+		MockPriceSource::set_price(BTC, Some(Price::saturating_from_rational(80, 100)));
+		assert!(LoansModule::validate_unsigned(TransactionSource::External, &call).is_ok());
+		// no debit in BTC pool to liquidate
+		assert_eq!(
+			LoansModule::validate_unsigned(TransactionSource::External, &crate::Call::<Runtime>::liquidate(BTC, ALICE)),
+			InvalidTransaction::Stale.into(),
+		);
+	});
+}
+
+#[test]
+fn borrow_against_collateral_in_several_pools_work() {
+	ExtBuilder::default().build().execute_with(|| {
+		System::set_block_number(1);
+		assert_ok!(LoansModule::update_deposit(&ALICE, DOT, 100));
+		assert_ok!(LoansModule::update_deposit(&ALICE, ETH, 100));
+		assert_ok!(LoansModule::update_deposit(&BOB, BTC, 500));
+
+		// deposits don't back debit until enabled as collateral
+		assert_noop!(
+			LoansModule::update_debit(&ALICE, BTC, 1),
+			Error::<Runtime>::InsufficientCollateral
+		);
+		assert_ok!(LoansModule::set_collateral(&ALICE, DOT, true));
+		let event = Event::loans(crate::Event::CollateralUpdated(ALICE, DOT, true));
+		assert!(System::events().iter().any(|record| record.event == event));
+		assert_ok!(LoansModule::set_collateral(&ALICE, ETH, true));
+		assert_noop!(
+			LoansModule::set_collateral(&ALICE, CurrencyId::Token(9), true),
+			Error::<Runtime>::InvalidAssetPool
+		);
+
+		assert_noop!(
+			LoansModule::update_debit(&ALICE, BTC, 151),
+			Error::<Runtime>::InsufficientCollateral
+		);
+		assert_ok!(LoansModule::update_debit(&ALICE, BTC, 150));
+		assert_eq!(LoansModule::get_account_liquidity(&ALICE).ok(), Some((150, 150)));
+
+		assert_noop!(
+			LoansModule::set_collateral(&ALICE, ETH, false),
+			Error::<Runtime>::InsufficientCollateral
+		);
+		assert_ok!(LoansModule::update_debit(&ALICE, BTC, -75));
+		assert_ok!(LoansModule::set_collateral(&ALICE, ETH, false));
+		assert!(!LoansModule::collaterals(&ALICE, ETH));
+		assert_eq!(LoansModule::get_account_liquidity(&ALICE).ok(), Some((75, 75)));
+	});
+}
+
+#[test]
+fn set_collateral_params_work() {
+	ExtBuilder::default().build().execute_with(|| {
+		System::set_block_number(1);
+		let (collateral_factor, liquidation_threshold, liquidation_bonus) = (
+			Ratio::saturating_from_rational(50, 100),
+			Ratio::saturating_from_rational(60, 100),
+			Ratio::saturating_from_rational(15, 100),
+		);
+		assert_noop!(
+			LoansModule::set_collateral_params(Origin::signed(ALICE), BTC, collateral_factor, liquidation_threshold, liquidation_bonus),
+			DispatchError::BadOrigin
+		);
+		assert_noop!(
+			LoansModule::set_collateral_params(Origin::root(), BTC, liquidation_threshold, collateral_factor, liquidation_bonus),
+			Error::<Runtime>::InvalidCollateralParams
+		);
+		assert_noop!(
+			LoansModule::set_collateral_params(Origin::root(), BTC, collateral_factor, Ratio::saturating_from_rational(101, 100), liquidation_bonus),
+			Error::<Runtime>::InvalidCollateralParams
+		);
+		assert_noop!(
+			LoansModule::set_collateral_params(Origin::root(), BTC, collateral_factor, liquidation_threshold, Ratio::saturating_from_rational(21, 100)),
+			Error::<Runtime>::InvalidCollateralParams
+		);
+		assert_ok!(LoansModule::set_collateral_params(Origin::root(), BTC, collateral_factor, liquidation_threshold, liquidation_bonus));
+		let event = Event::loans(crate::Event::CollateralParamsUpdated(BTC, collateral_factor, liquidation_threshold, liquidation_bonus));
+		assert!(System::events().iter().any(|record| record.event == event));
+
+		assert_ok!(LoansModule::update_deposit(&ALICE, BTC, 100));
+		assert_ok!(LoansModule::set_collateral(&ALICE, BTC, true));
+		assert_ok!(LoansModule::update_deposit(&BOB, DOT, 500));
+		assert_noop!(
+			LoansModule::update_debit(&ALICE, DOT, 51),
+			Error::<Runtime>::InsufficientCollateral
+		);
+		assert_ok!(LoansModule::update_debit(&ALICE, DOT, 50));
+
+		MockPriceSource::set_price(BTC, Some(Price::saturating_from_rational(80, 100)));
+		assert!(LoansModule::is_debit_unsafe(&ALICE));
+	});
+}
+
+#[test]
+fn update_asset_pool_params_work() {
+	ExtBuilder::default().build().execute_with(|| {
+		System::set_block_number(1);
+		let interest_info = InterestInfo {
+			critical_point: Ratio::saturating_from_rational(80, 100),
+			base: Ratio::saturating_from_rational(2, 100),
+			slope_1: Ratio::saturating_from_rational(10, 100),
+			slope_2: Ratio::saturating_from_rational(200, 100),
+		};
+		assert_noop!(
+			LoansModule::update_asset_pool_params(Origin::signed(ALICE), DOT, None, None, None, None, None, None, Some(InterestModel::JumpRate(interest_info))),
+			DispatchError::BadOrigin
+		);
+		assert_noop!(
+			LoansModule::update_asset_pool_params(Origin::root(), CurrencyId::Token(9), None, None, None, None, None, None, None),
+			Error::<Runtime>::InvalidAssetPool
+		);
+		for critical_point in vec![Ratio::zero(), Ratio::one()] {
+			assert_noop!(
+				LoansModule::update_asset_pool_params(
+					Origin::root(), DOT, None, None, None, None, None, None,
+					Some(InterestModel::JumpRate(InterestInfo { critical_point, ..interest_info })),
+				),
+				Error::<Runtime>::InvalidAssetPoolParams
+			);
+		}
+		assert_noop!(
+			LoansModule::update_asset_pool_params(Origin::root(), DOT, None, None, None, None, None, Some(Ratio::saturating_from_rational(11, 10)), None),
+			Error::<Runtime>::InvalidAssetPoolParams
+		);
+
+		assert_ok!(LoansModule::update_asset_pool_params(
+			Origin::root(),
+			DOT,
+			Some(Ratio::saturating_from_rational(80, 100)),
+			None,
+			Some(100),
+			None,
+			None,
+			None,
+			Some(InterestModel::Fixed(Ratio::saturating_from_rational(5, 100))),
+		));
+		let event = Event::loans(crate::Event::AssetPoolParamsUpdated(DOT));
+		assert!(System::events().iter().any(|record| record.event == event));
+
+		let asset_pool_info = LoansModule::asset_pool_infos(DOT);
+		assert_eq!(asset_pool_info.maximum_total_debit_ratio, Ratio::saturating_from_rational(80, 100));
+		assert_eq!(asset_pool_info.minimum_deposit, 10);
+		assert_eq!(asset_pool_info.minimum_debit, 100);
+		assert_eq!(asset_pool_info.reserve_factor, Ratio::saturating_from_rational(10, 100));
+		assert_eq!(asset_pool_info.interest_model, InterestModel::Fixed(Ratio::saturating_from_rational(5, 100)));
+	});
+}
+
+#[test]
+fn list_and_delist_asset_pool_work() {
+	ExtBuilder::default().build().execute_with(|| {
+		System::set_block_number(1);
+		let fil = CurrencyId::Token(4);
+		let asset_pool_info = AssetPoolInfo {
+			total_deposit: 100,
+			..LoansModule::asset_pool_infos(DOT)
+		};
+		assert_eq!(LoansModule::asset_pool_ids(), vec![DOT, BTC, ETH]);
+		assert_noop!(
+			LoansModule::list_asset_pool(Origin::signed(ALICE), fil, asset_pool_info.clone()),
+			DispatchError::BadOrigin
+		);
+		assert_noop!(
+			LoansModule::list_asset_pool(Origin::root(), DOT, asset_pool_info.clone()),
+			Error::<Runtime>::AssetPoolAlreadyListed
+		);
+		assert_noop!(
+			LoansModule::list_asset_pool(Origin::root(), fil, AssetPoolInfo {
+				liquidation_bonus: Ratio::saturating_from_rational(21, 100),
+				..asset_pool_info.clone()
+			}),
+			Error::<Runtime>::InvalidCollateralParams
+		);
+		assert_noop!(
+			LoansModule::update_deposit(&ALICE, fil, 100),
+			Error::<Runtime>::AssetPoolNotListed
+		);
+
+		// accounting of a new pool starts from scratch
+		assert_ok!(LoansModule::list_asset_pool(Origin::root(), fil, asset_pool_info.clone()));
+		let event = Event::loans(crate::Event::AssetPoolListed(fil));
+		assert!(System::events().iter().any(|record| record.event == event));
+		assert_eq!(LoansModule::asset_pool_ids(), vec![DOT, BTC, ETH, fil]);
+		assert_eq!(LoansModule::asset_pool_infos(fil).total_deposit, 0);
+
+		assert_ok!(LoansModule::update_deposit(&ALICE, DOT, 200));
+		assert_ok!(LoansModule::set_collateral(&ALICE, DOT, true));
+		assert_ok!(LoansModule::update_debit(&ALICE, DOT, 50));
+
+		assert_noop!(
+			LoansModule::delist_asset_pool(Origin::root(), CurrencyId::Token(9)),
+			Error::<Runtime>::AssetPoolNotListed
+		);
+		assert_ok!(LoansModule::delist_asset_pool(Origin::root(), DOT));
+		let event = Event::loans(crate::Event::AssetPoolDelisted(DOT));
+		assert!(System::events().iter().any(|record| record.event == event));
+		assert_eq!(LoansModule::asset_pool_statuses(DOT), Some(AssetPoolStatus::Delisted));
+
+		// no new deposits or debits
+		assert_noop!(
+			LoansModule::update_deposit(&ALICE, DOT, 10),
+			Error::<Runtime>::AssetPoolNotListed
+		);
+		assert_noop!(
+			LoansModule::update_debit(&ALICE, DOT, 10),
+			Error::<Runtime>::AssetPoolNotListed
+		);
+		// repay and withdraw keep working
+		assert_ok!(LoansModule::update_debit(&ALICE, DOT, -50));
+		assert_ok!(LoansModule::update_deposit(&ALICE, DOT, -100));
+
+		// listing again keeps the accounting
+		assert_ok!(LoansModule::list_asset_pool(Origin::root(), DOT, asset_pool_info));
+		assert_eq!(LoansModule::asset_pool_infos(DOT).total_deposit, 100);
+		assert_ok!(LoansModule::update_deposit(&ALICE, DOT, 10));
+	});
+}
+
+#[test]
+fn minimum_and_close_all_work() {
+	ExtBuilder::default().build().execute_with(|| {
+		assert_noop!(
+			LoansModule::update_deposit(&ALICE, DOT, 9),
+			Error::<Runtime>::DepositTooLow
+		);
+		assert_ok!(LoansModule::update_deposit(&ALICE, DOT, 100));
+		assert_noop!(
+			LoansModule::update_deposit(&ALICE, DOT, -95),
+			Error::<Runtime>::DepositTooLow
+		);
+		assert_ok!(LoansModule::set_collateral(&ALICE, DOT, true));
+		assert_noop!(
+			LoansModule::update_debit(&ALICE, DOT, 5),
+			Error::<Runtime>::DebitTooLow
+		);
+		assert_ok!(LoansModule::update_debit(&ALICE, DOT, 50));
+		assert_noop!(
+			LoansModule::update_debit(&ALICE, DOT, -45),
+			Error::<Runtime>::DebitTooLow
+		);
+		assert_noop!(LoansModule::repay_all(&BOB, DOT), Error::<Runtime>::NoDebit);
+		assert_noop!(LoansModule::withdraw_all(&BOB, DOT), Error::<Runtime>::NoDeposit);
+
+		// accrued interest is included
+		Timestamp::set_timestamp(SECONDS_PER_YEAR * 1000);
+		assert_eq!(LoansModule::repay_all(&ALICE, DOT), Ok(51));
+		assert_eq!(LoansModule::loans(DOT, &ALICE).debit, 0);
+		assert_eq!(LoansModule::withdraw_all(&ALICE, DOT), Ok(101));
+		assert_eq!(LoansModule::loans(DOT, &ALICE), LoanInfo { deposit: 0, debit: 0 });
+		assert_eq!(Tokens::free_balance(DOT, &ALICE), 1000);
+		assert_eq!(Tokens::free_balance(DOT, &LoansModule::account_id()), 0);
+	});
+}
+
+#[test]
+fn interest_reconciles_with_deposits_and_reserves_work() {
+	ExtBuilder::default().build().execute_with(|| {
+		let unit = 10u128.pow(18);
+		let day = 24 * 60 * 60 * 1000;
+		assert_ok!(Tokens::deposit(DOT, &ALICE, 1_000 * unit));
+		assert_ok!(Tokens::deposit(DOT, &BOB, 1_000 * unit));
+
+		assert_ok!(LoansModule::update_deposit(&ALICE, DOT, (500 * unit) as Amount));
+		assert_ok!(LoansModule::update_deposit(&BOB, DOT, (300 * unit) as Amount));
+		assert_ok!(LoansModule::set_collateral(&ALICE, DOT, true));
+		assert_ok!(LoansModule::set_collateral(&BOB, DOT, true));
+		assert_ok!(LoansModule::update_debit(&ALICE, DOT, (350 * unit) as Amount));
+		assert_ok!(LoansModule::update_debit(&BOB, DOT, (200 * unit) as Amount));
+
+		let real_balances = || {
+			let asset_pool_info = LoansModule::asset_pool_infos(DOT);
+			let (alice, bob) = (LoansModule::loans(DOT, &ALICE), LoansModule::loans(DOT, &BOB));
+			(
+				asset_pool_info.deposit_rate.saturating_mul_int(alice.deposit)
+					+ asset_pool_info.deposit_rate.saturating_mul_int(bob.deposit),
+				asset_pool_info.debit_rate.saturating_mul_int(alice.debit)
+					+ asset_pool_info.debit_rate.saturating_mul_int(bob.debit),
+			)
+		};
+
+		for days in 1..=365u64 {
+			Timestamp::set_timestamp(days * day);
+			if days % 30 == 0 {
+				assert_ok!(LoansModule::update_debit(&BOB, DOT, -(unit as Amount)));
+				assert_ok!(LoansModule::update_deposit(&ALICE, DOT, unit as Amount));
+			} else {
+				LoansModule::accrue_interest(DOT);
+			}
+
+			// cash + what borrowers owe == what depositors own + reserves
+			let asset_pool_info = LoansModule::asset_pool_infos(DOT);
+			assert_eq!(
+				Tokens::free_balance(DOT, &LoansModule::account_id()) + asset_pool_info.total_debit,
+				asset_pool_info.total_deposit + LoansModule::total_reserves(DOT),
+			);
+
+			// totals follow the interest indices up to rounding
+			let (deposits, debits) = real_balances();
+			assert!(asset_pool_info.total_deposit.max(deposits) - asset_pool_info.total_deposit.min(deposits) < unit / 1_000_000);
+			assert!(asset_pool_info.total_debit.max(debits) - asset_pool_info.total_debit.min(debits) < unit / 1_000_000);
+		}
+
+		// interest paid by borrowers == interest earned by depositors + reserves
+		let (deposits, debits) = real_balances();
+		let debit_interest = debits - (550 - 12) * unit;
+		let deposit_interest = deposits - (800 + 12) * unit;
+		let reserves = LoansModule::total_reserves(DOT);
+		assert!(!reserves.is_zero());
+		let paid = debit_interest;
+		let earned = deposit_interest + reserves;
+		assert!(paid.max(earned) - paid.min(earned) < unit / 1_000_000);
+		assert!(Ratio::saturating_from_rational(reserves, debit_interest) > Ratio::saturating_from_rational(99, 1000));
+		assert!(Ratio::saturating_from_rational(reserves, debit_interest) < Ratio::saturating_from_rational(101, 1000));
+	});
+}
+
+#[test]
+fn withdraw_reserves_work() {
+	ExtBuilder::default().build().execute_with(|| {
+		System::set_block_number(1);
+		let unit = 10u128.pow(18);
+		assert_ok!(Tokens::deposit(DOT, &ALICE, 1_000 * unit));
+		assert_ok!(LoansModule::update_deposit(&ALICE, DOT, (500 * unit) as Amount));
+		assert_ok!(LoansModule::set_collateral(&ALICE, DOT, true));
+		assert_ok!(LoansModule::update_debit(&ALICE, DOT, (300 * unit) as Amount));
+
+		Timestamp::set_timestamp(SECONDS_PER_YEAR * 1000);
+		LoansModule::accrue_interest(DOT);
+		let reserves = LoansModule::total_reserves(DOT);
+		assert!(!reserves.is_zero());
+
+		assert_noop!(
+			LoansModule::withdraw_reserves(Origin::signed(ALICE), DOT, reserves, TREASURY),
+			DispatchError::BadOrigin
+		);
+		assert_noop!(
+			LoansModule::withdraw_reserves(Origin::root(), DOT, reserves + 1, TREASURY),
+			Error::<Runtime>::InsufficientReserves
+		);
+
+		assert_ok!(LoansModule::withdraw_reserves(Origin::root(), DOT, reserves, TREASURY));
+		let event = Event::loans(crate::Event::ReservesWithdrawn(DOT, reserves, TREASURY));
+		assert!(System::events().iter().any(|record| record.event == event));
+		assert_eq!(Tokens::free_balance(DOT, &TREASURY), reserves);
+		assert_eq!(LoansModule::total_reserves(DOT), 0);
+
+		// depositors can still be paid out in full
+		let asset_pool_info = LoansModule::asset_pool_infos(DOT);
+		assert_eq!(
+			Tokens::free_balance(DOT, &LoansModule::account_id()) + asset_pool_info.total_debit,
+			asset_pool_info.total_deposit,
+		);
+	});
+}
+
+#[test]
+fn withdraw_reserves_limited_by_cash_work() {
+	ExtBuilder::default().build().execute_with(|| {
+		let unit = 10u128.pow(18);
+		assert_ok!(Tokens::deposit(DOT, &BOB, 1_000 * unit));
+		assert_ok!(Tokens::deposit(BTC, &ALICE, 1_000 * unit));
+		assert_ok!(LoansModule::update_deposit(&BOB, DOT, (500 * unit) as Amount));
+		assert_ok!(LoansModule::update_deposit(&ALICE, BTC, (1_000 * unit) as Amount));
+		assert_ok!(LoansModule::set_collateral(&ALICE, BTC, true));
+		assert_ok!(LoansModule::update_debit(&ALICE, DOT, (300 * unit) as Amount));
+
+		Timestamp::set_timestamp(SECONDS_PER_YEAR * 1000);
+		LoansModule::accrue_interest(DOT);
+		let reserves = LoansModule::total_reserves(DOT);
+		assert!(!reserves.is_zero());
+
+		// the depositor takes all cash left, the reserves are still owed by the borrower
+		assert_ok!(LoansModule::update_deposit(&BOB, DOT, -((200 * unit) as Amount)));
+		assert_eq!(Tokens::free_balance(DOT, &LoansModule::account_id()), 0);
+		assert_noop!(
+			LoansModule::withdraw_reserves(Origin::root(), DOT, reserves, TREASURY),
+			Error::<Runtime>::InsufficientCash
+		);
+
+		assert_ok!(LoansModule::update_debit(&ALICE, DOT, -((100 * unit) as Amount)));
+		assert_ok!(LoansModule::withdraw_reserves(Origin::root(), DOT, reserves, TREASURY));
+		assert_eq!(Tokens::free_balance(DOT, &TREASURY), reserves);
+	});
+}
+
+#[test]
+fn jump_rate_model_work() {
+	let model = InterestInfo {
+		critical_point: Ratio::saturating_from_rational(80, 100),
+		base: Ratio::saturating_from_rational(2, 100),
+		slope_1: Ratio::saturating_from_rational(8, 100),
+		slope_2: Ratio::saturating_from_rational(100, 100),
+	};
+	assert!(model.is_valid());
+	assert_eq!(model.debit_apy(Ratio::zero()), Ratio::saturating_from_rational(2, 100));
+	assert_eq!(model.debit_apy(Ratio::saturating_from_rational(40, 100)), Ratio::saturating_from_rational(6, 100));
+	assert_eq!(model.debit_apy(Ratio::saturating_from_rational(80, 100)), Ratio::saturating_from_rational(10, 100));
+	assert_eq!(model.debit_apy(Ratio::saturating_from_rational(90, 100)), Ratio::saturating_from_rational(60, 100));
+	assert_eq!(model.debit_apy(Ratio::one()), Ratio::saturating_from_rational(110, 100));
+
+	assert!(!InterestInfo { critical_point: Ratio::zero(), ..model }.is_valid());
+	assert!(!InterestInfo { critical_point: Ratio::one(), ..model }.is_valid());
+}
+
+#[test]
+fn linear_model_work() {
+	let model = InterestModel::Linear(LinearInterest {
+		base: Ratio::saturating_from_rational(1, 100),
+		slope: Ratio::saturating_from_rational(20, 100),
+	});
+	assert!(model.is_valid());
+	assert_eq!(model.debit_apy(Ratio::zero()), Ratio::saturating_from_rational(1, 100));
+	assert_eq!(model.debit_apy(Ratio::saturating_from_rational(50, 100)), Ratio::saturating_from_rational(11, 100));
+	assert_eq!(model.debit_apy(Ratio::one()), Ratio::saturating_from_rational(21, 100));
+}
+
+#[test]
+fn fixed_model_work() {
+	let model = InterestModel::Fixed(Ratio::saturating_from_rational(3, 100));
+	assert!(model.is_valid());
+	assert_eq!(model.debit_apy(Ratio::zero()), Ratio::saturating_from_rational(3, 100));
+	assert_eq!(model.debit_apy(Ratio::saturating_from_rational(95, 100)), Ratio::saturating_from_rational(3, 100));
+}
+
+#[test]
+fn accrue_interest_with_selected_model_work() {
+	ExtBuilder::default().build().execute_with(|| {
+		assert_ok!(LoansModule::update_asset_pool_params(
+			Origin::root(), DOT, None, None, None, None, None, None,
+			Some(InterestModel::Fixed(Ratio::saturating_from_rational(5, 100))),
+		));
+		assert_ok!(LoansModule::update_deposit(&ALICE, DOT, 100));
+		assert_ok!(LoansModule::set_collateral(&ALICE, DOT, true));
+		assert_ok!(LoansModule::update_debit(&ALICE, DOT, 20));
+
+		Timestamp::set_timestamp(SECONDS_PER_YEAR * 1000);
+		LoansModule::accrue_interest(DOT);
+		assert_eq!(LoansModule::asset_pool_infos(DOT).debit_apy, Ratio::saturating_from_rational(5, 100));
+		assert_eq!(LoansModule::asset_pool_infos(DOT).debit_rate, Ratio::saturating_from_rational(105, 100));
+		assert_eq!(LoansModule::asset_pool_infos(BTC).debit_rate, Ratio::one());
+	});
+}
+
+#[test]
+fn supply_and_borrow_caps_work() {
+	ExtBuilder::default().build().execute_with(|| {
+		assert_ok!(LoansModule::update_asset_pool_params(
+			Origin::root(), DOT, None, None, None, Some(300), Some(100), None, None,
+		));
+		assert_eq!(LoansModule::asset_pool_infos(DOT).supply_cap, 300);
+		assert_eq!(LoansModule::asset_pool_infos(DOT).borrow_cap, 100);
+
+		assert_ok!(LoansModule::update_deposit(&ALICE, DOT, 200));
+		assert_noop!(
+			LoansModule::update_deposit(&BOB, DOT, 101),
+			Error::<Runtime>::SupplyCapExceeded
+		);
+		assert_ok!(LoansModule::update_deposit(&BOB, DOT, 100));
+
+		assert_ok!(LoansModule::set_collateral(&ALICE, DOT, true));
+		assert_noop!(
+			LoansModule::update_debit(&ALICE, DOT, 101),
+			Error::<Runtime>::BorrowCapExceeded
+		);
+		assert_ok!(LoansModule::update_debit(&ALICE, DOT, 100));
+
+		// withdraw and repay are never capped
+		assert_ok!(LoansModule::update_debit(&ALICE, DOT, -50));
+		assert_ok!(LoansModule::update_deposit(&BOB, DOT, -50));
+
+		// 0 removes the caps
+		assert_ok!(LoansModule::update_asset_pool_params(
+			Origin::root(), DOT, None, None, None, Some(0), Some(0), None, None,
+		));
+		assert_ok!(LoansModule::update_deposit(&BOB, DOT, 500));
+		assert_ok!(LoansModule::update_debit(&ALICE, DOT, 100));
+	});
+}
+
+#[test]
+fn set_operation_paused_work() {
+	ExtBuilder::default().build().execute_with(|| {
+		System::set_block_number(1);
+		assert_noop!(
+			LoansModule::set_operation_paused(Origin::signed(ALICE), DOT, AssetPoolOperation::Deposit, true),
+			DispatchError::BadOrigin
+		);
+		assert_noop!(
+			LoansModule::set_operation_paused(Origin::root(), CurrencyId::Token(9), AssetPoolOperation::Deposit, true),
+			Error::<Runtime>::InvalidAssetPool
+		);
+
+		assert_ok!(LoansModule::update_deposit(&ALICE, DOT, 200));
+		assert_ok!(LoansModule::set_collateral(&ALICE, DOT, true));
+		assert_ok!(LoansModule::update_debit(&ALICE, DOT, 50));
+
+		assert_ok!(LoansModule::set_operation_paused(Origin::root(), DOT, AssetPoolOperation::Deposit, true));
+		let event = Event::loans(crate::Event::OperationPausedUpdated(DOT, AssetPoolOperation::Deposit, true));
+		assert!(System::events().iter().any(|record| record.event == event));
+		assert_noop!(LoansModule::update_deposit(&ALICE, DOT, 10), Error::<Runtime>::OperationPaused);
+		// other operations and pools are not affected
+		assert_ok!(LoansModule::update_deposit(&ALICE, DOT, -10));
+		assert_ok!(LoansModule::update_deposit(&ALICE, BTC, 10));
+
+		assert_ok!(LoansModule::set_operation_paused(Origin::root(), DOT, AssetPoolOperation::Withdraw, true));
+		assert_noop!(LoansModule::update_deposit(&ALICE, DOT, -10), Error::<Runtime>::OperationPaused);
+		assert_ok!(LoansModule::set_operation_paused(Origin::root(), DOT, AssetPoolOperation::Borrow, true));
+		assert_noop!(LoansModule::update_debit(&ALICE, DOT, 10), Error::<Runtime>::OperationPaused);
+		assert_ok!(LoansModule::set_operation_paused(Origin::root(), DOT, AssetPoolOperation::Repay, true));
+		assert_noop!(LoansModule::update_debit(&ALICE, DOT, -10), Error::<Runtime>::OperationPaused);
+
+		assert_ok!(LoansModule::set_operation_paused(Origin::root(), DOT, AssetPoolOperation::Deposit, false));
+		assert!(!LoansModule::paused_operations(DOT, AssetPoolOperation::Deposit));
+		assert_ok!(LoansModule::update_deposit(&ALICE, DOT, 10));
+	});
+}
+
+#[test]
+fn paused_liquidation_work() {
+	ExtBuilder::default().build().execute_with(|| {
+		assert_ok!(LoansModule::update_deposit(&ALICE, BTC, 100));
+		assert_ok!(LoansModule::set_collateral(&ALICE, BTC, true));
+		assert_ok!(LoansModule::update_deposit(&BOB, DOT, 500));
+		assert_ok!(LoansModule::update_debit(&ALICE, DOT, 70));
+		MockPriceSource::set_price(BTC, Some(Price::saturating_from_rational(80, 100)));
+
+		assert_ok!(LoansModule::set_operation_paused(Origin::root(), DOT, AssetPoolOperation::Liquidate, true));
+		let call = crate::Call::<Runtime>::liquidate(DOT, ALICE);
+		assert_eq!(
+			LoansModule::validate_unsigned(TransactionSource::External, &call),
+			InvalidTransaction::Stale.into(),
+		);
+		assert_noop!(
+			LoansModule::liquidate_unsafe_debit(ALICE, DOT),
+			Error::<Runtime>::OperationPaused
+		);
+
+		assert_ok!(LoansModule::set_operation_paused(Origin::root(), DOT, AssetPoolOperation::Liquidate, false));
+		assert!(LoansModule::validate_unsigned(TransactionSource::External, &call).is_ok());
+		assert_ok!(LoansModule::liquidate_unsafe_debit(ALICE, DOT));
+	});
+}
+
+#[test]
+fn flash_loan_work() {
+	ExtBuilder::default().build().execute_with(|| {
+		System::set_block_number(1);
+		let trader: AccountId = 4;
+		assert_ok!(LoansModule::update_deposit(&ALICE, DOT, 500));
+
+		// nothing to repay with
+		assert_noop!(LoansModule::flash_loan(&trader, DOT, 100, 0), Error::<Runtime>::FlashLoanNotRepaid);
+		assert_noop!(LoansModule::flash_loan(&trader, DOT, 501, 5), orml_tokens::Error::<Runtime>::BalanceTooLow);
+
+		assert_eq!(LoansModule::flash_loan(&trader, DOT, 100, 5), Ok(1));
+		let event = Event::loans(crate::Event::FlashLoan(trader, DOT, 100, 1));
+		assert!(System::events().iter().any(|record| record.event == event));
+		assert_eq!(Tokens::free_balance(DOT, &trader), 4);
+		assert_eq!(Tokens::free_balance(DOT, &LoansModule::account_id()), 501);
+		assert_eq!(LoansModule::total_reserves(DOT), 1);
+		assert_eq!(LoansModule::asset_pool_infos(DOT).total_deposit, 500);
+
+		// the fee rounds up, small loans are not free
+		assert_eq!(LoansModule::flash_loan(&trader, DOT, 250, 0), Ok(3));
+		assert_eq!(LoansModule::flash_loan(&trader, DOT, 10, 0), Ok(1));
+		assert_eq!(Tokens::free_balance(DOT, &trader), 0);
+		assert_eq!(LoansModule::total_reserves(DOT), 5);
+		assert_noop!(LoansModule::flash_loan(&trader, DOT, 10, 0), Error::<Runtime>::FlashLoanNotRepaid);
+
+		assert_ok!(LoansModule::set_operation_paused(Origin::root(), DOT, AssetPoolOperation::Borrow, true));
+		assert_noop!(LoansModule::flash_loan(&trader, DOT, 100, 5), Error::<Runtime>::OperationPaused);
+		assert_ok!(LoansModule::delist_asset_pool(Origin::root(), BTC));
+		assert_noop!(LoansModule::flash_loan(&trader, BTC, 100, 5), Error::<Runtime>::AssetPoolNotListed);
+	});
+}
+
+#[test]
+fn deposit_for_and_repay_on_behalf_work() {
+	ExtBuilder::default().build().execute_with(|| {
+		System::set_block_number(1);
+		assert_ok!(LoansModule::deposit_for(&BOB, &ALICE, DOT, 100));
+		let event = Event::loans(crate::Event::DepositUpdated(ALICE, DOT, 100));
+		assert!(System::events().iter().any(|record| record.event == event));
+		assert_eq!(LoansModule::loans(DOT, &ALICE), LoanInfo { deposit: 100, debit: 0 });
+		assert_eq!(LoansModule::loans(DOT, &BOB), LoanInfo { deposit: 0, debit: 0 });
+		assert_eq!(Tokens::free_balance(DOT, &ALICE), 1000);
+		assert_eq!(Tokens::free_balance(DOT, &BOB), 900);
+		assert_eq!(LoansModule::asset_pool_infos(DOT).total_deposit, 100);
+		assert_noop!(LoansModule::deposit_for(&BOB, &TREASURY, DOT, 5), Error::<Runtime>::DepositTooLow);
+
+		assert_ok!(LoansModule::set_collateral(&ALICE, DOT, true));
+		assert_ok!(LoansModule::update_debit(&ALICE, DOT, 50));
+		assert_noop!(LoansModule::repay_on_behalf(&BOB, &ALICE, DOT, 45), Error::<Runtime>::DebitTooLow);
+		assert_ok!(LoansModule::repay_on_behalf(&BOB, &ALICE, DOT, 30));
+		let event = Event::loans(crate::Event::DebitUpdated(ALICE, DOT, -30));
+		assert!(System::events().iter().any(|record| record.event == event));
+		assert_eq!(LoansModule::loans(DOT, &ALICE), LoanInfo { deposit: 100, debit: 20 });
+		assert_eq!(Tokens::free_balance(DOT, &ALICE), 1050);
+		assert_eq!(Tokens::free_balance(DOT, &BOB), 870);
+		assert_eq!(LoansModule::asset_pool_infos(DOT).total_debit, 20);
+
+		assert_ok!(LoansModule::set_operation_paused(Origin::root(), DOT, AssetPoolOperation::Repay, true));
+		assert_noop!(LoansModule::repay_on_behalf(&BOB, &ALICE, DOT, 20), Error::<Runtime>::OperationPaused);
+	});
+}
+
+#[test]
+fn credit_loan_work() {
+	ExtBuilder::default().build().execute_with(|| {
+		System::set_block_number(1);
+		assert_ok!(LoansModule::update_deposit(&BOB, DOT, 500));
+
+		assert_noop!(LoansModule::request_credit_loan(&ALICE, DOT, 5, 1000), Error::<Runtime>::InvalidCreditLoanTerms);
+		assert_noop!(LoansModule::request_credit_loan(&ALICE, DOT, 100, 0), Error::<Runtime>::InvalidCreditLoanTerms);
+		assert_eq!(LoansModule::request_credit_loan(&ALICE, DOT, 100, 1000), Ok(0));
+		let event = Event::loans(crate::Event::CreditLoanRequested(0, ALICE, DOT, 100, 1000));
+		assert!(System::events().iter().any(|record| record.event == event));
+		assert_noop!(LoansModule::draw_credit_loan(&ALICE, 0, 80), Error::<Runtime>::InsufficientGuarantee);
+
+		assert_ok!(LoansModule::guarantee_credit_loan(&BOB, 0, 60));
+		assert_ok!(LoansModule::guarantee_credit_loan(&CHARLIE, 0, 40));
+		assert_eq!(LoansModule::withdraw_guarantee(&CHARLIE, 0), Ok(40));
+		assert_noop!(LoansModule::withdraw_guarantee(&CHARLIE, 0), Error::<Runtime>::NoGuarantee);
+		assert_ok!(LoansModule::guarantee_credit_loan(&CHARLIE, 0, 40));
+		assert_eq!(LoansModule::guarantees(0, &BOB), 60);
+		assert_eq!(LoansModule::guarantees(0, &CHARLIE), 40);
+		assert_eq!(Tokens::free_balance(DOT, &LoansModule::guarantee_account_id()), 100);
+
+		assert_noop!(LoansModule::draw_credit_loan(&BOB, 0, 80), Error::<Runtime>::NotCreditLoanBorrower);
+		assert_ok!(LoansModule::draw_credit_loan(&ALICE, 0, 80));
+		assert_eq!(LoansModule::credit_loans(0).unwrap().status, CreditLoanStatus::Active(1000));
+		assert_eq!(LoansModule::credit_loans(0).unwrap().debit, 80);
+		assert_eq!(LoansModule::asset_pool_infos(DOT).total_debit, 80);
+		assert_eq!(Tokens::free_balance(DOT, &ALICE), 1080);
+		// the borrower's own loan is untouched
+		assert_eq!(LoansModule::loans(DOT, &ALICE), LoanInfo { deposit: 0, debit: 0 });
+		assert_noop!(LoansModule::draw_credit_loan(&ALICE, 0, 30), Error::<Runtime>::CreditLimitExceeded);
+		assert_noop!(LoansModule::withdraw_guarantee(&BOB, 0), Error::<Runtime>::GuaranteeLocked);
+		assert_noop!(LoansModule::close_credit_loan(&ALICE, 0), Error::<Runtime>::CreditLoanNotRepaid);
+		assert_noop!(LoansModule::default_credit_loan(0), Error::<Runtime>::CreditLoanNotOverdue);
+
+		assert_ok!(LoansModule::repay_credit_loan(&BOB, 0, 30));
+		assert_ok!(LoansModule::repay_credit_loan(&ALICE, 0, 50));
+		assert_eq!(LoansModule::credit_loans(0).unwrap().debit, 0);
+		assert_eq!(LoansModule::asset_pool_infos(DOT).total_debit, 0);
+
+		assert_ok!(LoansModule::close_credit_loan(&ALICE, 0));
+		assert_eq!(LoansModule::credit_loans(0), None);
+		assert_eq!(LoansModule::guarantees(0, &BOB), 0);
+		assert_eq!(Tokens::free_balance(DOT, &LoansModule::guarantee_account_id()), 0);
+		assert_eq!(Tokens::free_balance(DOT, &BOB), 470);
+		assert_eq!(Tokens::free_balance(DOT, &CHARLIE), 1000);
+		assert_eq!(Tokens::free_balance(DOT, &ALICE), 1030);
+	});
+}
+
+#[test]
+fn credit_loan_default_covered_by_guarantors_work() {
+	ExtBuilder::default().build().execute_with(|| {
+		System::set_block_number(1);
+		assert_ok!(LoansModule::update_asset_pool_params(
+			Origin::root(), DOT, None, None, None, None, None, None, Some(InterestModel::Fixed(Ratio::zero())),
+		));
+		assert_ok!(LoansModule::update_deposit(&BOB, DOT, 500));
+		assert_ok!(LoansModule::request_credit_loan(&ALICE, DOT, 100, 1000));
+		assert_noop!(LoansModule::default_credit_loan(0), Error::<Runtime>::CreditLoanNotActive);
+		assert_ok!(LoansModule::guarantee_credit_loan(&BOB, 0, 90));
+		assert_ok!(LoansModule::guarantee_credit_loan(&CHARLIE, 0, 60));
+		assert_ok!(LoansModule::draw_credit_loan(&ALICE, 0, 100));
+
+		Timestamp::set_timestamp(1000 * 1000);
+		assert_noop!(LoansModule::draw_credit_loan(&ALICE, 0, 10), Error::<Runtime>::CreditLoanOverdue);
+		assert_ok!(LoansModule::default_credit_loan(0));
+		let event = Event::loans(crate::Event::CreditLoanDefaulted(0, 100, 0));
+		assert!(System::events().iter().any(|record| record.event == event));
+
+		// 100 debit covered 60 / 40 in proportion to the 90 / 60 guarantees
+		assert_eq!(Tokens::free_balance(DOT, &BOB), 440);
+		assert_eq!(Tokens::free_balance(DOT, &CHARLIE), 960);
+		assert_eq!(Tokens::free_balance(DOT, &LoansModule::guarantee_account_id()), 0);
+		assert_eq!(Tokens::free_balance(DOT, &LoansModule::account_id()), 500);
+		assert_eq!(LoansModule::asset_pool_infos(DOT).total_debit, 0);
+		assert_eq!(LoansModule::credit_loans(0), None);
+		assert_eq!(LoansModule::loans(DOT, &ALICE), LoanInfo { deposit: 0, debit: 0 });
+	});
+}
+
+#[test]
+fn credit_loan_default_shortfall_is_written_off_work() {
+	ExtBuilder::default().build().execute_with(|| {
+		System::set_block_number(1);
+		assert_ok!(LoansModule::update_asset_pool_params(
+			Origin::root(), DOT, None, None, None, None, None, None, Some(InterestModel::Fixed(Ratio::one())),
+		));
+		assert_ok!(LoansModule::update_deposit(&BOB, DOT, 500));
+		assert_ok!(LoansModule::request_credit_loan(&ALICE, DOT, 100, 1000));
+		assert_ok!(LoansModule::guarantee_credit_loan(&BOB, 0, 60));
+		assert_ok!(LoansModule::guarantee_credit_loan(&CHARLIE, 0, 40));
+		assert_ok!(LoansModule::draw_credit_loan(&ALICE, 0, 100));
+
+		// a year at 100% doubles the debit
+		Timestamp::set_timestamp(SECONDS_PER_YEAR * 1000);
+		assert_ok!(LoansModule::default_credit_loan(0));
+		assert_eq!(Tokens::free_balance(DOT, &BOB), 440);
+		assert_eq!(Tokens::free_balance(DOT, &CHARLIE), 960);
+		assert_eq!(LoansModule::asset_pool_infos(DOT).debit_rate, Ratio::saturating_from_rational(2, 1));
+		assert_eq!(LoansModule::asset_pool_infos(DOT).total_debit, 0);
+		assert_eq!(LoansModule::loans(DOT, &ALICE), LoanInfo { deposit: 0, debit: 0 });
+		// the shortfall of 100 takes the 10 of reserves and the 90 of interest depositors earned
+		assert_eq!(LoansModule::total_reserves(DOT), 0);
+		assert_eq!(LoansModule::asset_pool_infos(DOT).total_deposit, 500);
+		assert_eq!(Tokens::free_balance(DOT, &LoansModule::account_id()), 500);
+		assert!(System::events().iter().any(|record| record.event == Event::loans(crate::Event::CreditLoanDefaulted(0, 100, 100))));
+	});
+}
+
+#[test]
+fn fixed_term_loan_work() {
+	ExtBuilder::default().build().execute_with(|| {
+		System::set_block_number(1);
+		assert_ok!(LoansModule::update_asset_pool_params(
+			Origin::root(), DOT, None, None, None, None, None, None,
+			Some(InterestModel::Fixed(Ratio::saturating_from_rational(10, 100))),
+		));
+		assert_ok!(LoansModule::update_deposit(&BOB, DOT, 500));
+		assert_ok!(LoansModule::update_deposit(&ALICE, BTC, 200));
+		assert_ok!(LoansModule::set_collateral(&ALICE, BTC, true));
+
+		assert_noop!(LoansModule::borrow_fixed_term(&ALICE, DOT, 100, 0), Error::<Runtime>::InvalidFixedTerm);
+		assert_noop!(LoansModule::borrow_fixed_term(&ALICE, DOT, 5, SECONDS_PER_YEAR), Error::<Runtime>::DebitTooLow);
+		assert_ok!(LoansModule::borrow_fixed_term(&ALICE, DOT, 100, SECONDS_PER_YEAR));
+		let rate = Ratio::saturating_from_rational(10, 100);
+		let event = Event::loans(crate::Event::FixedTermLoanOpened(ALICE, DOT, 100, rate, SECONDS_PER_YEAR));
+		assert!(System::events().iter().any(|record| record.event == event));
+		assert_eq!(
+			LoansModule::fixed_term_loans(DOT, &ALICE),
+			Some(FixedTermLoan { principal: 100, debit: 100, rate, maturity: SECONDS_PER_YEAR, accrued_at: 0 })
+		);
+		assert_eq!(LoansModule::total_fixed_term_debits(DOT), 100);
+		assert_eq!(LoansModule::asset_pool_infos(DOT).total_debit, 0);
+		assert_eq!(Tokens::free_balance(DOT, &ALICE), 1100);
+		assert_noop!(
+			LoansModule::borrow_fixed_term(&ALICE, DOT, 10, SECONDS_PER_YEAR),
+			Error::<Runtime>::FixedTermLoanExists
+		);
+		// the fixed-term debit counts against the collateral
+		assert_noop!(LoansModule::update_debit(&ALICE, DOT, 60), Error::<Runtime>::InsufficientCollateral);
+
+		// the rate stays locked
+		assert_ok!(LoansModule::update_asset_pool_params(
+			Origin::root(), DOT, None, None, None, None, None, None,
+			Some(InterestModel::Fixed(Ratio::saturating_from_rational(50, 100))),
+		));
+		Timestamp::set_timestamp(SECONDS_PER_YEAR * 1000);
+		assert_eq!(LoansModule::current_fixed_term_debit(&LoansModule::fixed_term_loans(DOT, &ALICE).unwrap()), 110);
+		assert_noop!(LoansModule::repay_fixed_term(&ALICE, DOT, 111), Error::<Runtime>::DebitTooLow);
+		// the interest accrues to depositors over time, not at repayment
+		LoansModule::accrue_interest(DOT);
+		assert_eq!(LoansModule::total_fixed_term_debits(DOT), 110);
+		assert_eq!(LoansModule::total_reserves(DOT), 1);
+		assert_eq!(LoansModule::asset_pool_infos(DOT).total_deposit, 509);
+
+		assert_ok!(LoansModule::repay_fixed_term(&ALICE, DOT, 110));
+		assert_eq!(LoansModule::fixed_term_loans(DOT, &ALICE), None);
+		assert_eq!(LoansModule::total_fixed_term_debits(DOT), 0);
+		assert_eq!(Tokens::free_balance(DOT, &ALICE), 990);
+		// 10 interest, 1 to reserves and 9 to depositors
+		assert_eq!(LoansModule::total_reserves(DOT), 1);
+		assert_eq!(LoansModule::asset_pool_infos(DOT).total_deposit, 509);
+		assert_eq!(LoansModule::withdraw_all(&BOB, DOT), Ok(509));
+	});
+}
+
+#[test]
+fn overdue_fixed_term_loan_liquidation_work() {
+	ExtBuilder::default().build().execute_with(|| {
+		System::set_block_number(1);
+		assert_ok!(LoansModule::update_asset_pool_params(
+			Origin::root(), DOT, None, None, None, None, None, None,
+			Some(InterestModel::Fixed(Ratio::saturating_from_rational(10, 100))),
+		));
+		assert_ok!(LoansModule::update_deposit(&BOB, DOT, 500));
+		assert_ok!(LoansModule::update_deposit(&ALICE, DOT, 200));
+		assert_ok!(LoansModule::set_collateral(&ALICE, DOT, true));
+		assert_ok!(LoansModule::borrow_fixed_term(&ALICE, DOT, 100, SECONDS_PER_YEAR));
+
+		// still in the grace period
+		Timestamp::set_timestamp((SECONDS_PER_YEAR + 500) * 1000);
+		assert!(!LoansModule::is_liquidatable(DOT, &ALICE));
+		let call = crate::Call::<Runtime>::liquidate(DOT, ALICE);
+		assert_eq!(
+			LoansModule::validate_unsigned(TransactionSource::External, &call),
+			InvalidTransaction::Stale.into(),
+		);
+		assert_noop!(LoansModule::liquidate_unsafe_debit(ALICE, DOT), Error::<Runtime>::MustBeUnsafe);
+
+		// a year overdue at 10% + 10% penalty
+		Timestamp::set_timestamp(SECONDS_PER_YEAR * 2 * 1000);
+		assert_eq!(LoansModule::current_fixed_term_debit(&LoansModule::fixed_term_loans(DOT, &ALICE).unwrap()), 130);
+		assert!(!LoansModule::is_debit_unsafe(&ALICE));
+		assert!(LoansModule::is_liquidatable(DOT, &ALICE));
+		assert!(LoansModule::validate_unsigned(TransactionSource::External, &call).is_ok());
+
+		assert_ok!(LoansModule::liquidate(Origin::none(), DOT, ALICE));
+		let event = Event::loans(crate::Event::Liquidated(ALICE, DOT, 130, 13));
+		assert!(System::events().iter().any(|record| record.event == event));
+		assert_eq!(LoansModule::fixed_term_loans(DOT, &ALICE), None);
+		assert_eq!(LoansModule::total_fixed_term_debits(DOT), 0);
+		assert_eq!(Tokens::free_balance(DOT, &TREASURY), 13);
+		assert_eq!(LoansModule::total_reserves(DOT), 3);
+		// 143 seized from the deposits, 27 of the interest paid to depositors
+		assert_eq!(LoansModule::asset_pool_infos(DOT).total_deposit, 584);
+	});
+}
+
+#[test]
+fn leverage_debit_is_kept_apart_from_loans_work() {
+	ExtBuilder::default().build().execute_with(|| {
+		System::set_block_number(1);
+		assert_ok!(LoansModule::update_deposit(&ALICE, DOT, 500));
+
+		// no deposits, the position collateral held by the dex backs the debit
+		assert_ok!(<LoansModule as AssetPoolManager<AccountId>>::update_debit(&BOB, DOT, 100));
+		let event = Event::loans(crate::Event::LeverageDebitUpdated(BOB, DOT, 100));
+		assert!(System::events().iter().any(|record| record.event == event));
+		assert_eq!(LoansModule::leverage_debits(DOT, &BOB), 100);
+		assert_eq!(LoansModule::loans(DOT, &BOB), LoanInfo { deposit: 0, debit: 0 });
+		assert_eq!(LoansModule::asset_pool_infos(DOT).total_debit, 100);
+		assert_eq!(Tokens::free_balance(DOT, &BOB), 1100);
+		assert!(!LoansModule::is_debit_unsafe(&BOB));
+		assert!(!LoansModule::is_liquidatable(DOT, &BOB));
+
+		// ordinary borrowing of the trader is judged without it
+		assert_ok!(LoansModule::update_deposit(&BOB, BTC, 100));
+		assert_ok!(LoansModule::set_collateral(&BOB, BTC, true));
+		assert_ok!(LoansModule::update_debit(&BOB, DOT, 75));
+		assert_eq!(LoansModule::loans(DOT, &BOB), LoanInfo { deposit: 0, debit: 75 });
+		assert!(!LoansModule::is_debit_unsafe(&BOB));
+
+		assert_ok!(<LoansModule as AssetPoolManager<AccountId>>::update_debit(&BOB, DOT, -40));
+		assert_eq!(LoansModule::leverage_debits(DOT, &BOB), 60);
+		assert_noop!(
+			<LoansModule as AssetPoolManager<AccountId>>::update_debit(&BOB, DOT, -61),
+			Error::<Runtime>::DebitTooLow
+		);
+
+		// no reserves yet, the depositors bear the loss
+		assert_ok!(<LoansModule as AssetPoolManager<AccountId>>::write_off_debit(&BOB, DOT, 60));
+		let event = Event::loans(crate::Event::DebitWrittenOff(DOT, 60));
+		assert!(System::events().iter().any(|record| record.event == event));
+		assert_eq!(LoansModule::leverage_debits(DOT, &BOB), 0);
+		assert_eq!(LoansModule::asset_pool_infos(DOT).total_debit, 75);
+		assert_eq!(LoansModule::asset_pool_infos(DOT).total_deposit, 440);
+		assert_eq!(LoansModule::asset_pool_infos(DOT).deposit_rate, Ratio::saturating_from_rational(88, 100));
+		assert_eq!(LoansModule::loans(DOT, &BOB), LoanInfo { deposit: 0, debit: 75 });
+	});
+}
+
+#[test]
+fn offchain_worker_submits_only_unsafe_loans_work() {
+	let (offchain, _offchain_state) = testing::TestOffchainExt::new();
+	let (pool, pool_state) = testing::TestTransactionPoolExt::new();
+	let mut ext = ExtBuilder::default().build();
+	ext.register_extension(OffchainExt::new(offchain));
+	ext.register_extension(TransactionPoolExt::new(pool));
+
+	ext.execute_with(|| {
+		assert_ok!(LoansModule::update_deposit(&ALICE, BTC, 100));
+		assert_ok!(LoansModule::set_collateral(&ALICE, BTC, true));
+		assert_ok!(LoansModule::update_deposit(&BOB, DOT, 500));
+		assert_ok!(LoansModule::update_debit(&ALICE, DOT, 70));
+		assert_ok!(LoansModule::update_deposit(&CHARLIE, DOT, 100));
+		assert_ok!(LoansModule::set_collateral(&CHARLIE, DOT, true));
+		assert_ok!(LoansModule::update_debit(&CHARLIE, DOT, 50));
+		MockPriceSource::set_price(BTC, Some(Price::saturating_from_rational(80, 100)));
+		assert!(LoansModule::is_debit_unsafe(&ALICE));
+		assert!(!LoansModule::is_debit_unsafe(&CHARLIE));
+
+		// start from the DOT pool
+		StorageValueRef::persistent(&OFFCHAIN_WORKER_DATA).set(&(0u32, Option::<Vec<u8>>::None));
+		assert!(LoansModule::_offchain_worker().is_ok());
+
+		assert_eq!(pool_state.read().transactions.len(), 1);
+		let tx = pool_state.write().transactions.pop().unwrap();
+		let tx = Extrinsic::decode(&mut &*tx).unwrap();
+		assert_eq!(tx.signature, None);
+		assert_eq!(tx.call, Call::LoansModule(crate::Call::liquidate(DOT, ALICE)));
+	});
+}
+
+#[test]
+fn migrate_to_v2_work() {
+	ExtBuilder::default().build().execute_with(|| {
+		AssetPoolInfos::<Runtime>::remove_all();
+		AssetPoolIds::<Runtime>::kill();
+		AssetPoolStatuses::<Runtime>::remove_all();
+		StorageVersion::<Runtime>::kill();
+		let interest_info = InterestInfo {
+			critical_point: Ratio::saturating_from_rational(90, 100),
+			base: Ratio::zero(),
+			slope_1: Ratio::saturating_from_rational(4, 100),
+			slope_2: Ratio::one(),
+		};
+		let old = migrations::AssetPoolInfoV1 {
+			maximum_total_debit_ratio: Ratio::saturating_from_rational(90, 100),
+			minimum_deposit: 10,
+			minimum_debit: 10,
+			health_ratio: Ratio::saturating_from_rational(75, 100),
+			total_deposit: 500,
+			total_debit: 100,
+			deposit_rate: Ratio::one(),
+			debit_rate: Ratio::one(),
+			deposit_apy: Ratio::zero(),
+			debit_apy: Ratio::zero(),
+			reserve_factor: Ratio::saturating_from_rational(10, 100),
+			interest_info,
+		};
+		frame_support::storage::unhashed::put(&AssetPoolInfos::<Runtime>::hashed_key_for(DOT), &old);
+		// a borrower from before deposits had to be enabled as collateral
+		LoanInfos::<Runtime>::insert(DOT, ALICE, LoanInfo { deposit: 200, debit: 100 });
+		LoanInfos::<Runtime>::insert(DOT, BOB, LoanInfo { deposit: 300, debit: 0 });
+		assert!(!LoansModule::collaterals(ALICE, DOT));
+
+		assert!(migrations::migrate_to_v2::<Runtime>() > 0);
+		assert_eq!(LoansModule::storage_version(), Releases::V2_0_0);
+		assert_eq!(LoansModule::asset_pool_ids(), vec![DOT]);
+		assert_eq!(LoansModule::asset_pool_statuses(DOT), Some(AssetPoolStatus::Listed));
+		let asset_pool_info = LoansModule::asset_pool_infos(DOT);
+		assert_eq!(asset_pool_info.collateral_factor, Ratio::saturating_from_rational(75, 100));
+		assert_eq!(asset_pool_info.liquidation_threshold, Ratio::saturating_from_rational(875, 1000));
+		assert_eq!(asset_pool_info.liquidation_bonus, Ratio::saturating_from_rational(5, 100));
+		assert_eq!((asset_pool_info.supply_cap, asset_pool_info.borrow_cap), (0, 0));
+		assert_eq!((asset_pool_info.total_deposit, asset_pool_info.total_debit), (500, 100));
+		assert_eq!(asset_pool_info.interest_model, InterestModel::JumpRate(interest_info));
+
+		// the borrower keeps backing its debit, with room before liquidation
+		assert!(LoansModule::collaterals(ALICE, DOT));
+		assert!(LoansModule::collaterals(BOB, DOT));
+		assert_eq!(LoansModule::get_account_liquidity(&ALICE).ok(), Some((150, 100)));
+		assert!(!LoansModule::is_debit_unsafe(&ALICE));
+		assert_eq!(
+			migrations::collateral_params_from_health_ratio(Ratio::one()),
+			(Ratio::saturating_from_rational(95, 100), Ratio::saturating_from_rational(975, 1000))
+		);
+
+		// runs once
+		assert_eq!(migrations::migrate_to_v2::<Runtime>(), 0);
+	});
+}
+
+#[test]
+fn offchain_worker_resumes_fixed_term_loans_work() {
+	let (offchain, _offchain_state) = testing::TestOffchainExt::new();
+	let (pool, pool_state) = testing::TestTransactionPoolExt::new();
+	let mut ext = ExtBuilder::default().build();
+	ext.register_extension(OffchainExt::new(offchain));
+	ext.register_extension(TransactionPoolExt::new(pool));
+
+	ext.execute_with(|| {
+		assert_ok!(LoansModule::update_deposit(&BOB, DOT, 500));
+		for who in [ALICE, CHARLIE].iter() {
+			assert_ok!(LoansModule::update_deposit(who, DOT, 200));
+			assert_ok!(LoansModule::set_collateral(who, DOT, true));
+			assert_ok!(LoansModule::borrow_fixed_term(who, DOT, 100, SECONDS_PER_YEAR));
+		}
+		Timestamp::set_timestamp(SECONDS_PER_YEAR * 2 * 1000);
+		assert!(LoansModule::is_liquidatable(DOT, &ALICE));
+		assert!(LoansModule::is_liquidatable(DOT, &CHARLIE));
+
+		// one loan of each map per run
+		StorageValueRef::persistent(&OFFCHAIN_WORKER_MAX_ITERATIONS).set(&1u32);
+		StorageValueRef::persistent(&OFFCHAIN_WORKER_DATA).set(&(0u32, Option::<Vec<u8>>::None));
+		let mut liquidated = vec![];
+		for _ in 0..2 {
+			assert!(LoansModule::_offchain_worker().is_ok());
+			StorageValueRef::persistent(&OFFCHAIN_WORKER_LOCK).clear();
+
+			assert_eq!(pool_state.read().transactions.len(), 1);
+			let tx = pool_state.write().transactions.pop().unwrap();
+			match Extrinsic::decode(&mut &*tx).unwrap().call {
+				Call::LoansModule(crate::Call::liquidate(currency_id, who)) => {
+					assert_eq!(currency_id, DOT);
+					liquidated.push(who);
+				}
+				_ => panic!("unexpected call"),
+			}
+		}
+		liquidated.sort();
+		assert_eq!(liquidated, vec![ALICE, CHARLIE]);
 	});
 }
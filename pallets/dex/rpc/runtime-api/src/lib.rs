@@ -0,0 +1,52 @@
+//! Runtime API definition for the dex pallet.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+// The `too_many_arguments` warning originates from `decl_runtime_apis` macro.
+#![allow(clippy::too_many_arguments)]
+// The `unnecessary_mut_passed` warning originates from `decl_runtime_apis` macro.
+#![allow(clippy::unnecessary_mut_passed)]
+
+use codec::Codec;
+use sp_std::prelude::Vec;
+
+sp_api::decl_runtime_apis! {
+	pub trait DexApi<AccountId, CurrencyId, TradingPair, Balance, Amount, Price> where
+		AccountId: Codec,
+		CurrencyId: Codec,
+		TradingPair: Codec,
+		Balance: Codec,
+		Amount: Codec,
+		Price: Codec,
+	{
+		/// Target amount received by swapping `supply_amount` along `path`, fees included.
+		fn get_swap_target_amount(path: Vec<CurrencyId>, supply_amount: Balance) -> Option<Balance>;
+
+		/// Supply amount needed to receive `target_amount` along `path`, fees included.
+		fn get_swap_supply_amount(path: Vec<CurrencyId>, target_amount: Balance) -> Option<Balance>;
+
+		/// Path with the largest target amount for `supply_amount`, and that amount.
+		fn get_best_target_route(
+			supply_currency_id: CurrencyId,
+			target_currency_id: CurrencyId,
+			supply_amount: Balance,
+		) -> Option<(Vec<CurrencyId>, Balance)>;
+
+		/// Path needing the smallest supply amount for `target_amount`, and that amount.
+		fn get_best_supply_route(
+			supply_currency_id: CurrencyId,
+			target_currency_id: CurrencyId,
+			target_amount: Balance,
+		) -> Option<(Vec<CurrencyId>, Balance)>;
+
+		/// Reserves of the trading pair, in the order of the pair.
+		fn get_liquidity_pool(trading_pair: TradingPair) -> (Balance, Balance);
+
+		/// Time-weighted average prices of the pair over at least the last `window` seconds,
+		/// (price of the first currency in the second, price of the second in the first).
+		fn get_twap(trading_pair: TradingPair, window: u64) -> Option<(Price, Price)>;
+
+		/// Profit (positive) or loss (negative) of the leveraged position of `who` in the debit
+		/// currency, valued by selling the collateral into the trading pair.
+		fn get_position_pnl(who: AccountId, trading_pair: TradingPair) -> Option<Amount>;
+	}
+}
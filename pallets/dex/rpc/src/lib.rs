@@ -0,0 +1,231 @@
+//! RPC interface for the dex pallet, quotes swaps with the exact math of the runtime.
+
+use std::{convert::TryFrom, sync::Arc};
+
+use codec::Codec;
+use jsonrpc_core::{Error as RpcError, ErrorCode, Result};
+use jsonrpc_derive::rpc;
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_rpc::number::NumberOrHex;
+use sp_runtime::{generic::BlockId, traits::Block as BlockT};
+
+pub use pallet_dex_rpc_runtime_api::DexApi as DexRuntimeApi;
+
+/// Amounts of the runtime `Balance` type go over the wire as `NumberOrHex`.
+#[rpc]
+pub trait DexApi<BlockHash, AccountId, CurrencyId, TradingPair, Balance, Amount, Price> {
+	#[rpc(name = "dex_getSwapTargetAmount")]
+	fn get_swap_target_amount(
+		&self,
+		path: Vec<CurrencyId>,
+		supply_amount: NumberOrHex,
+		at: Option<BlockHash>,
+	) -> Result<Option<NumberOrHex>>;
+
+	#[rpc(name = "dex_getSwapSupplyAmount")]
+	fn get_swap_supply_amount(
+		&self,
+		path: Vec<CurrencyId>,
+		target_amount: NumberOrHex,
+		at: Option<BlockHash>,
+	) -> Result<Option<NumberOrHex>>;
+
+	#[rpc(name = "dex_getBestTargetRoute")]
+	fn get_best_target_route(
+		&self,
+		supply_currency_id: CurrencyId,
+		target_currency_id: CurrencyId,
+		supply_amount: NumberOrHex,
+		at: Option<BlockHash>,
+	) -> Result<Option<(Vec<CurrencyId>, NumberOrHex)>>;
+
+	#[rpc(name = "dex_getBestSupplyRoute")]
+	fn get_best_supply_route(
+		&self,
+		supply_currency_id: CurrencyId,
+		target_currency_id: CurrencyId,
+		target_amount: NumberOrHex,
+		at: Option<BlockHash>,
+	) -> Result<Option<(Vec<CurrencyId>, NumberOrHex)>>;
+
+	#[rpc(name = "dex_getLiquidityPool")]
+	fn get_liquidity_pool(&self, trading_pair: TradingPair, at: Option<BlockHash>) -> Result<(NumberOrHex, NumberOrHex)>;
+
+	#[rpc(name = "dex_getTwap")]
+	fn get_twap(&self, trading_pair: TradingPair, window: u64, at: Option<BlockHash>) -> Result<Option<(Price, Price)>>;
+
+	/// (profit, loss) of a leveraged position in its debit currency, at most one of them is not zero.
+	#[rpc(name = "dex_getPositionPnl")]
+	fn get_position_pnl(
+		&self,
+		who: AccountId,
+		trading_pair: TradingPair,
+		at: Option<BlockHash>,
+	) -> Result<Option<(NumberOrHex, NumberOrHex)>>;
+}
+
+/// A struct that implements the [`DexApi`].
+pub struct Dex<C, B> {
+	client: Arc<C>,
+	_marker: std::marker::PhantomData<B>,
+}
+
+impl<C, B> Dex<C, B> {
+	/// Create new `Dex` with the given reference to the client.
+	pub fn new(client: Arc<C>) -> Self {
+		Dex {
+			client,
+			_marker: Default::default(),
+		}
+	}
+}
+
+pub enum Error {
+	RuntimeError,
+}
+
+impl From<Error> for i64 {
+	fn from(e: Error) -> i64 {
+		match e {
+			Error::RuntimeError => 1,
+		}
+	}
+}
+
+fn invalid_amount() -> RpcError {
+	RpcError {
+		code: ErrorCode::InvalidParams,
+		message: "Amount doesn't fit in a balance.".into(),
+		data: None,
+	}
+}
+
+fn try_into_balance<Balance: TryFrom<NumberOrHex>>(amount: NumberOrHex) -> Result<Balance> {
+	Balance::try_from(amount).map_err(|_| invalid_amount())
+}
+
+fn runtime_error(e: impl std::fmt::Debug) -> RpcError {
+	RpcError {
+		code: ErrorCode::ServerError(Error::RuntimeError.into()),
+		message: "Unable to query the dex.".into(),
+		data: Some(format!("{:?}", e).into()),
+	}
+}
+
+impl<C, Block, AccountId, CurrencyId, TradingPair, Balance, Amount, Price>
+	DexApi<<Block as BlockT>::Hash, AccountId, CurrencyId, TradingPair, Balance, Amount, Price> for Dex<C, Block>
+where
+	Block: BlockT,
+	C: Send + Sync + 'static + ProvideRuntimeApi<Block> + HeaderBackend<Block>,
+	C::Api: DexRuntimeApi<Block, AccountId, CurrencyId, TradingPair, Balance, Amount, Price>,
+	AccountId: Codec,
+	CurrencyId: Codec,
+	TradingPair: Codec,
+	Balance: Codec + TryFrom<NumberOrHex> + Into<NumberOrHex>,
+	Amount: Codec + Into<i128>,
+	Price: Codec,
+{
+	fn get_swap_target_amount(
+		&self,
+		path: Vec<CurrencyId>,
+		supply_amount: NumberOrHex,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> Result<Option<NumberOrHex>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+		let supply_amount = try_into_balance::<Balance>(supply_amount)?;
+		api.get_swap_target_amount(&at, path, supply_amount)
+			.map(|amount| amount.map(Into::into))
+			.map_err(runtime_error)
+	}
+
+	fn get_swap_supply_amount(
+		&self,
+		path: Vec<CurrencyId>,
+		target_amount: NumberOrHex,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> Result<Option<NumberOrHex>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+		let target_amount = try_into_balance::<Balance>(target_amount)?;
+		api.get_swap_supply_amount(&at, path, target_amount)
+			.map(|amount| amount.map(Into::into))
+			.map_err(runtime_error)
+	}
+
+	fn get_best_target_route(
+		&self,
+		supply_currency_id: CurrencyId,
+		target_currency_id: CurrencyId,
+		supply_amount: NumberOrHex,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> Result<Option<(Vec<CurrencyId>, NumberOrHex)>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+		let supply_amount = try_into_balance::<Balance>(supply_amount)?;
+		api.get_best_target_route(&at, supply_currency_id, target_currency_id, supply_amount)
+			.map(|route| route.map(|(path, amount)| (path, amount.into())))
+			.map_err(runtime_error)
+	}
+
+	fn get_best_supply_route(
+		&self,
+		supply_currency_id: CurrencyId,
+		target_currency_id: CurrencyId,
+		target_amount: NumberOrHex,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> Result<Option<(Vec<CurrencyId>, NumberOrHex)>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+		let target_amount = try_into_balance::<Balance>(target_amount)?;
+		api.get_best_supply_route(&at, supply_currency_id, target_currency_id, target_amount)
+			.map(|route| route.map(|(path, amount)| (path, amount.into())))
+			.map_err(runtime_error)
+	}
+
+	fn get_liquidity_pool(
+		&self,
+		trading_pair: TradingPair,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> Result<(NumberOrHex, NumberOrHex)> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+		api.get_liquidity_pool(&at, trading_pair)
+			.map(|(pool_0, pool_1)| (pool_0.into(), pool_1.into()))
+			.map_err(runtime_error)
+	}
+
+	fn get_twap(
+		&self,
+		trading_pair: TradingPair,
+		window: u64,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> Result<Option<(Price, Price)>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+		api.get_twap(&at, trading_pair, window).map_err(runtime_error)
+	}
+
+	fn get_position_pnl(
+		&self,
+		who: AccountId,
+		trading_pair: TradingPair,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> Result<Option<(NumberOrHex, NumberOrHex)>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+		api.get_position_pnl(&at, who, trading_pair)
+			.map(|pnl| pnl.map(|pnl| {
+				let pnl: i128 = pnl.into();
+				// the magnitude of a negative pnl, i128::MIN included
+				let magnitude = if pnl.is_negative() { 0u128.wrapping_sub(pnl as u128) } else { pnl as u128 };
+				if pnl.is_negative() {
+					(NumberOrHex::from(0u128), NumberOrHex::from(magnitude))
+				} else {
+					(NumberOrHex::from(magnitude), NumberOrHex::from(0u128))
+				}
+			}))
+			.map_err(runtime_error)
+	}
+}
@@ -1,22 +1,25 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
-use frame_support::{pallet_prelude::*, transactional};
+use frame_support::{pallet_prelude::*, traits::UnixTime, transactional};
 use frame_system::pallet_prelude::*;
 use orml_traits::{MultiCurrency, MultiCurrencyExtended};
 use sp_core::U256;
 use sp_runtime::{
 	DispatchError,
 	DispatchResult, FixedPointNumber, ModuleId, RuntimeDebug, SaturatedConversion,
-	traits::{AccountIdConversion, UniqueSaturatedInto, Zero},
+	traits::{AccountIdConversion, One, StaticLookup, UniqueSaturatedInto, Zero},
 };
 use sp_std::{convert::TryInto, prelude::*, vec};
 
-use model::{Balance, CurrencyId, Price, Ratio, TradingPair};
+use model::{Amount, AssetPoolManager, Balance, CurrencyId, DEXManager, Price, PriceProvider, Ratio, TradingPair, TwapProvider};
 pub use module::*;
 
-# mod mock;
-# mod test;
+pub mod migrations;
+mod mock;
+mod test;
+mod twap;
 pub mod weights;
+pub use twap::CumulativePrice;
 pub use weights::WeightInfo;
 
 pub type CurrencyIds = Vec<CurrencyId>;
@@ -47,6 +50,48 @@ impl<Balance, BlockNumber> Default for TradingPairStatus<Balance, BlockNumber> {
 	}
 }
 
+#[derive(Encode, Decode, Clone, Copy, RuntimeDebug, PartialEq, Eq)]
+pub struct LeveragePosition {
+	// currency borrowed from asset pool and sold into the trading pair
+	pub debit_currency_id: CurrencyId,
+
+	// trader's own supply, in debit currency
+	pub margin: Balance,
+
+	// the other currency of the trading pair, held by dex for the trader
+	pub collateral: Balance,
+
+	// real debit: debit * debit_rate
+	pub debit: Balance,
+}
+
+/// Storage layout versions of the pallet, used by the runtime upgrade migrations.
+#[derive(Encode, Decode, Clone, Copy, RuntimeDebug, PartialEq, Eq)]
+pub enum Releases {
+	// leverage positions as a (Balance, Balance) pair
+	V1_0_0,
+	// leverage positions as `LeveragePosition`
+	V2_0_0,
+	// enabled trading pairs indexed in `EnabledTradingPairs`
+	V3_0_0,
+}
+
+impl Default for Releases {
+	fn default() -> Self {
+		Releases::V1_0_0
+	}
+}
+
+/// Operations of a trading pair that can be paused independently.
+#[derive(Encode, Decode, Clone, Copy, RuntimeDebug, PartialEq, Eq)]
+pub enum DexOperation {
+	Swap,
+
+	AddLiquidity,
+
+	RemoveLiquidity,
+}
+
 #[frame_support::pallet]
 pub mod module {
 	use super::*;
@@ -63,12 +108,53 @@ pub mod module {
 		#[pallet::constant]
 		type TradingPathLimit: Get<u32>;
 
+		/// The maximum number of routes a split swap can be divided across.
+		#[pallet::constant]
+		type SplitRouteLimit: Get<u32>;
+
+		/// The maximum number of trading pairs the best route search reads, which is also the
+		/// maximum number of candidate paths it prices.
+		#[pallet::constant]
+		type RouteSearchLimit: Get<u32>;
+
+		/// The maximum number of enabled trading pairs, which bounds `EnabledTradingPairs`.
+		#[pallet::constant]
+		type MaxEnabledTradingPairs: Get<u32>;
+
 		#[pallet::constant]
 		type ModuleId: Get<ModuleId>;
 
 		type WhiteListOrigin: EnsureOrigin<Self::Origin>;
 
+		type AssetPool: AssetPoolManager<Self::AccountId>;
+
+		#[pallet::constant]
+		type MaxLeverage: Get<Ratio>;
+
+		type PriceSource: PriceProvider;
+
+		/// A leveraged position can be liquidated once its collateral value is below its debit value
+		/// times this ratio.
+		#[pallet::constant]
+		type LiquidationRatio: Get<Ratio>;
+
+		/// Share of the repaid debit paid to the liquidator of a position, out of what is left of the
+		/// collateral sale. The sale may also fall this much below the price source.
+		#[pallet::constant]
+		type LiquidationPenalty: Get<Ratio>;
+
 		type WeightInfo: WeightInfo;
+
+		type UnixTime: UnixTime;
+
+		/// Minimum seconds between two observations of the price accumulators of a trading pair.
+		#[pallet::constant]
+		type ObservationPeriod: Get<u64>;
+
+		/// The number of observations kept per trading pair, the longest TWAP window is about
+		/// `ObservationPeriod * ObservationLimit` seconds.
+		#[pallet::constant]
+		type ObservationLimit: Get<u32>;
 	}
 
 	#[pallet::error]
@@ -81,6 +167,8 @@ pub mod module {
 
 		MustBeDisabled,
 
+		TooManyEnabledTradingPairs,
+
 		NotAllowedNew,
 
 		InvalidContributionIncrement,
@@ -102,6 +190,28 @@ pub mod module {
 		ZeroSupplyAmount,
 
 		ZeroTargetAmount,
+
+		InvalidLeverage,
+
+		LeverageDirectionMismatch,
+
+		AmountConvertFailed,
+
+		PositionNotFound,
+
+		InvalidCollateralAmount,
+
+		ExcessiveMargin,
+
+		OperationPaused,
+
+		NoTradingRoute,
+
+		InvalidSplitRoutes,
+
+		MustBeUnsafe,
+
+		UnsafePosition,
 	}
 
 	#[pallet::event]
@@ -115,6 +225,9 @@ pub mod module {
 
 		Swap(T::AccountId, CurrencyIds, Balance, Balance),
 
+		/// trader, (route, supply amount, target amount) of every route, total supply, total target
+		SplitSwap(T::AccountId, Vec<(CurrencyIds, Balance, Balance)>, Balance, Balance),
+
 		EnableTradingPair(TradingPair),
 
 		NewTradingPair(TradingPair),
@@ -122,6 +235,21 @@ pub mod module {
 		DisableTradingPair(TradingPair),
 
 		PreparingToEnabled(TradingPair, Balance, Balance, Balance),
+
+		/// trader, trading pair, debit currency, margin, borrowed amount, collateral
+		PositionOpened(T::AccountId, TradingPair, CurrencyId, Balance, Balance, Balance),
+
+		/// trader, trading pair, margin, collateral, debit after the adjustment
+		PositionAdjusted(T::AccountId, TradingPair, Balance, Balance, Balance),
+
+		/// trader, trading pair, collateral sold, debit repaid
+		PositionClosed(T::AccountId, TradingPair, Balance, Balance),
+
+		/// liquidator, trader, trading pair, collateral sold, debit repaid, debit written off, penalty
+		PositionLiquidated(T::AccountId, T::AccountId, TradingPair, Balance, Balance, Balance, Balance),
+
+		/// trading pair, operation, paused
+		OperationPausedUpdated(TradingPair, DexOperation, bool),
 	}
 
 	#[pallet::storage]
@@ -133,6 +261,11 @@ pub mod module {
 	pub type TradingPairStatuses<T: Config> =
 	StorageMap<_, Twox64Concat, TradingPair, TradingPairStatus<Balance, T::BlockNumber>, ValueQuery>;
 
+	/// The trading pairs with `Enabled` status, at most `MaxEnabledTradingPairs`.
+	#[pallet::storage]
+	#[pallet::getter(fn enabled_trading_pairs)]
+	pub type EnabledTradingPairs<T: Config> = StorageValue<_, Vec<TradingPair>, ValueQuery>;
+
 	#[pallet::storage]
 	#[pallet::getter(fn preparing_pool)]
 	pub type PreparingPool<T: Config> =
@@ -141,7 +274,32 @@ pub mod module {
 	#[pallet::storage]
 	#[pallet::getter(fn leverage_pool)]
 	pub type LeveragePool<T: Config> =
-	StorageDoubleMap<_, Twox64Concat, TradingPair, Twox64Concat, T::AccountId, (Balance, Balance), ValueQuery>;
+	StorageDoubleMap<_, Twox64Concat, TradingPair, Twox64Concat, T::AccountId, LeveragePosition, OptionQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn paused_operations)]
+	pub type PausedOperations<T: Config> =
+	StorageDoubleMap<_, Twox64Concat, TradingPair, Twox64Concat, DexOperation, bool, ValueQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn price_cumulatives)]
+	pub type PriceCumulatives<T: Config> = StorageMap<_, Twox64Concat, TradingPair, CumulativePrice, OptionQuery>;
+
+	/// Snapshots of the price accumulators, a ring buffer of `ObservationLimit` slots per trading pair.
+	#[pallet::storage]
+	#[pallet::getter(fn price_observations)]
+	pub type PriceObservations<T: Config> =
+	StorageDoubleMap<_, Twox64Concat, TradingPair, Twox64Concat, u32, CumulativePrice, OptionQuery>;
+
+	/// Slot of the latest observation of a trading pair in `PriceObservations`.
+	#[pallet::storage]
+	#[pallet::getter(fn latest_observation_index)]
+	pub type LatestObservationIndex<T: Config> = StorageMap<_, Twox64Concat, TradingPair, u32, ValueQuery>;
+
+	/// Storage layout version, new chains start at the latest.
+	#[pallet::storage]
+	#[pallet::getter(fn storage_version)]
+	pub type StorageVersion<T: Config> = StorageValue<_, Releases, ValueQuery>;
 
 	#[pallet::genesis_config]
 	pub struct GenesisConfig<T: Config> {
@@ -188,6 +346,10 @@ pub mod module {
 					"the trading pair is invalid!",
 				);
 				TradingPairStatuses::<T>::insert(trading_pair, TradingPairStatus::<_, _>::Enabled);
+				assert!(
+					<Module<T>>::insert_enabled_trading_pair(*trading_pair).is_ok(),
+					"too many enabled trading pairs!",
+				);
 			});
 
 			self.initial_liquidity_pools
@@ -216,6 +378,8 @@ pub mod module {
 							assert!(result.is_ok(), "genesis add liquidity pool failed.");
 						});
 				});
+
+			StorageVersion::<T>::put(Releases::V3_0_0);
 		}
 	}
 
@@ -223,17 +387,113 @@ pub mod module {
 	pub struct Pallet<T>(PhantomData<T>);
 
 	#[pallet::hooks]
-	impl<T: Config> Hooks<T::BlockNumber> for Pallet<T> {}
+	impl<T: Config> Hooks<T::BlockNumber> for Pallet<T> {
+		fn on_runtime_upgrade() -> Weight {
+			migrations::migrate_to_v2::<T>().saturating_add(migrations::migrate_to_v3::<T>())
+		}
+	}
 
 	#[pallet::call]
 	impl<T: Config> Pallet<T> {
-		// TODO
-		pub fn swap_with_exact_supply_by_leverage() -> DispatchResultWithPostInfo {
+		#[pallet::weight((<T as Config>::WeightInfo::swap_with_exact_supply_by_leverage(), DispatchClass::Operational))]
+		#[transactional]
+		pub fn swap_with_exact_supply_by_leverage(
+			origin: OriginFor<T>,
+			supply_currency_id: CurrencyId,
+			target_currency_id: CurrencyId,
+			supply_amount: Balance,
+			leverage: Ratio,
+			min_target_amount: Balance,
+			price_impact_limit: Ratio,
+		) -> DispatchResultWithPostInfo {
+			let who = ensure_signed(origin)?;
+			let _ = Self::do_swap_with_exact_supply_by_leverage(&who, supply_currency_id, target_currency_id,
+																supply_amount, leverage, min_target_amount,
+																Some(price_impact_limit))?;
 			Ok(().into())
 		}
 
-		// TODO
-		pub fn swap_with_exact_target_by_leverage() -> DispatchResultWithPostInfo {
+		#[pallet::weight((<T as Config>::WeightInfo::swap_with_exact_target_by_leverage(), DispatchClass::Operational))]
+		#[transactional]
+		pub fn swap_with_exact_target_by_leverage(
+			origin: OriginFor<T>,
+			supply_currency_id: CurrencyId,
+			target_currency_id: CurrencyId,
+			target_amount: Balance,
+			leverage: Ratio,
+			max_supply_amount: Balance,
+			price_impact_limit: Ratio,
+		) -> DispatchResultWithPostInfo {
+			let who = ensure_signed(origin)?;
+			let _ = Self::do_swap_with_exact_target_by_leverage(&who, supply_currency_id, target_currency_id,
+																target_amount, leverage, max_supply_amount,
+																Some(price_impact_limit))?;
+			Ok(().into())
+		}
+
+		#[pallet::weight((<T as Config>::WeightInfo::reduce_position(), DispatchClass::Operational))]
+		#[transactional]
+		pub fn reduce_position(
+			origin: OriginFor<T>,
+			currency_id_a: CurrencyId,
+			currency_id_b: CurrencyId,
+			collateral_amount: Balance,
+			min_target_amount: Balance,
+			price_impact_limit: Ratio,
+		) -> DispatchResultWithPostInfo {
+			let who = ensure_signed(origin)?;
+			let trading_pair = TradingPair::from_token_currency_ids(currency_id_a, currency_id_b)
+				.ok_or(Error::<T>::InvalidCurrencyId)?;
+			Self::do_reduce_position(&who, trading_pair, collateral_amount, min_target_amount, Some(price_impact_limit))?;
+			Ok(().into())
+		}
+
+		#[pallet::weight((<T as Config>::WeightInfo::reduce_position(), DispatchClass::Operational))]
+		#[transactional]
+		pub fn close_position(
+			origin: OriginFor<T>,
+			currency_id_a: CurrencyId,
+			currency_id_b: CurrencyId,
+			min_target_amount: Balance,
+			price_impact_limit: Ratio,
+		) -> DispatchResultWithPostInfo {
+			let who = ensure_signed(origin)?;
+			let trading_pair = TradingPair::from_token_currency_ids(currency_id_a, currency_id_b)
+				.ok_or(Error::<T>::InvalidCurrencyId)?;
+			let position = Self::leverage_pool(trading_pair, &who).ok_or(Error::<T>::PositionNotFound)?;
+			Self::do_reduce_position(&who, trading_pair, position.collateral, min_target_amount, Some(price_impact_limit))?;
+			Ok(().into())
+		}
+
+		#[pallet::weight((<T as Config>::WeightInfo::add_margin(), DispatchClass::Operational))]
+		#[transactional]
+		pub fn add_margin(
+			origin: OriginFor<T>,
+			currency_id_a: CurrencyId,
+			currency_id_b: CurrencyId,
+			margin_amount: Balance,
+		) -> DispatchResultWithPostInfo {
+			let who = ensure_signed(origin)?;
+			let trading_pair = TradingPair::from_token_currency_ids(currency_id_a, currency_id_b)
+				.ok_or(Error::<T>::InvalidCurrencyId)?;
+			Self::do_add_margin(&who, trading_pair, margin_amount)?;
+			Ok(().into())
+		}
+
+		/// Sell the whole collateral of an unsafe leveraged position of `who` to repay its debit.
+		#[pallet::weight((<T as Config>::WeightInfo::liquidate_position(), DispatchClass::Operational))]
+		#[transactional]
+		pub fn liquidate_position(
+			origin: OriginFor<T>,
+			who: <T::Lookup as StaticLookup>::Source,
+			currency_id_a: CurrencyId,
+			currency_id_b: CurrencyId,
+		) -> DispatchResultWithPostInfo {
+			let liquidator = ensure_signed(origin)?;
+			let who = T::Lookup::lookup(who)?;
+			let trading_pair = TradingPair::from_token_currency_ids(currency_id_a, currency_id_b)
+				.ok_or(Error::<T>::InvalidCurrencyId)?;
+			Self::do_liquidate_position(&liquidator, &who, trading_pair)?;
 			Ok(().into())
 		}
 
@@ -269,6 +529,38 @@ pub mod module {
 			Ok(().into())
 		}
 
+		#[pallet::weight((<T as Config>::WeightInfo::swap_with_best_route(T::RouteSearchLimit::get()), DispatchClass::Operational))]
+		#[transactional]
+		pub fn swap_with_best_route(
+			origin: OriginFor<T>,
+			supply_currency_id: CurrencyId,
+			target_currency_id: CurrencyId,
+			supply_amount: Balance,
+			min_target_amount: Balance,
+			price_impact_limit: Ratio,
+		) -> DispatchResultWithPostInfo {
+			let who = ensure_signed(origin)?;
+			let (route, _) = Self::get_best_target_route(supply_currency_id, target_currency_id, supply_amount)
+				.ok_or(Error::<T>::NoTradingRoute)?;
+			let _ = Self::do_swap_with_exact_supply(&who, &route,
+													supply_amount, min_target_amount,
+													Some(price_impact_limit))?;
+			Ok(().into())
+		}
+
+		#[pallet::weight((<T as Config>::WeightInfo::swap_with_split_routes(routes.len() as u32), DispatchClass::Operational))]
+		#[transactional]
+		pub fn swap_with_split_routes(
+			origin: OriginFor<T>,
+			routes: Vec<(CurrencyIds, Balance)>,
+			min_target_amount: Balance,
+			price_impact_limit: Ratio,
+		) -> DispatchResultWithPostInfo {
+			let who = ensure_signed(origin)?;
+			let _ = Self::do_swap_with_split_routes(&who, &routes, min_target_amount, Some(price_impact_limit))?;
+			Ok(().into())
+		}
+
 		#[pallet::weight((<T as Config>::WeightInfo::add_liquidity(), DispatchClass::Operational))]
 		#[transactional]
 		pub fn add_liquidity(
@@ -311,10 +603,34 @@ pub mod module {
 			by_withdraw: bool,
 		) -> DispatchResultWithPostInfo {
 			let who = ensure_signed(origin)?;
+			let trading_pair = TradingPair::from_token_currency_ids(currency_id_a, currency_id_b)
+				.ok_or(Error::<T>::InvalidCurrencyId)?;
 			Self::do_remove_liquidity(&who, currency_id_a, currency_id_b, remove_share, by_withdraw)?;
 			Ok(().into())
 		}
 
+		#[pallet::weight((<T as Config>::WeightInfo::set_operation_paused(), DispatchClass::Operational))]
+		#[transactional]
+		pub fn set_operation_paused(
+			origin: OriginFor<T>,
+			currency_id_a: CurrencyId,
+			currency_id_b: CurrencyId,
+			operation: DexOperation,
+			paused: bool,
+		) -> DispatchResultWithPostInfo {
+			T::WhiteListOrigin::ensure_origin(origin)?;
+			let trading_pair = TradingPair::from_token_currency_ids(currency_id_a, currency_id_b)
+				.ok_or(Error::<T>::InvalidCurrencyId)?;
+
+			if paused {
+				PausedOperations::<T>::insert(trading_pair, operation, true);
+			} else {
+				PausedOperations::<T>::remove(trading_pair, operation);
+			}
+			Self::deposit_event(Event::OperationPausedUpdated(trading_pair, operation, paused));
+			Ok(().into())
+		}
+
 		#[pallet::weight((<T as Config>::WeightInfo::new_trading_pair(), DispatchClass::Operational))]
 		#[transactional]
 		pub fn new_trading_pair(
@@ -387,6 +703,7 @@ pub mod module {
 				Error::<T>::MustBeDisabled
 			);
 
+			Self::insert_enabled_trading_pair(trading_pair)?;
 			TradingPairStatuses::<T>::insert(trading_pair, TradingPairStatus::Enabled);
 			Self::deposit_event(Event::EnableTradingPair(trading_pair));
 			Ok(().into())
@@ -406,6 +723,7 @@ pub mod module {
 			match Self::trading_pair_statuses(trading_pair) {
 				TradingPairStatus::<_, _>::Enabled => {
 					TradingPairStatuses::<T>::insert(trading_pair, TradingPairStatus::Disabled);
+					EnabledTradingPairs::<T>::mutate(|trading_pairs| trading_pairs.retain(|pair| *pair != trading_pair));
 					Self::deposit_event(Event::DisableTradingPair(trading_pair));
 				}
 				TradingPairStatus::<_, _>::Preparing(_) => {
@@ -435,6 +753,21 @@ impl<T: Config> Pallet<T> {
 		T::ModuleId::get().into_account()
 	}
 
+	/// Add `trading_pair` to `EnabledTradingPairs`, unless it is full.
+	fn insert_enabled_trading_pair(trading_pair: TradingPair) -> DispatchResult {
+		EnabledTradingPairs::<T>::try_mutate(|trading_pairs| -> DispatchResult {
+			if !trading_pairs.contains(&trading_pair) {
+				ensure!(
+					trading_pairs.len() < T::MaxEnabledTradingPairs::get().saturated_into::<usize>(),
+					Error::<T>::TooManyEnabledTradingPairs
+				);
+				trading_pairs.push(trading_pair);
+			}
+			Ok(())
+		})
+	}
+
+	// a preparing trading pair waits for room in `EnabledTradingPairs`
 	fn convert_to_enabled_if_possible(trading_pair: TradingPair) {
 		if let TradingPairStatus::<_, _>::Preparing(preparing_parameters) = Self::trading_pair_statuses(trading_pair)
 		{
@@ -443,6 +776,7 @@ impl<T: Config> Pallet<T> {
 				&& !preparing_parameters.accumulated_amount.1.is_zero()
 				&& (preparing_parameters.accumulated_amount.0 >= preparing_parameters.target_amount.0
 				|| preparing_parameters.accumulated_amount.1 >= preparing_parameters.target_amount.1)
+				&& Self::enabled_trading_pairs().len() < T::MaxEnabledTradingPairs::get().saturated_into::<usize>()
 			{
 				let initial_price_0_in_1: Price = Price::checked_from_rational(
 					preparing_parameters.accumulated_amount.1,
@@ -464,12 +798,14 @@ impl<T: Config> Pallet<T> {
 					frame_system::Module::<T>::dec_consumers(&who);
 				}
 
+				Self::update_cumulative_price(trading_pair);
 				LiquidityPool::<T>::mutate(trading_pair, |(pool_0, pool_1)| {
 					*pool_0 = pool_0.saturating_add(preparing_parameters.accumulated_amount.0);
 					*pool_1 = pool_1.saturating_sub(preparing_parameters.accumulated_amount.1);
 				});
 
 				TradingPairStatuses::<T>::insert(trading_pair, TradingPairStatus::<_, _>::Enabled);
+				EnabledTradingPairs::<T>::append(trading_pair);
 
 				Self::deposit_event(Event::PreparingToEnabled(
 					trading_pair,
@@ -493,6 +829,7 @@ impl<T: Config> Pallet<T> {
 			TradingPairStatus::<_, _>::Preparing(preparing_parameters) => preparing_parameters,
 			_ => return Err(Error::<T>::MustBePreparing.into()),
 		};
+		Self::ensure_not_paused(trading_pair, DexOperation::AddLiquidity)?;
 		let (contribution_0, contribution_1) = if currency_id_a == trading_pair.0 {
 			(contribution_a, contribution_b)
 		} else {
@@ -570,7 +907,9 @@ impl<T: Config> Pallet<T> {
 			),
 			Error::<T>::MustBeEnabled,
 		);
+		Self::ensure_not_paused(trading_pair, DexOperation::AddLiquidity)?;
 
+		Self::update_cumulative_price(trading_pair);
 		LiquidityPool::<T>::try_mutate(trading_pair, |(pool_0, pool_1)| -> DispatchResult {
 			let total_shares = T::Currency::total_issuance(lp_share_currency_id);
 			let (max_amount_0, max_amount_1) = if currency_id_a == trading_pair.0 {
@@ -642,7 +981,9 @@ impl<T: Config> Pallet<T> {
 		let lp_share_currency_id = trading_pair
 			.get_dex_share_currency_id()
 			.ok_or(Error::<T>::InvalidCurrencyId)?;
+		Self::ensure_not_paused(trading_pair, DexOperation::RemoveLiquidity)?;
 
+		Self::update_cumulative_price(trading_pair);
 		LiquidityPool::<T>::try_mutate(trading_pair, |(pool_0, pool_1)| -> DispatchResult {
 			let total_shares = T::Currency::total_issuance(lp_share_currency_id);
 			let proportion = Ratio::checked_from_rational(remove_share, total_shares).unwrap_or_default();
@@ -804,12 +1145,100 @@ impl<T: Config> Pallet<T> {
 		}
 	}
 
+	/// The first `RouteSearchLimit` enabled trading pairs, the direct pair of the supply and the
+	/// target currency always among them, less those with swaps paused. The route search reads a
+	/// bounded number of pairs.
+	fn route_search_pairs(supply_currency_id: CurrencyId, target_currency_id: CurrencyId) -> Vec<TradingPair> {
+		let enabled_trading_pairs = Self::enabled_trading_pairs();
+		let direct_pair = TradingPair::from_token_currency_ids(supply_currency_id, target_currency_id)
+			.filter(|trading_pair| enabled_trading_pairs.contains(trading_pair));
+		direct_pair
+			.into_iter()
+			.chain(enabled_trading_pairs.iter().copied().filter(|trading_pair| Some(*trading_pair) != direct_pair))
+			.take(T::RouteSearchLimit::get().saturated_into())
+			.filter(|trading_pair| !Self::paused_operations(trading_pair, DexOperation::Swap))
+			.collect()
+	}
+
+	/// Paths from the supply to the target currency over the route search pairs, each at most
+	/// `TradingPathLimit` long and visiting a currency only once. At most `RouteSearchLimit`
+	/// paths are returned.
+	fn get_candidate_paths(supply_currency_id: CurrencyId, target_currency_id: CurrencyId) -> Vec<CurrencyIds> {
+		let trading_pairs = Self::route_search_pairs(supply_currency_id, target_currency_id);
+		let path_limit: usize = T::TradingPathLimit::get().saturated_into();
+		let paths_limit: usize = T::RouteSearchLimit::get().saturated_into();
+
+		let mut paths: Vec<CurrencyIds> = vec![];
+		let mut pending: Vec<CurrencyIds> = vec![vec![supply_currency_id]];
+		while let Some(path) = pending.pop() {
+			if paths.len() >= paths_limit {
+				break;
+			}
+			let last = path[path.len() - 1];
+			if last == target_currency_id {
+				paths.push(path);
+				continue;
+			}
+			if path.len() >= path_limit {
+				continue;
+			}
+			for trading_pair in trading_pairs.iter() {
+				let next = if trading_pair.0 == last {
+					trading_pair.1
+				} else if trading_pair.1 == last {
+					trading_pair.0
+				} else {
+					continue;
+				};
+				if !path.contains(&next) {
+					let mut next_path = path.clone();
+					next_path.push(next);
+					pending.push(next_path);
+				}
+			}
+		}
+		paths
+	}
+
+	/// The path giving the largest target amount for `supply_amount`, with that amount.
+	pub fn get_best_target_route(
+		supply_currency_id: CurrencyId,
+		target_currency_id: CurrencyId,
+		supply_amount: Balance,
+	) -> Option<(CurrencyIds, Balance)> {
+		Self::get_candidate_paths(supply_currency_id, target_currency_id)
+			.into_iter()
+			.filter_map(|path| {
+				let amounts = Self::get_target_amounts(&path, supply_amount, None).ok()?;
+				let target_amount = amounts[amounts.len() - 1];
+				Some((path, target_amount))
+			})
+			.max_by_key(|(_, target_amount)| *target_amount)
+	}
+
+	/// The path needing the smallest supply amount for `target_amount`, with that amount.
+	pub fn get_best_supply_route(
+		supply_currency_id: CurrencyId,
+		target_currency_id: CurrencyId,
+		target_amount: Balance,
+	) -> Option<(CurrencyIds, Balance)> {
+		Self::get_candidate_paths(supply_currency_id, target_currency_id)
+			.into_iter()
+			.filter_map(|path| {
+				let amounts = Self::get_supply_amounts(&path, target_amount, None).ok()?;
+				let supply_amount = amounts[0];
+				Some((path, supply_amount))
+			})
+			.min_by_key(|(_, supply_amount)| *supply_amount)
+	}
+
 	fn _swap(supply_currency_id: CurrencyId,
 			 target_currency_id: CurrencyId,
 			 supply_increment: Balance,
 			 target_decrement: Balance,
 	) {
 		if let Some(trading_pair) = TradingPair::from_token_currency_ids(supply_currency_id, target_currency_id) {
+			Self::update_cumulative_price(trading_pair);
 			LiquidityPool::<T>::mutate(trading_pair, |(pool_0, pool_1)| {
 				if supply_currency_id == trading_pair.0 {
 					*pool_0 = pool_0.saturating_add(supply_increment);
@@ -822,6 +1251,21 @@ impl<T: Config> Pallet<T> {
 		}
 	}
 
+	fn ensure_not_paused(trading_pair: TradingPair, operation: DexOperation) -> DispatchResult {
+		ensure!(!Self::paused_operations(trading_pair, operation), Error::<T>::OperationPaused);
+		Ok(())
+	}
+
+	/// every trading pair along the path must allow swapping
+	fn ensure_swap_path_not_paused(path: &[CurrencyId]) -> DispatchResult {
+		for pair in path.windows(2) {
+			if let Some(trading_pair) = TradingPair::from_token_currency_ids(pair[0], pair[1]) {
+				Self::ensure_not_paused(trading_pair, DexOperation::Swap)?;
+			}
+		}
+		Ok(())
+	}
+
 	fn _swap_by_path(path: &[CurrencyId], amounts: &[Balance]) {
 		let mut i: usize = 0;
 		while i + 1 < path.len() {
@@ -832,25 +1276,34 @@ impl<T: Config> Pallet<T> {
 		}
 	}
 
-	#[transactional]
-	fn do_swap_with_exact_supply(
+	/// Swap the whole `supply_amount` along `path`, returns the target amount.
+	fn swap_along_path(
 		who: &T::AccountId,
 		path: &[CurrencyId],
 		supply_amount: Balance,
-		min_target_amount: Balance,
 		price_impact_limit: Option<Ratio>,
 	) -> sp_std::result::Result<Balance, DispatchError> {
+		Self::ensure_swap_path_not_paused(path)?;
 		let amounts = Self::get_target_amounts(&path, supply_amount, price_impact_limit)?;
-		ensure!(
-			amounts[amounts.len() - 1] >= min_target_amount,
-			Error::<T>::InsufficientTargetAmount
-		);
 		let module_account_id = Self::account_id();
-		let actual_target_amount = amounts[amounts.len() - 1];
+		let target_amount = amounts[amounts.len() - 1];
 
 		T::Currency::transfer(path[0], who, &module_account_id, supply_amount)?;
 		Self::_swap_by_path(&path, &amounts);
-		T::Currency::transfer(path[path.len() - 1], &module_account_id, who, actual_target_amount)?;
+		T::Currency::transfer(path[path.len() - 1], &module_account_id, who, target_amount)?;
+		Ok(target_amount)
+	}
+
+	#[transactional]
+	fn do_swap_with_exact_supply(
+		who: &T::AccountId,
+		path: &[CurrencyId],
+		supply_amount: Balance,
+		min_target_amount: Balance,
+		price_impact_limit: Option<Ratio>,
+	) -> sp_std::result::Result<Balance, DispatchError> {
+		let actual_target_amount = Self::swap_along_path(who, path, supply_amount, price_impact_limit)?;
+		ensure!(actual_target_amount >= min_target_amount, Error::<T>::InsufficientTargetAmount);
 
 		Self::deposit_event(Event::Swap(
 			who.clone(),
@@ -869,6 +1322,7 @@ impl<T: Config> Pallet<T> {
 		max_supply_amount: Balance,
 		price_impact_limit: Option<Ratio>,
 	) -> sp_std::result::Result<Balance, DispatchError> {
+		Self::ensure_swap_path_not_paused(path)?;
 		let amounts = Self::get_supply_amounts(&path, target_amount, price_impact_limit)?;
 		ensure!(amounts[0] <= max_supply_amount, Error::<T>::ExcessiveSupplyAmount);
 		let module_account_id = Self::account_id();
@@ -887,4 +1341,403 @@ impl<T: Config> Pallet<T> {
 		Ok(actual_supply_amount)
 	}
 
+	/// Swap every route in turn, each one against the reserves left by the previous routes.
+	/// All routes share the supply and target currency and `min_target_amount` bounds their sum.
+	#[transactional]
+	fn do_swap_with_split_routes(
+		who: &T::AccountId,
+		routes: &[(CurrencyIds, Balance)],
+		min_target_amount: Balance,
+		price_impact_limit: Option<Ratio>,
+	) -> sp_std::result::Result<Balance, DispatchError> {
+		ensure!(
+			!routes.is_empty() && routes.len() <= T::SplitRouteLimit::get().saturated_into(),
+			Error::<T>::InvalidSplitRoutes
+		);
+		let (first_path, _) = &routes[0];
+		ensure!(!first_path.is_empty(), Error::<T>::InvalidTradingPathLength);
+		let (supply_currency_id, target_currency_id) = (first_path[0], first_path[first_path.len() - 1]);
+
+		let mut route_amounts: Vec<(CurrencyIds, Balance, Balance)> = Vec::with_capacity(routes.len());
+		let mut total_supply_amount: Balance = Zero::zero();
+		let mut total_target_amount: Balance = Zero::zero();
+		for (path, supply_amount) in routes.iter() {
+			ensure!(
+				!supply_amount.is_zero()
+					&& path.first() == Some(&supply_currency_id)
+					&& path.last() == Some(&target_currency_id),
+				Error::<T>::InvalidSplitRoutes
+			);
+			let target_amount = Self::swap_along_path(who, path, *supply_amount, price_impact_limit)?;
+			total_supply_amount = total_supply_amount.saturating_add(*supply_amount);
+			total_target_amount = total_target_amount.saturating_add(target_amount);
+			route_amounts.push((path.clone(), *supply_amount, target_amount));
+		}
+		ensure!(total_target_amount >= min_target_amount, Error::<T>::InsufficientTargetAmount);
+
+		Self::deposit_event(Event::SplitSwap(who.clone(), route_amounts, total_supply_amount, total_target_amount));
+		Ok(total_target_amount)
+	}
+
+	fn ensure_valid_leverage(leverage: Ratio) -> DispatchResult {
+		ensure!(
+			leverage > Ratio::one() && leverage <= T::MaxLeverage::get(),
+			Error::<T>::InvalidLeverage
+		);
+		Ok(())
+	}
+
+	fn borrow_by_leverage(who: &T::AccountId, currency_id: CurrencyId, borrow_amount: Balance) -> DispatchResult {
+		let debit_adjustment = TryInto::<Amount>::try_into(borrow_amount).map_err(|_| Error::<T>::AmountConvertFailed)?;
+		T::AssetPool::update_debit(who, currency_id, debit_adjustment)
+	}
+
+	fn collateral_currency_id(trading_pair: TradingPair, debit_currency_id: CurrencyId) -> CurrencyId {
+		if debit_currency_id == trading_pair.0 {
+			trading_pair.1
+		} else {
+			trading_pair.0
+		}
+	}
+
+	fn open_position(
+		who: &T::AccountId,
+		trading_pair: TradingPair,
+		debit_currency_id: CurrencyId,
+		margin: Balance,
+		borrow_amount: Balance,
+		collateral: Balance,
+	) -> DispatchResult {
+		let collateral_currency_id = Self::collateral_currency_id(trading_pair, debit_currency_id);
+		let debit = T::AssetPool::debit_rate(debit_currency_id)
+			.reciprocal()
+			.unwrap_or_default()
+			.saturating_mul_int(borrow_amount);
+
+		LeveragePool::<T>::try_mutate(trading_pair, who, |maybe_position| -> DispatchResult {
+			let mut position = maybe_position.take().unwrap_or(LeveragePosition {
+				debit_currency_id,
+				margin: Zero::zero(),
+				collateral: Zero::zero(),
+				debit: Zero::zero(),
+			});
+			ensure!(
+				position.debit_currency_id == debit_currency_id,
+				Error::<T>::LeverageDirectionMismatch
+			);
+
+			// the bought currency stays in dex until the position is closed
+			T::Currency::transfer(collateral_currency_id, who, &Self::account_id(), collateral)?;
+
+			position.margin = position.margin.saturating_add(margin);
+			position.collateral = position.collateral.saturating_add(collateral);
+			position.debit = position.debit.saturating_add(debit);
+
+			// price impact must not open a position that can be liquidated right away
+			ensure!(
+				!Self::position_values(trading_pair, &position)
+					.map_or(false, |(collateral_value, debit_value)| Self::is_below_liquidation_ratio(collateral_value, debit_value)),
+				Error::<T>::UnsafePosition
+			);
+			*maybe_position = Some(position);
+			Ok(())
+		})?;
+
+		Self::deposit_event(Event::PositionOpened(
+			who.clone(),
+			trading_pair,
+			debit_currency_id,
+			margin,
+			borrow_amount,
+			collateral,
+		));
+		Ok(())
+	}
+
+	#[transactional]
+	fn do_swap_with_exact_supply_by_leverage(
+		who: &T::AccountId,
+		supply_currency_id: CurrencyId,
+		target_currency_id: CurrencyId,
+		supply_amount: Balance,
+		leverage: Ratio,
+		min_target_amount: Balance,
+		price_impact_limit: Option<Ratio>,
+	) -> sp_std::result::Result<Balance, DispatchError> {
+		let trading_pair = TradingPair::from_token_currency_ids(supply_currency_id, target_currency_id)
+			.ok_or(Error::<T>::InvalidCurrencyId)?;
+		Self::ensure_valid_leverage(leverage)?;
+
+		let total_supply_amount = leverage.saturating_mul_int(supply_amount);
+		let borrow_amount = total_supply_amount.saturating_sub(supply_amount);
+
+		Self::borrow_by_leverage(who, supply_currency_id, borrow_amount)?;
+		let target_amount = Self::do_swap_with_exact_supply(
+			who,
+			&[supply_currency_id, target_currency_id],
+			total_supply_amount,
+			min_target_amount,
+			price_impact_limit,
+		)?;
+		Self::open_position(who, trading_pair, supply_currency_id, supply_amount, borrow_amount, target_amount)?;
+
+		Ok(target_amount)
+	}
+
+	#[transactional]
+	fn do_swap_with_exact_target_by_leverage(
+		who: &T::AccountId,
+		supply_currency_id: CurrencyId,
+		target_currency_id: CurrencyId,
+		target_amount: Balance,
+		leverage: Ratio,
+		max_supply_amount: Balance,
+		price_impact_limit: Option<Ratio>,
+	) -> sp_std::result::Result<Balance, DispatchError> {
+		let trading_pair = TradingPair::from_token_currency_ids(supply_currency_id, target_currency_id)
+			.ok_or(Error::<T>::InvalidCurrencyId)?;
+		Self::ensure_valid_leverage(leverage)?;
+
+		let path = [supply_currency_id, target_currency_id];
+		let total_supply_amount = Self::get_supply_amounts(&path, target_amount, price_impact_limit)?[0];
+		let supply_amount = leverage.reciprocal().unwrap_or_default().saturating_mul_int(total_supply_amount);
+		ensure!(supply_amount <= max_supply_amount, Error::<T>::ExcessiveSupplyAmount);
+		let borrow_amount = total_supply_amount.saturating_sub(supply_amount);
+
+		Self::borrow_by_leverage(who, supply_currency_id, borrow_amount)?;
+		let _ = Self::do_swap_with_exact_target(who, &path, target_amount, total_supply_amount, price_impact_limit)?;
+		Self::open_position(who, trading_pair, supply_currency_id, supply_amount, borrow_amount, target_amount)?;
+
+		Ok(supply_amount)
+	}
+
+	#[transactional]
+	fn do_reduce_position(
+		who: &T::AccountId,
+		trading_pair: TradingPair,
+		collateral_amount: Balance,
+		min_target_amount: Balance,
+		price_impact_limit: Option<Ratio>,
+	) -> DispatchResult {
+		let mut position = Self::leverage_pool(trading_pair, who).ok_or(Error::<T>::PositionNotFound)?;
+		ensure!(
+			!collateral_amount.is_zero() && collateral_amount <= position.collateral,
+			Error::<T>::InvalidCollateralAmount
+		);
+		let debit_currency_id = position.debit_currency_id;
+		let collateral_currency_id = Self::collateral_currency_id(trading_pair, debit_currency_id);
+
+		let (margin_decrement, debit_decrement) = if collateral_amount == position.collateral {
+			(position.margin, position.debit)
+		} else {
+			let proportion = Ratio::checked_from_rational(collateral_amount, position.collateral).unwrap_or_default();
+			(proportion.saturating_mul_int(position.margin), proportion.saturating_mul_int(position.debit))
+		};
+		let repay_amount = T::AssetPool::debit_rate(debit_currency_id).saturating_mul_int(debit_decrement);
+
+		// sell the released collateral and repay the matching part of debit,
+		// the trader covers any shortfall from own balance
+		T::Currency::transfer(collateral_currency_id, &Self::account_id(), who, collateral_amount)?;
+		let _ = Self::do_swap_with_exact_supply(
+			who,
+			&[collateral_currency_id, debit_currency_id],
+			collateral_amount,
+			min_target_amount,
+			price_impact_limit,
+		)?;
+		Self::repay_by_leverage(who, debit_currency_id, repay_amount)?;
+
+		position.margin = position.margin.saturating_sub(margin_decrement);
+		position.collateral = position.collateral.saturating_sub(collateral_amount);
+		position.debit = position.debit.saturating_sub(debit_decrement);
+
+		if position.collateral.is_zero() {
+			LeveragePool::<T>::remove(trading_pair, who);
+			Self::deposit_event(Event::PositionClosed(who.clone(), trading_pair, collateral_amount, repay_amount));
+		} else {
+			// what stays open must not be left for liquidation
+			ensure!(
+				!Self::position_values(trading_pair, &position)
+					.map_or(false, |(collateral_value, debit_value)| Self::is_below_liquidation_ratio(collateral_value, debit_value)),
+				Error::<T>::UnsafePosition
+			);
+			LeveragePool::<T>::insert(trading_pair, who, position);
+			Self::deposit_event(Event::PositionAdjusted(
+				who.clone(),
+				trading_pair,
+				position.margin,
+				position.collateral,
+				position.debit,
+			));
+		}
+		Ok(())
+	}
+
+	#[transactional]
+	fn do_add_margin(who: &T::AccountId, trading_pair: TradingPair, margin_amount: Balance) -> DispatchResult {
+		LeveragePool::<T>::try_mutate_exists(trading_pair, who, |maybe_position| -> DispatchResult {
+			let position = maybe_position.as_mut().ok_or(Error::<T>::PositionNotFound)?;
+			let debit_rate = T::AssetPool::debit_rate(position.debit_currency_id);
+			let debit_decrement = debit_rate.reciprocal().unwrap_or_default().saturating_mul_int(margin_amount);
+			ensure!(debit_decrement <= position.debit, Error::<T>::ExcessiveMargin);
+
+			// new margin goes straight to repay the debit
+			Self::repay_by_leverage(who, position.debit_currency_id, margin_amount)?;
+			position.margin = position.margin.saturating_add(margin_amount);
+			position.debit = position.debit.saturating_sub(debit_decrement);
+
+			Self::deposit_event(Event::PositionAdjusted(
+				who.clone(),
+				trading_pair,
+				position.margin,
+				position.collateral,
+				position.debit,
+			));
+			Ok(())
+		})
+	}
+
+	fn repay_by_leverage(who: &T::AccountId, currency_id: CurrencyId, repay_amount: Balance) -> DispatchResult {
+		let debit_adjustment = TryInto::<Amount>::try_into(repay_amount).map_err(|_| Error::<T>::AmountConvertFailed)?;
+		T::AssetPool::update_debit(who, currency_id, debit_adjustment.saturating_neg())
+	}
+
+	/// Collateral value and debit value of a leveraged position in debit currency, by the price source.
+	fn position_values(trading_pair: TradingPair, position: &LeveragePosition) -> Option<(Balance, Balance)> {
+		let collateral_currency_id = Self::collateral_currency_id(trading_pair, position.debit_currency_id);
+		let price = T::PriceSource::get_relative_price(collateral_currency_id, position.debit_currency_id)?;
+		let debit_value = T::AssetPool::debit_rate(position.debit_currency_id).saturating_mul_int(position.debit);
+		Some((price.saturating_mul_int(position.collateral), debit_value))
+	}
+
+	fn is_below_liquidation_ratio(collateral_value: Balance, debit_value: Balance) -> bool {
+		!debit_value.is_zero() && collateral_value < T::LiquidationRatio::get().saturating_mul_int(debit_value)
+	}
+
+	/// Whether the leveraged position can be liquidated. Positions can't be judged without prices,
+	/// so they are treated as safe.
+	pub fn is_position_unsafe(trading_pair: TradingPair, who: &T::AccountId) -> bool {
+		Self::leverage_pool(trading_pair, who)
+			.and_then(|position| Self::position_values(trading_pair, &position))
+			.map_or(false, |(collateral_value, debit_value)| Self::is_below_liquidation_ratio(collateral_value, debit_value))
+	}
+
+	#[transactional]
+	fn do_liquidate_position(liquidator: &T::AccountId, who: &T::AccountId, trading_pair: TradingPair) -> DispatchResult {
+		let position = Self::leverage_pool(trading_pair, who).ok_or(Error::<T>::PositionNotFound)?;
+		let (collateral_value, debit_value) = Self::position_values(trading_pair, &position)
+			.filter(|&(collateral_value, debit_value)| Self::is_below_liquidation_ratio(collateral_value, debit_value))
+			.ok_or(Error::<T>::MustBeUnsafe)?;
+		let debit_currency_id = position.debit_currency_id;
+		let collateral_currency_id = Self::collateral_currency_id(trading_pair, debit_currency_id);
+
+		// sell the whole collateral, at most the penalty below the price source. Once the collateral
+		// is worth less than the debit the trader has nothing left to protect, the collateral is sold
+		// for what the dex pays and the asset pool bears the rest.
+		let penalty_rate = T::LiquidationPenalty::get();
+		let min_target_amount = if collateral_value < debit_value {
+			Zero::zero()
+		} else {
+			Ratio::one().saturating_sub(penalty_rate).saturating_mul_int(collateral_value)
+		};
+		T::Currency::transfer(collateral_currency_id, &Self::account_id(), who, position.collateral)?;
+		let target_amount = Self::do_swap_with_exact_supply(
+			who,
+			&[collateral_currency_id, debit_currency_id],
+			position.collateral,
+			min_target_amount,
+			None,
+		)?;
+
+		// the sale repays what it can, the rest is a loss of the asset pool
+		let repay_amount = target_amount.min(debit_value);
+		Self::repay_by_leverage(who, debit_currency_id, repay_amount)?;
+		let written_off = debit_value.saturating_sub(repay_amount);
+		if !written_off.is_zero() {
+			T::AssetPool::write_off_debit(who, debit_currency_id, written_off)?;
+		}
+
+		// the liquidator is paid from the surplus, the trader keeps the remainder
+		let penalty = penalty_rate
+			.saturating_mul_int(repay_amount)
+			.min(target_amount.saturating_sub(repay_amount));
+		T::Currency::transfer(debit_currency_id, who, liquidator, penalty)?;
+		LeveragePool::<T>::remove(trading_pair, who);
+
+		Self::deposit_event(Event::PositionLiquidated(
+			liquidator.clone(),
+			who.clone(),
+			trading_pair,
+			position.collateral,
+			repay_amount,
+			written_off,
+			penalty,
+		));
+		Ok(())
+	}
+
+	/// Profit and loss of a leveraged position in debit currency, valued by selling the
+	/// collateral into the trading pair at the current price.
+	pub fn get_position_pnl(who: &T::AccountId, trading_pair: TradingPair) -> Option<Amount> {
+		let position = Self::leverage_pool(trading_pair, who)?;
+		let collateral_currency_id = Self::collateral_currency_id(trading_pair, position.debit_currency_id);
+		let value = Self::get_target_amounts(&[collateral_currency_id, position.debit_currency_id], position.collateral, None)
+			.ok()
+			.map(|amounts| amounts[1])?;
+		let cost = T::AssetPool::debit_rate(position.debit_currency_id)
+			.saturating_mul_int(position.debit)
+			.saturating_add(position.margin);
+
+		if value >= cost {
+			TryInto::<Amount>::try_into(value - cost).ok()
+		} else {
+			TryInto::<Amount>::try_into(cost - value).ok().map(|loss| loss.saturating_neg())
+		}
+	}
+}
+
+impl<T: Config> DEXManager<T::AccountId> for Pallet<T> {
+	fn get_swap_target_amount(path: &[CurrencyId], supply_amount: Balance, price_impact_limit: Option<Ratio>) -> Option<Balance> {
+		Self::get_target_amounts(path, supply_amount, price_impact_limit)
+			.ok()
+			.map(|amounts| amounts[amounts.len() - 1])
+	}
+
+	fn get_swap_supply_amount(path: &[CurrencyId], target_amount: Balance, price_impact_limit: Option<Ratio>) -> Option<Balance> {
+		Self::get_supply_amounts(path, target_amount, price_impact_limit)
+			.ok()
+			.map(|amounts| amounts[0])
+	}
+
+	fn swap_with_exact_supply(
+		who: &T::AccountId,
+		path: &[CurrencyId],
+		supply_amount: Balance,
+		min_target_amount: Balance,
+		price_impact_limit: Option<Ratio>,
+	) -> sp_std::result::Result<Balance, DispatchError> {
+		Self::do_swap_with_exact_supply(who, path, supply_amount, min_target_amount, price_impact_limit)
+	}
+
+	fn swap_with_exact_target(
+		who: &T::AccountId,
+		path: &[CurrencyId],
+		target_amount: Balance,
+		max_supply_amount: Balance,
+		price_impact_limit: Option<Ratio>,
+	) -> sp_std::result::Result<Balance, DispatchError> {
+		Self::do_swap_with_exact_target(who, path, target_amount, max_supply_amount, price_impact_limit)
+	}
+}
+
+impl<T: Config> TwapProvider for Pallet<T> {
+	fn get_twap(base_currency_id: CurrencyId, quote_currency_id: CurrencyId, window: u64) -> Option<Price> {
+		let trading_pair = TradingPair::from_token_currency_ids(base_currency_id, quote_currency_id)?;
+		let (price_0, price_1) = Self::get_twap(trading_pair, window)?;
+		if base_currency_id == trading_pair.0 {
+			Some(price_0)
+		} else {
+			Some(price_1)
+		}
+	}
 }
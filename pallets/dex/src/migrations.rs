@@ -0,0 +1,39 @@
+//! Storage migrations run on runtime upgrade.
+
+use super::*;
+
+/// `LeveragePool` held a `(Balance, Balance)` pair no call ever wrote, it carries no debit
+/// currency or debit to convert into a `LeveragePosition`, so leftover entries are dropped.
+pub fn migrate_to_v2<T: Config>() -> Weight {
+	if StorageVersion::<T>::get() != Releases::V1_0_0 {
+		return 0;
+	}
+
+	let mut removed: Weight = 0;
+	LeveragePool::<T>::translate::<(Balance, Balance), _>(|_, _, _| {
+		removed += 1;
+		None
+	});
+	StorageVersion::<T>::put(Releases::V2_0_0);
+
+	T::DbWeight::get().reads_writes(removed + 1, removed + 1)
+}
+
+/// Index the enabled trading pairs in `EnabledTradingPairs`. All of them are kept even beyond
+/// `MaxEnabledTradingPairs`, no more can be enabled until some are disabled.
+pub fn migrate_to_v3<T: Config>() -> Weight {
+	if StorageVersion::<T>::get() != Releases::V2_0_0 {
+		return 0;
+	}
+
+	let mut read: Weight = 0;
+	let enabled_trading_pairs: Vec<TradingPair> = TradingPairStatuses::<T>::iter()
+		.inspect(|_| read += 1)
+		.filter(|(_, status)| matches!(status, TradingPairStatus::<_, _>::Enabled))
+		.map(|(trading_pair, _)| trading_pair)
+		.collect();
+	EnabledTradingPairs::<T>::put(enabled_trading_pairs);
+	StorageVersion::<T>::put(Releases::V3_0_0);
+
+	T::DbWeight::get().reads_writes(read + 1, 2)
+}
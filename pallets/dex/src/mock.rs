@@ -3,12 +3,15 @@
 #![cfg(test)]
 
 use frame_support::{construct_runtime, ord_parameter_types, parameter_types};
-use frame_system::EnsureSignedBy;
+use frame_system::{EnsureRoot, EnsureSignedBy, offchain::SendTransactionTypes};
 use orml_traits::{ parameter_type_with_key};
 use sp_core::H256;
-use sp_runtime::{testing::Header, traits::IdentityLookup};
+use sp_runtime::{testing::{Header, TestXt}, traits::IdentityLookup};
+use sp_std::cell::RefCell;
+use std::collections::BTreeMap;
 
-use model::Amount;
+use asset_pool::{AssetPoolInfo, InterestInfo, InterestModel};
+use model::{Amount, AssetPoolManager, Price, PriceProvider};
 
 use super::*;
 
@@ -17,6 +20,9 @@ pub type AccountId = u128;
 
 pub const ALICE: AccountId = 1;
 pub const BOB: AccountId = 2;
+pub const LOANS: AccountId = 4;
+pub const CHARLIE: AccountId = 5;
+pub const TREASURY: AccountId = 6;
 // order is important for unit tests
 pub const MB: CurrencyId = CurrencyId::Token(1);
 pub const ETH: CurrencyId = CurrencyId::Token(4);
@@ -30,6 +36,10 @@ mod dex {
 	pub use super::super::*;
 }
 
+mod loans {
+	pub use asset_pool::*;
+}
+
 parameter_types! {
 	pub const BlockHashCount: BlockNumber = 250;
 }
@@ -75,14 +85,127 @@ impl orml_tokens::Config for Runtime {
 	type OnDust = ();
 }
 
+parameter_types! {
+	pub const MinimumPeriod: u64 = 1000;
+}
+
+impl pallet_timestamp::Config for Runtime {
+	type Moment = u64;
+	type OnTimestampSet = ();
+	type MinimumPeriod = MinimumPeriod;
+	type WeightInfo = ();
+}
+
 ord_parameter_types! {
 	pub const WhiteListOrigin: AccountId = 3;
 }
 
+parameter_types! {
+	pub const LoansModuleId: ModuleId = ModuleId(*b"antimatterdc");
+	pub const UnsignedPriority: u64 = 1 << 20;
+	pub const LiquidationPenaltyReceiver: AccountId = TREASURY;
+	pub FlashLoanFee: Ratio = Ratio::saturating_from_rational(1, 100);
+	pub OverduePenaltyRate: Ratio = Ratio::saturating_from_rational(10, 100);
+	pub const OverdueGracePeriod: u64 = 1000;
+	pub LiquidationCloseFactor: Ratio = Ratio::saturating_from_rational(50, 100);
+	pub MaxLiquidationBonus: Ratio = Ratio::saturating_from_rational(20, 100);
+}
+
+impl<LocalCall> SendTransactionTypes<LocalCall> for Runtime
+	where
+		Call: From<LocalCall>,
+{
+	type OverarchingCall = Call;
+	type Extrinsic = TestXt<Call, ()>;
+}
+
+impl asset_pool::Config for Runtime {
+	type Event = Event;
+	type Currency = Tokens;
+	type ModuleId = LoansModuleId;
+	type UnixTime = Timestamp;
+	type UnsignedPriority = UnsignedPriority;
+	type OnUpdateLoan = ();
+	type PriceSource = MockPriceSource;
+	type DEX = DexModule;
+	type LiquidationPenaltyReceiver = LiquidationPenaltyReceiver;
+	type UpdateOrigin = EnsureRoot<AccountId>;
+	type FlashLoanReceiver = ();
+	type FlashLoanFee = FlashLoanFee;
+	type OverduePenaltyRate = OverduePenaltyRate;
+	type OverdueGracePeriod = OverdueGracePeriod;
+	type LiquidationCloseFactor = LiquidationCloseFactor;
+	type MaxLiquidationBonus = MaxLiquidationBonus;
+}
+
+thread_local! {
+	static PRICES: RefCell<BTreeMap<CurrencyId, Price>> = RefCell::new(BTreeMap::new());
+	static USE_LOANS_MODULE: RefCell<bool> = RefCell::new(false);
+}
+
+/// Lends from the LOANS account, or from the real asset pool in `LoansModule` once an `ExtBuilder`
+/// is built `with_asset_pool`.
+pub struct MockAssetPool;
+impl MockAssetPool {
+	fn uses_loans_module() -> bool {
+		USE_LOANS_MODULE.with(|use_loans_module| *use_loans_module.borrow())
+	}
+}
+impl AssetPoolManager<AccountId> for MockAssetPool {
+	fn update_debit(who: &AccountId, currency_id: CurrencyId, debit_adjustment: Amount) -> DispatchResult {
+		if Self::uses_loans_module() {
+			return <LoansModule as AssetPoolManager<AccountId>>::update_debit(who, currency_id, debit_adjustment);
+		}
+		let amount = debit_adjustment.saturating_abs() as Balance;
+		if debit_adjustment.is_positive() {
+			Tokens::transfer(currency_id, &LOANS, who, amount)
+		} else {
+			Tokens::transfer(currency_id, who, &LOANS, amount)
+		}
+	}
+
+	fn write_off_debit(who: &AccountId, currency_id: CurrencyId, amount: Balance) -> DispatchResult {
+		if Self::uses_loans_module() {
+			return <LoansModule as AssetPoolManager<AccountId>>::write_off_debit(who, currency_id, amount);
+		}
+		Ok(())
+	}
+
+	fn debit_rate(currency_id: CurrencyId) -> Ratio {
+		if Self::uses_loans_module() {
+			return <LoansModule as AssetPoolManager<AccountId>>::debit_rate(currency_id);
+		}
+		Ratio::one()
+	}
+}
+
+pub struct MockPriceSource;
+impl MockPriceSource {
+	pub fn set_price(currency_id: CurrencyId, price: Option<Price>) {
+		PRICES.with(|prices| match price {
+			Some(price) => prices.borrow_mut().insert(currency_id, price),
+			None => prices.borrow_mut().remove(&currency_id),
+		});
+	}
+}
+impl PriceProvider for MockPriceSource {
+	fn get_price(currency_id: CurrencyId) -> Option<Price> {
+		PRICES.with(|prices| prices.borrow().get(&currency_id).copied())
+	}
+}
+
 parameter_types! {
 	pub const GetExchangeFee: (u32, u32) = (1, 100);
 	pub const TradingPathLimit: u32 = 3;
+	pub const SplitRouteLimit: u32 = 4;
+	pub const RouteSearchLimit: u32 = 3;
+	pub const MaxEnabledTradingPairs: u32 = 6;
 	pub const DEXModuleId: ModuleId = ModuleId(*b"antimatterex");
+	pub MaxLeverage: Ratio = Ratio::saturating_from_rational(5, 1);
+	pub LiquidationRatio: Ratio = Ratio::saturating_from_rational(110, 100);
+	pub LiquidationPenalty: Ratio = Ratio::saturating_from_rational(5, 100);
+	pub const ObservationPeriod: u64 = 60;
+	pub const ObservationLimit: u32 = 3;
 }
 
 impl Config for Runtime {
@@ -90,9 +213,20 @@ impl Config for Runtime {
 	type Currency = Tokens;
 	type GetExchangeFee = GetExchangeFee;
 	type TradingPathLimit = TradingPathLimit;
+	type SplitRouteLimit = SplitRouteLimit;
+	type RouteSearchLimit = RouteSearchLimit;
+	type MaxEnabledTradingPairs = MaxEnabledTradingPairs;
 	type ModuleId = DEXModuleId;
 	type WhiteListOrigin = EnsureSignedBy<WhiteListOrigin, AccountId>;
+	type AssetPool = MockAssetPool;
+	type MaxLeverage = MaxLeverage;
+	type PriceSource = MockPriceSource;
+	type LiquidationRatio = LiquidationRatio;
+	type LiquidationPenalty = LiquidationPenalty;
 	type WeightInfo = ();
+	type UnixTime = Timestamp;
+	type ObservationPeriod = ObservationPeriod;
+	type ObservationLimit = ObservationLimit;
 }
 
 type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Runtime>;
@@ -106,7 +240,9 @@ construct_runtime!(
 	{
 		System: frame_system::{Module, Call, Storage, Config, Event<T>},
 		DexModule: dex::{Module, Storage, Call, Event<T>, Config<T>},
+		LoansModule: loans::{Module, Storage, Call, Event<T>},
 		Tokens: orml_tokens::{Module, Storage, Event<T>, Config<T>},
+		Timestamp: pallet_timestamp::{Module, Call, Storage, Inherent},
 	}
 );
 
@@ -115,6 +251,7 @@ pub struct ExtBuilder {
 	initial_preparing_trading_pairs: Vec<(TradingPair, (Balance, Balance), (Balance, Balance), BlockNumber)>,
 	initial_enabled_trading_pairs: Vec<TradingPair>,
 	initial_liquidity_pools: Vec<(AccountId, Vec<(TradingPair, (Balance, Balance))>)>,
+	asset_pool_params: Vec<(CurrencyId, AssetPoolInfo)>,
 }
 
 impl Default for ExtBuilder {
@@ -127,10 +264,13 @@ impl Default for ExtBuilder {
 				(BOB, ETH, 1_000_000_000_000_000_000u128),
 				(ALICE, DOT, 1_000_000_000_000_000_000u128),
 				(BOB, DOT, 1_000_000_000_000_000_000u128),
+				(LOANS, MB, 1_000_000_000_000_000_000u128),
+				(LOANS, DOT, 1_000_000_000_000_000_000u128),
 			],
 			initial_preparing_trading_pairs: vec![],
 			initial_enabled_trading_pairs: vec![],
 			initial_liquidity_pools: vec![],
+			asset_pool_params: vec![],
 		}
 	}
 }
@@ -177,7 +317,15 @@ impl ExtBuilder {
 		self
 	}
 
+	/// Borrow leveraged debit from DOT and ETH asset pools in `LoansModule` instead of the LOANS account.
+	pub fn with_asset_pool(mut self) -> Self {
+		self.asset_pool_params = vec![(DOT, asset_pool_params()), (ETH, asset_pool_params())];
+		self
+	}
+
 	pub fn build(self) -> sp_io::TestExternalities {
+		USE_LOANS_MODULE.with(|use_loans_module| *use_loans_module.borrow_mut() = !self.asset_pool_params.is_empty());
+
 		let mut t = frame_system::GenesisConfig::default()
 			.build_storage::<Runtime>()
 			.unwrap();
@@ -196,6 +344,38 @@ impl ExtBuilder {
 			.assimilate_storage(&mut t)
 			.unwrap();
 
+		loans::GenesisConfig {
+			asset_pool_params: self.asset_pool_params,
+		}
+			.assimilate_storage::<Runtime>(&mut t)
+			.unwrap();
+
 		t.into()
 	}
 }
+
+fn asset_pool_params() -> AssetPoolInfo {
+	AssetPoolInfo {
+		maximum_total_debit_ratio: Ratio::saturating_from_rational(90, 100),
+		minimum_deposit: 10u128.pow(1),
+		minimum_debit: 10u128.pow(1),
+		supply_cap: 0,
+		borrow_cap: 0,
+		collateral_factor: Ratio::saturating_from_rational(75, 100),
+		liquidation_threshold: Ratio::saturating_from_rational(80, 100),
+		liquidation_bonus: Ratio::saturating_from_rational(10, 100),
+		total_deposit: 0,
+		total_debit: 0,
+		deposit_rate: Ratio::one(),
+		debit_rate: Ratio::one(),
+		deposit_apy: Ratio::zero(),
+		debit_apy: Ratio::zero(),
+		reserve_factor: Ratio::saturating_from_rational(10, 100),
+		interest_model: InterestModel::JumpRate(InterestInfo {
+			critical_point: Ratio::saturating_from_rational(90, 100),
+			base: Ratio::zero(),
+			slope_1: Ratio::saturating_from_rational(4, 100),
+			slope_2: Ratio::one(),
+		}),
+	}
+}
@@ -4,9 +4,11 @@ use frame_support::{assert_noop, assert_ok};
 use orml_traits::MultiReservableCurrency;
 use sp_runtime::traits::BadOrigin;
 
+use asset_pool::LoanInfo;
 use mock::{
-	ALICE, BOB, DexModule, DOT, ETH, Event, ExtBuilder, FIL, WhiteListOrigin, MB, MB_DOT_PAIR, MB_ETH_PAIR,
-	Origin, Runtime, System, Tokens,
+	ALICE, BOB, CHARLIE, DexModule, DOT, DOT_ETH_PAIR, ETH, Event, ExtBuilder, FIL, LOANS, LoansModule, WhiteListOrigin,
+	MaxEnabledTradingPairs, MB, MB_DOT_PAIR, MB_ETH_PAIR, MockPriceSource, Origin, RouteSearchLimit, Runtime, System,
+	Timestamp, Tokens,
 };
 
 use super::*;
@@ -1115,3 +1117,923 @@ fn initialize_added_liquidity_pools_genesis_work() {
 			);
 		});
 }
+
+#[test]
+fn swap_with_exact_supply_by_leverage_work() {
+	ExtBuilder::default()
+		.initialize_enabled_trading_pairs()
+		.build()
+		.execute_with(|| {
+			System::set_block_number(1);
+
+			assert_ok!(DexModule::add_liquidity(
+				Origin::signed(ALICE),
+				MB,
+				DOT,
+				500_000_000_000_000,
+				100_000_000_000_000,
+				false,
+			));
+
+			assert_noop!(
+				DexModule::swap_with_exact_supply_by_leverage(
+					Origin::signed(BOB),
+					DOT,
+					MB,
+					10_000_000_000_000,
+					Ratio::one(),
+					0,
+					Ratio::one(),
+				),
+				Error::<Runtime>::InvalidLeverage
+			);
+			assert_noop!(
+				DexModule::swap_with_exact_supply_by_leverage(
+					Origin::signed(BOB),
+					DOT,
+					MB,
+					10_000_000_000_000,
+					Ratio::saturating_from_rational(6, 1),
+					0,
+					Ratio::one(),
+				),
+				Error::<Runtime>::InvalidLeverage
+			);
+
+			assert_ok!(DexModule::swap_with_exact_supply_by_leverage(
+				Origin::signed(BOB),
+				DOT,
+				MB,
+				10_000_000_000_000,
+				Ratio::saturating_from_rational(2, 1),
+				0,
+				Ratio::one(),
+			));
+			let position_opened_event = Event::dex(crate::Event::PositionOpened(
+				BOB,
+				MB_DOT_PAIR,
+				DOT,
+				10_000_000_000_000,
+				10_000_000_000_000,
+				82_637_729_549_248,
+			));
+			assert!(System::events().iter().any(|record| record.event == position_opened_event));
+
+			assert_eq!(
+				DexModule::leverage_pool(MB_DOT_PAIR, BOB),
+				Some(LeveragePosition {
+					debit_currency_id: DOT,
+					margin: 10_000_000_000_000,
+					collateral: 82_637_729_549_248,
+					debit: 10_000_000_000_000,
+				})
+			);
+			assert_eq!(
+				DexModule::get_liquidity(MB, DOT),
+				(417_362_270_450_752, 120_000_000_000_000)
+			);
+			assert_eq!(Tokens::free_balance(DOT, &BOB), 999_990_000_000_000_000);
+			assert_eq!(Tokens::free_balance(MB, &BOB), 1_000_000_000_000_000_000);
+			assert_eq!(Tokens::free_balance(DOT, &LOANS), 999_990_000_000_000_000);
+			assert_eq!(
+				Tokens::free_balance(MB, &DexModule::account_id()),
+				500_000_000_000_000
+			);
+
+			assert_noop!(
+				DexModule::swap_with_exact_supply_by_leverage(
+					Origin::signed(BOB),
+					MB,
+					DOT,
+					10_000_000_000_000,
+					Ratio::saturating_from_rational(2, 1),
+					0,
+					Ratio::one(),
+				),
+				Error::<Runtime>::LeverageDirectionMismatch
+			);
+		});
+}
+
+#[test]
+fn swap_with_exact_target_by_leverage_work() {
+	ExtBuilder::default()
+		.initialize_enabled_trading_pairs()
+		.build()
+		.execute_with(|| {
+			System::set_block_number(1);
+
+			assert_ok!(DexModule::add_liquidity(
+				Origin::signed(ALICE),
+				MB,
+				DOT,
+				500_000_000_000_000,
+				100_000_000_000_000,
+				false,
+			));
+
+			assert_noop!(
+				DexModule::swap_with_exact_target_by_leverage(
+					Origin::signed(BOB),
+					DOT,
+					MB,
+					50_000_000_000_000,
+					Ratio::saturating_from_rational(3, 1),
+					3_000_000_000_000,
+					Ratio::one(),
+				),
+				Error::<Runtime>::ExcessiveSupplyAmount
+			);
+
+			assert_ok!(DexModule::swap_with_exact_target_by_leverage(
+				Origin::signed(BOB),
+				DOT,
+				MB,
+				50_000_000_000_000,
+				Ratio::saturating_from_rational(3, 1),
+				4_000_000_000_000,
+				Ratio::one(),
+			));
+			assert_eq!(
+				DexModule::leverage_pool(MB_DOT_PAIR, BOB),
+				Some(LeveragePosition {
+					debit_currency_id: DOT,
+					margin: 3_741_114_852_225,
+					collateral: 50_000_000_000_000,
+					debit: 7_482_229_704_453,
+				})
+			);
+			assert_eq!(Tokens::free_balance(DOT, &BOB), 999_996_258_885_147_775);
+			assert_eq!(Tokens::free_balance(MB, &BOB), 1_000_000_000_000_000_000);
+			assert_eq!(Tokens::free_balance(DOT, &LOANS), 999_992_517_770_295_547);
+		});
+}
+
+#[test]
+fn reduce_and_close_position_work() {
+	ExtBuilder::default()
+		.initialize_enabled_trading_pairs()
+		.build()
+		.execute_with(|| {
+			System::set_block_number(1);
+
+			assert_ok!(DexModule::add_liquidity(
+				Origin::signed(ALICE),
+				MB,
+				DOT,
+				500_000_000_000_000,
+				100_000_000_000_000,
+				false,
+			));
+			assert_noop!(
+				DexModule::close_position(Origin::signed(BOB), MB, DOT, 0, Ratio::one()),
+				Error::<Runtime>::PositionNotFound
+			);
+
+			assert_ok!(DexModule::swap_with_exact_supply_by_leverage(
+				Origin::signed(BOB),
+				DOT,
+				MB,
+				10_000_000_000_000,
+				Ratio::saturating_from_rational(2, 1),
+				0,
+				Ratio::one(),
+			));
+			assert_eq!(DexModule::get_position_pnl(&BOB, MB_DOT_PAIR), Some(-332_770_355_011));
+
+			assert_noop!(
+				DexModule::reduce_position(Origin::signed(BOB), MB, DOT, 82_637_729_549_249, 0, Ratio::one()),
+				Error::<Runtime>::InvalidCollateralAmount
+			);
+
+			// the half left open would be unsafe
+			MockPriceSource::set_price(DOT, Some(Price::one()));
+			MockPriceSource::set_price(MB, Some(Price::saturating_from_rational(13, 100)));
+			assert_noop!(
+				DexModule::reduce_position(Origin::signed(BOB), MB, DOT, 41_318_864_774_624, 0, Ratio::one()),
+				Error::<Runtime>::UnsafePosition
+			);
+			MockPriceSource::set_price(MB, Some(Price::saturating_from_rational(20, 100)));
+
+			assert_noop!(
+				DexModule::reduce_position(
+					Origin::signed(BOB),
+					MB,
+					DOT,
+					41_318_864_774_624,
+					10_711_377_856_304,
+					Ratio::one()
+				),
+				Error::<Runtime>::InsufficientTargetAmount
+			);
+			assert_ok!(DexModule::reduce_position(
+				Origin::signed(BOB),
+				MB,
+				DOT,
+				41_318_864_774_624,
+				10_711_377_856_303,
+				Ratio::one()
+			));
+			let position_adjusted_event = Event::dex(crate::Event::PositionAdjusted(
+				BOB,
+				MB_DOT_PAIR,
+				5_000_000_000_000,
+				41_318_864_774_624,
+				5_000_000_000_000,
+			));
+			assert!(System::events().iter().any(|record| record.event == position_adjusted_event));
+			assert_eq!(Tokens::free_balance(DOT, &BOB), 999_995_711_377_856_303);
+			assert_eq!(Tokens::free_balance(DOT, &LOANS), 999_995_000_000_000_000);
+
+			assert_ok!(DexModule::close_position(Origin::signed(BOB), DOT, MB, 8_948_444_755_101, Ratio::one()));
+			let position_closed_event = Event::dex(crate::Event::PositionClosed(
+				BOB,
+				MB_DOT_PAIR,
+				41_318_864_774_624,
+				5_000_000_000_000,
+			));
+			assert!(System::events().iter().any(|record| record.event == position_closed_event));
+			assert_eq!(DexModule::leverage_pool(MB_DOT_PAIR, BOB), None);
+			assert_eq!(DexModule::get_position_pnl(&BOB, MB_DOT_PAIR), None);
+			assert_eq!(
+				DexModule::get_liquidity(MB, DOT),
+				(500_000_000_000_000, 100_340_177_388_596)
+			);
+			assert_eq!(Tokens::free_balance(DOT, &BOB), 999_999_659_822_611_404);
+			assert_eq!(Tokens::free_balance(DOT, &LOANS), 1_000_000_000_000_000_000);
+			assert_eq!(Tokens::free_balance(MB, &DexModule::account_id()), 500_000_000_000_000);
+		});
+}
+
+#[test]
+fn add_margin_work() {
+	ExtBuilder::default()
+		.initialize_enabled_trading_pairs()
+		.build()
+		.execute_with(|| {
+			System::set_block_number(1);
+
+			assert_ok!(DexModule::add_liquidity(
+				Origin::signed(ALICE),
+				MB,
+				DOT,
+				500_000_000_000_000,
+				100_000_000_000_000,
+				false,
+			));
+			assert_noop!(
+				DexModule::add_margin(Origin::signed(BOB), MB, DOT, 1_000_000_000_000),
+				Error::<Runtime>::PositionNotFound
+			);
+
+			assert_ok!(DexModule::swap_with_exact_supply_by_leverage(
+				Origin::signed(BOB),
+				DOT,
+				MB,
+				10_000_000_000_000,
+				Ratio::saturating_from_rational(2, 1),
+				0,
+				Ratio::one(),
+			));
+			assert_noop!(
+				DexModule::add_margin(Origin::signed(BOB), MB, DOT, 10_000_000_000_001),
+				Error::<Runtime>::ExcessiveMargin
+			);
+
+			assert_ok!(DexModule::add_margin(Origin::signed(BOB), MB, DOT, 1_000_000_000_000));
+			assert_eq!(
+				DexModule::leverage_pool(MB_DOT_PAIR, BOB),
+				Some(LeveragePosition {
+					debit_currency_id: DOT,
+					margin: 11_000_000_000_000,
+					collateral: 82_637_729_549_248,
+					debit: 9_000_000_000_000,
+				})
+			);
+			let position_adjusted_event = Event::dex(crate::Event::PositionAdjusted(
+				BOB,
+				MB_DOT_PAIR,
+				11_000_000_000_000,
+				82_637_729_549_248,
+				9_000_000_000_000,
+			));
+			assert!(System::events().iter().any(|record| record.event == position_adjusted_event));
+			assert_eq!(Tokens::free_balance(DOT, &BOB), 999_989_000_000_000_000);
+			assert_eq!(Tokens::free_balance(DOT, &LOANS), 999_991_000_000_000_000);
+		});
+}
+
+#[test]
+fn open_unsafe_position_fails() {
+	ExtBuilder::default()
+		.initialize_enabled_trading_pairs()
+		.build()
+		.execute_with(|| {
+			System::set_block_number(1);
+
+			assert_ok!(DexModule::add_liquidity(
+				Origin::signed(ALICE),
+				MB,
+				DOT,
+				500_000_000_000_000,
+				100_000_000_000_000,
+				false,
+			));
+			MockPriceSource::set_price(DOT, Some(Price::one()));
+
+			// 82_637_729_549_248 MB are worth less than 110% of the 10_000_000_000_000 DOT borrowed
+			MockPriceSource::set_price(MB, Some(Price::saturating_from_rational(13, 100)));
+			assert_noop!(
+				DexModule::swap_with_exact_supply_by_leverage(
+					Origin::signed(BOB),
+					DOT,
+					MB,
+					10_000_000_000_000,
+					Ratio::saturating_from_rational(2, 1),
+					0,
+					Ratio::one(),
+				),
+				Error::<Runtime>::UnsafePosition
+			);
+
+			MockPriceSource::set_price(MB, Some(Price::saturating_from_rational(20, 100)));
+			assert_ok!(DexModule::swap_with_exact_supply_by_leverage(
+				Origin::signed(BOB),
+				DOT,
+				MB,
+				10_000_000_000_000,
+				Ratio::saturating_from_rational(2, 1),
+				0,
+				Ratio::one(),
+			));
+
+			// adding to the position is checked as well
+			MockPriceSource::set_price(MB, Some(Price::saturating_from_rational(13, 100)));
+			assert_noop!(
+				DexModule::swap_with_exact_supply_by_leverage(
+					Origin::signed(BOB),
+					DOT,
+					MB,
+					1_000_000_000_000,
+					Ratio::saturating_from_rational(2, 1),
+					0,
+					Ratio::one(),
+				),
+				Error::<Runtime>::UnsafePosition
+			);
+		});
+}
+
+#[test]
+fn liquidate_position_work() {
+	ExtBuilder::default()
+		.initialize_enabled_trading_pairs()
+		.build()
+		.execute_with(|| {
+			System::set_block_number(1);
+
+			assert_ok!(DexModule::add_liquidity(
+				Origin::signed(ALICE),
+				MB,
+				DOT,
+				500_000_000_000_000,
+				100_000_000_000_000,
+				false,
+			));
+			assert_noop!(
+				DexModule::liquidate_position(Origin::signed(ALICE), BOB, MB, DOT),
+				Error::<Runtime>::PositionNotFound
+			);
+			assert_ok!(DexModule::swap_with_exact_supply_by_leverage(
+				Origin::signed(BOB),
+				DOT,
+				MB,
+				10_000_000_000_000,
+				Ratio::saturating_from_rational(2, 1),
+				0,
+				Ratio::one(),
+			));
+
+			// no prices, the position can't be judged
+			assert!(!DexModule::is_position_unsafe(MB_DOT_PAIR, &BOB));
+			assert_noop!(
+				DexModule::liquidate_position(Origin::signed(ALICE), BOB, MB, DOT),
+				Error::<Runtime>::MustBeUnsafe
+			);
+
+			MockPriceSource::set_price(DOT, Some(Price::one()));
+			MockPriceSource::set_price(MB, Some(Price::saturating_from_rational(20, 100)));
+			assert!(!DexModule::is_position_unsafe(MB_DOT_PAIR, &BOB));
+			assert_noop!(
+				DexModule::liquidate_position(Origin::signed(ALICE), BOB, MB, DOT),
+				Error::<Runtime>::MustBeUnsafe
+			);
+
+			// collateral worth 10_742_904_841_402 against 10_000_000_000_000 debit at 110%
+			MockPriceSource::set_price(MB, Some(Price::saturating_from_rational(13, 100)));
+			assert!(DexModule::is_position_unsafe(MB_DOT_PAIR, &BOB));
+			assert_ok!(DexModule::liquidate_position(Origin::signed(ALICE), BOB, MB, DOT));
+			let position_liquidated_event = Event::dex(crate::Event::PositionLiquidated(
+				ALICE,
+				BOB,
+				MB_DOT_PAIR,
+				82_637_729_549_248,
+				10_000_000_000_000,
+				0,
+				500_000_000_000,
+			));
+			assert!(System::events().iter().any(|record| record.event == position_liquidated_event));
+			assert_eq!(DexModule::leverage_pool(MB_DOT_PAIR, BOB), None);
+			assert_eq!(
+				DexModule::get_liquidity(MB, DOT),
+				(500_000_000_000_000, 100_332_770_355_011)
+			);
+			assert_eq!(Tokens::free_balance(DOT, &ALICE), 999_900_500_000_000_000);
+			assert_eq!(Tokens::free_balance(DOT, &BOB), 999_999_167_229_644_989);
+			assert_eq!(Tokens::free_balance(DOT, &LOANS), 1_000_000_000_000_000_000);
+			assert_eq!(Tokens::free_balance(MB, &DexModule::account_id()), 500_000_000_000_000);
+		});
+}
+
+/// DOT asset pool with 100_000_000_000_000 deposited by ALICE, MB-DOT liquidity at 0.2 DOT per MB.
+fn asset_pool_ext() -> sp_io::TestExternalities {
+	let mut ext = ExtBuilder::default()
+		.initialize_enabled_trading_pairs()
+		.with_asset_pool()
+		.build();
+	ext.execute_with(|| {
+		System::set_block_number(1);
+		MockPriceSource::set_price(DOT, Some(Price::one()));
+		MockPriceSource::set_price(MB, Some(Price::saturating_from_rational(20, 100)));
+		MockPriceSource::set_price(ETH, Some(Price::one()));
+
+		assert_ok!(LoansModule::update_deposit(&ALICE, DOT, 100_000_000_000_000));
+		assert_ok!(DexModule::add_liquidity(
+			Origin::signed(ALICE),
+			MB,
+			DOT,
+			500_000_000_000_000,
+			100_000_000_000_000,
+			false,
+		));
+	});
+	ext
+}
+
+#[test]
+fn leveraged_debit_stays_out_of_the_loans_work() {
+	asset_pool_ext().execute_with(|| {
+		// the trader has no deposits at all
+		assert_ok!(DexModule::swap_with_exact_supply_by_leverage(
+			Origin::signed(BOB),
+			DOT,
+			MB,
+			10_000_000_000_000,
+			Ratio::saturating_from_rational(2, 1),
+			0,
+			Ratio::one(),
+		));
+		assert_eq!(LoansModule::leverage_debits(DOT, &BOB), 10_000_000_000_000);
+		assert_eq!(LoansModule::loans(DOT, &BOB), LoanInfo { deposit: 0, debit: 0 });
+		assert_eq!(LoansModule::asset_pool_infos(DOT).total_debit, 10_000_000_000_000);
+		assert!(!LoansModule::is_debit_unsafe(&BOB));
+		assert!(!LoansModule::is_liquidatable(DOT, &BOB));
+
+		assert_ok!(DexModule::close_position(Origin::signed(BOB), MB, DOT, 0, Ratio::one()));
+		assert_eq!(DexModule::leverage_pool(MB_DOT_PAIR, BOB), None);
+		assert_eq!(LoansModule::leverage_debits(DOT, &BOB), 0);
+		assert_eq!(LoansModule::asset_pool_infos(DOT).total_debit, 0);
+		assert_eq!(Tokens::free_balance(DOT, &LoansModule::account_id()), 100_000_000_000_000);
+	});
+}
+
+#[test]
+fn liquidate_position_keeps_other_deposits_work() {
+	asset_pool_ext().execute_with(|| {
+		assert_ok!(LoansModule::update_deposit(&BOB, ETH, 1_000_000_000_000));
+		assert_ok!(LoansModule::set_collateral(&BOB, ETH, true));
+		assert_ok!(DexModule::swap_with_exact_supply_by_leverage(
+			Origin::signed(BOB),
+			DOT,
+			MB,
+			10_000_000_000_000,
+			Ratio::saturating_from_rational(2, 1),
+			0,
+			Ratio::one(),
+		));
+		assert_noop!(
+			DexModule::liquidate_position(Origin::signed(CHARLIE), BOB, MB, DOT),
+			Error::<Runtime>::MustBeUnsafe
+		);
+
+		MockPriceSource::set_price(MB, Some(Price::saturating_from_rational(13, 100)));
+		assert_ok!(DexModule::liquidate_position(Origin::signed(CHARLIE), BOB, MB, DOT));
+		assert_eq!(DexModule::leverage_pool(MB_DOT_PAIR, BOB), None);
+		assert_eq!(LoansModule::leverage_debits(DOT, &BOB), 0);
+		assert_eq!(LoansModule::asset_pool_infos(DOT).total_debit, 0);
+		assert_eq!(Tokens::free_balance(DOT, &LoansModule::account_id()), 100_000_000_000_000);
+		assert_eq!(Tokens::free_balance(DOT, &CHARLIE), 500_000_000_000);
+
+		// the deposits of the trader are untouched
+		assert_eq!(LoansModule::loans(ETH, &BOB), LoanInfo { deposit: 1_000_000_000_000, debit: 0 });
+	});
+}
+
+#[test]
+fn liquidate_underwater_position_work() {
+	asset_pool_ext().execute_with(|| {
+		assert_ok!(DexModule::swap_with_exact_supply_by_leverage(
+			Origin::signed(BOB),
+			DOT,
+			MB,
+			10_000_000_000_000,
+			Ratio::saturating_from_rational(2, 1),
+			0,
+			Ratio::one(),
+		));
+		assert_eq!(DexModule::leverage_pool(MB_DOT_PAIR, BOB).unwrap().collateral, 82_637_729_549_248);
+
+		// MB crashes on the dex far below the price source,
+		// the collateral is worth 8_263_772_954_924 against 10_000_000_000_000 debit
+		assert_ok!(DexModule::do_swap_with_exact_supply(&ALICE, &[MB, DOT], 2_000_000_000_000_000, 0, None));
+		MockPriceSource::set_price(MB, Some(Price::saturating_from_rational(10, 100)));
+		assert!(DexModule::is_position_unsafe(MB_DOT_PAIR, &BOB));
+
+		// the sale repays what it can and the asset pool writes off the rest
+		assert_ok!(DexModule::liquidate_position(Origin::signed(CHARLIE), BOB, MB, DOT));
+		let position_liquidated_event = Event::dex(crate::Event::PositionLiquidated(
+			CHARLIE,
+			BOB,
+			MB_DOT_PAIR,
+			82_637_729_549_248,
+			683_876_859_765,
+			9_316_123_140_235,
+			0,
+		));
+		assert!(System::events().iter().any(|record| record.event == position_liquidated_event));
+		assert_eq!(DexModule::leverage_pool(MB_DOT_PAIR, BOB), None);
+		assert_eq!(LoansModule::leverage_debits(DOT, &BOB), 0);
+		assert_eq!(LoansModule::asset_pool_infos(DOT).total_debit, 0);
+		assert_eq!(LoansModule::asset_pool_infos(DOT).total_deposit, 90_683_876_859_765);
+		assert_eq!(Tokens::free_balance(DOT, &LoansModule::account_id()), 90_683_876_859_765);
+		assert_eq!(Tokens::free_balance(DOT, &CHARLIE), 0);
+	});
+}
+
+#[test]
+fn set_operation_paused_work() {
+	ExtBuilder::default()
+		.initialize_enabled_trading_pairs()
+		.build()
+		.execute_with(|| {
+			System::set_block_number(1);
+
+			assert_noop!(
+				DexModule::set_operation_paused(Origin::signed(ALICE), MB, DOT, DexOperation::Swap, true),
+				BadOrigin
+			);
+			assert_ok!(DexModule::add_liquidity(
+				Origin::signed(ALICE),
+				MB,
+				DOT,
+				500_000_000_000_000,
+				100_000_000_000_000,
+				false,
+			));
+			assert_ok!(DexModule::add_liquidity(
+				Origin::signed(ALICE),
+				MB,
+				ETH,
+				100_000_000_000_000,
+				10_000_000_000,
+				false,
+			));
+
+			assert_ok!(DexModule::set_operation_paused(
+				Origin::signed(WhiteListOrigin::get()),
+				DOT,
+				MB,
+				DexOperation::Swap,
+				true
+			));
+			let paused_event = Event::dex(crate::Event::OperationPausedUpdated(MB_DOT_PAIR, DexOperation::Swap, true));
+			assert!(System::events().iter().any(|record| record.event == paused_event));
+			assert!(DexModule::paused_operations(MB_DOT_PAIR, DexOperation::Swap));
+
+			assert_noop!(
+				DexModule::do_swap_with_exact_supply(&BOB, &[DOT, MB], 100_000_000, 0, None),
+				Error::<Runtime>::OperationPaused
+			);
+			// any paused pair along the path stops the swap
+			assert_noop!(
+				DexModule::do_swap_with_exact_target(&BOB, &[ETH, MB, DOT], 100_000_000, 1_000_000_000_000, None),
+				Error::<Runtime>::OperationPaused
+			);
+			assert_ok!(DexModule::do_swap_with_exact_supply(&BOB, &[MB, ETH], 100_000_000, 0, None));
+			// liquidity is paused separately
+			assert_ok!(DexModule::add_liquidity(Origin::signed(BOB), MB, DOT, 5_000_000, 1_000_000, false));
+
+			assert_ok!(DexModule::set_operation_paused(
+				Origin::signed(WhiteListOrigin::get()),
+				MB,
+				DOT,
+				DexOperation::AddLiquidity,
+				true
+			));
+			assert_noop!(
+				DexModule::add_liquidity(Origin::signed(BOB), MB, DOT, 5_000_000, 1_000_000, false),
+				Error::<Runtime>::OperationPaused
+			);
+			assert_noop!(
+				DexModule::do_add_liquidity(&BOB, MB, DOT, 5_000_000, 1_000_000, false),
+				Error::<Runtime>::OperationPaused
+			);
+			assert_ok!(DexModule::set_operation_paused(
+				Origin::signed(WhiteListOrigin::get()),
+				MB,
+				DOT,
+				DexOperation::RemoveLiquidity,
+				true
+			));
+			assert_noop!(
+				DexModule::remove_liquidity(Origin::signed(ALICE), MB, DOT, 100_000_000, false),
+				Error::<Runtime>::OperationPaused
+			);
+			assert_noop!(
+				DexModule::do_remove_liquidity(&ALICE, MB, DOT, 100_000_000, false),
+				Error::<Runtime>::OperationPaused
+			);
+
+			assert_ok!(DexModule::set_operation_paused(
+				Origin::signed(WhiteListOrigin::get()),
+				MB,
+				DOT,
+				DexOperation::Swap,
+				false
+			));
+			assert!(!DexModule::paused_operations(MB_DOT_PAIR, DexOperation::Swap));
+			assert_ok!(DexModule::do_swap_with_exact_supply(&BOB, &[DOT, MB], 100_000_000, 0, None));
+		});
+}
+
+#[test]
+fn best_route_work() {
+	ExtBuilder::default()
+		.initialize_enabled_trading_pairs()
+		.initialize_added_liquidity_pools(ALICE)
+		.build()
+		.execute_with(|| {
+			System::set_block_number(1);
+
+			// MB is cheap against DOT, going through DOT beats the direct pair
+			assert_eq!(DexModule::get_best_target_route(MB, ETH, 1_000), Some((vec![MB, DOT, ETH], 3_908)));
+			assert_eq!(DexModule::get_best_supply_route(MB, ETH, 1_000), Some((vec![MB, DOT, ETH], 256)));
+			assert_eq!(DexModule::get_best_target_route(FIL, ETH, 1_000), None);
+			assert_eq!(DexModule::get_best_target_route(MB, MB, 1_000), None);
+
+			assert_noop!(
+				DexModule::swap_with_best_route(Origin::signed(BOB), FIL, ETH, 1_000, 0, Ratio::one()),
+				Error::<Runtime>::NoTradingRoute
+			);
+			assert_noop!(
+				DexModule::swap_with_best_route(Origin::signed(BOB), MB, ETH, 1_000, 3_909, Ratio::one()),
+				Error::<Runtime>::InsufficientTargetAmount
+			);
+			assert_ok!(DexModule::swap_with_best_route(Origin::signed(BOB), MB, ETH, 1_000, 3_908, Ratio::one()));
+			let swap_event = Event::dex(crate::Event::Swap(BOB, vec![MB, DOT, ETH], 1_000, 3_908));
+			assert!(System::events().iter().any(|record| record.event == swap_event));
+			assert_eq!(DexModule::get_liquidity(MB, ETH), (1_000_000, 2_000_000));
+
+			// paused pairs are routed around
+			assert_ok!(DexModule::set_operation_paused(
+				Origin::signed(WhiteListOrigin::get()),
+				DOT,
+				ETH,
+				DexOperation::Swap,
+				true
+			));
+			assert_eq!(DexModule::get_best_target_route(MB, ETH, 1_000), Some((vec![MB, ETH], 1_978)));
+		});
+}
+
+#[test]
+fn route_search_is_bounded_work() {
+	ExtBuilder::default().build().execute_with(|| {
+		let whitelist = Origin::signed(WhiteListOrigin::get());
+		for (currency_id_a, currency_id_b) in [(FIL, MB), (FIL, ETH), (FIL, DOT), (MB, DOT), (MB, ETH), (DOT, ETH)].iter() {
+			assert_ok!(DexModule::enable_trading_pair(whitelist.clone(), *currency_id_a, *currency_id_b));
+		}
+		assert_eq!(DexModule::enabled_trading_pairs().len(), MaxEnabledTradingPairs::get() as usize);
+		assert_noop!(
+			DexModule::enable_trading_pair(whitelist.clone(), CurrencyId::Token(3), MB),
+			Error::<Runtime>::TooManyEnabledTradingPairs
+		);
+		assert_ok!(DexModule::add_liquidity(
+			Origin::signed(ALICE),
+			DOT,
+			ETH,
+			1_000_000,
+			2_000_000,
+			false,
+		));
+
+		// the direct pair is searched though it was enabled last
+		let trading_pairs = DexModule::route_search_pairs(DOT, ETH);
+		assert_eq!(trading_pairs.len(), RouteSearchLimit::get() as usize);
+		assert_eq!(trading_pairs[0], DOT_ETH_PAIR);
+		assert!(DexModule::get_candidate_paths(DOT, ETH).len() <= RouteSearchLimit::get() as usize);
+		assert_eq!(DexModule::get_best_target_route(DOT, ETH, 1_000).map(|(path, _)| path), Some(vec![DOT, ETH]));
+
+		assert_ok!(DexModule::disable_trading_pair(whitelist.clone(), DOT, ETH));
+		assert!(!DexModule::enabled_trading_pairs().contains(&DOT_ETH_PAIR));
+		assert!(!DexModule::route_search_pairs(DOT, ETH).contains(&DOT_ETH_PAIR));
+		assert_ok!(DexModule::enable_trading_pair(whitelist, CurrencyId::Token(3), MB));
+	});
+}
+
+#[test]
+fn swap_with_split_routes_work() {
+	ExtBuilder::default()
+		.initialize_enabled_trading_pairs()
+		.initialize_added_liquidity_pools(ALICE)
+		.build()
+		.execute_with(|| {
+			System::set_block_number(1);
+
+			assert_noop!(
+				DexModule::swap_with_split_routes(Origin::signed(BOB), vec![], 0, Ratio::one()),
+				Error::<Runtime>::InvalidSplitRoutes
+			);
+			assert_noop!(
+				DexModule::swap_with_split_routes(Origin::signed(BOB), vec![(vec![MB, ETH], 1_000); 5], 0, Ratio::one()),
+				Error::<Runtime>::InvalidSplitRoutes
+			);
+			// every route must go from the same supply to the same target currency
+			assert_noop!(
+				DexModule::swap_with_split_routes(
+					Origin::signed(BOB),
+					vec![(vec![MB, ETH], 1_000), (vec![MB, DOT], 1_000)],
+					0,
+					Ratio::one()
+				),
+				Error::<Runtime>::InvalidSplitRoutes
+			);
+			assert_noop!(
+				DexModule::swap_with_split_routes(
+					Origin::signed(BOB),
+					vec![(vec![MB, ETH], 1_000), (vec![MB, DOT, ETH], 0)],
+					0,
+					Ratio::one()
+				),
+				Error::<Runtime>::InvalidSplitRoutes
+			);
+			// the slippage limit applies to the sum of all routes
+			assert_noop!(
+				DexModule::swap_with_split_routes(
+					Origin::signed(BOB),
+					vec![(vec![MB, ETH], 1_000), (vec![MB, DOT, ETH], 1_000)],
+					5_887,
+					Ratio::one()
+				),
+				Error::<Runtime>::InsufficientTargetAmount
+			);
+
+			let bob_mb = Tokens::free_balance(MB, &BOB);
+			let bob_eth = Tokens::free_balance(ETH, &BOB);
+			assert_ok!(DexModule::swap_with_split_routes(
+				Origin::signed(BOB),
+				vec![(vec![MB, ETH], 1_000), (vec![MB, DOT, ETH], 1_000)],
+				5_886,
+				Ratio::one()
+			));
+			let split_swap_event = Event::dex(crate::Event::SplitSwap(
+				BOB,
+				vec![(vec![MB, ETH], 1_000, 1_978), (vec![MB, DOT, ETH], 1_000, 3_908)],
+				2_000,
+				5_886,
+			));
+			assert!(System::events().iter().any(|record| record.event == split_swap_event));
+			assert!(!System::events().iter().any(|record| matches!(record.event, Event::dex(crate::Event::Swap(..)))));
+			assert_eq!(Tokens::free_balance(MB, &BOB), bob_mb - 2_000);
+			assert_eq!(Tokens::free_balance(ETH, &BOB), bob_eth + 5_886);
+			assert_eq!(DexModule::get_liquidity(MB, ETH), (1_001_000, 2_000_000 - 1_978));
+		});
+}
+
+#[test]
+fn twap_work() {
+	ExtBuilder::default()
+		.initialize_enabled_trading_pairs()
+		.build()
+		.execute_with(|| {
+			System::set_block_number(1);
+			Timestamp::set_timestamp(1_000_000);
+
+			assert_ok!(DexModule::add_liquidity(Origin::signed(ALICE), MB, DOT, 1_000_000, 2_000_000, false));
+			let initial = CumulativePrice { price_0_cumulative: 0, price_1_cumulative: 0, timestamp: 1_000 };
+			assert_eq!(DexModule::price_cumulatives(MB_DOT_PAIR), Some(initial));
+			assert_eq!(DexModule::latest_observations(MB_DOT_PAIR), vec![initial]);
+			assert_eq!(DexModule::get_twap(MB_DOT_PAIR, 60), None);
+
+			Timestamp::set_timestamp(1_100_000);
+			assert_eq!(
+				DexModule::get_twap(MB_DOT_PAIR, 100),
+				Some((Price::saturating_from_integer(2), Price::saturating_from_rational(1, 2)))
+			);
+			assert_eq!(<DexModule as TwapProvider>::get_twap(MB, DOT, 100), Some(Price::saturating_from_integer(2)));
+			assert_eq!(<DexModule as TwapProvider>::get_twap(DOT, MB, 100), Some(Price::saturating_from_rational(1, 2)));
+			assert_eq!(<DexModule as TwapProvider>::get_twap(DOT, ETH, 100), None);
+
+			// moving the spot price does not move the average within the same block
+			assert_ok!(DexModule::do_swap_with_exact_supply(&BOB, &[MB, DOT], 1_000_000, 0, None));
+			assert_eq!(DexModule::get_liquidity(MB, DOT), (2_000_000, 1_005_026));
+			assert_eq!(DexModule::latest_observations(MB_DOT_PAIR).len(), 2);
+			assert_eq!(
+				DexModule::get_twap(MB_DOT_PAIR, 100),
+				Some((Price::saturating_from_integer(2), Price::saturating_from_rational(1, 2)))
+			);
+
+			// 100 seconds at 2 and 100 seconds at 0.502513
+			Timestamp::set_timestamp(1_200_000);
+			assert_eq!(
+				DexModule::get_twap(MB_DOT_PAIR, 200).map(|(price_0, _)| price_0),
+				Some(Price::saturating_from_rational(12_512_565, 10_000_000))
+			);
+			assert_eq!(DexModule::get_twap(MB_DOT_PAIR, 500), None);
+
+			// only the latest observations are kept, the newest overwrites the oldest slot
+			assert_ok!(DexModule::do_swap_with_exact_supply(&BOB, &[MB, DOT], 1_000, 0, None));
+			Timestamp::set_timestamp(1_300_000);
+			assert_ok!(DexModule::do_swap_with_exact_supply(&BOB, &[MB, DOT], 1_000, 0, None));
+			let timestamps: Vec<u64> = DexModule::latest_observations(MB_DOT_PAIR).iter().map(|o| o.timestamp).collect();
+			assert_eq!(timestamps, vec![1_300, 1_200, 1_100]);
+			assert_eq!(DexModule::latest_observation_index(MB_DOT_PAIR), 0);
+			assert_eq!(DexModule::price_observations(MB_DOT_PAIR, 0).map(|o| o.timestamp), Some(1_300));
+			assert_eq!(DexModule::get_twap(MB_DOT_PAIR, 300), None);
+			assert!(DexModule::get_twap(MB_DOT_PAIR, 200).is_some());
+		});
+}
+
+#[test]
+fn twap_accumulators_wrap_work() {
+	ExtBuilder::default()
+		.initialize_enabled_trading_pairs()
+		.build()
+		.execute_with(|| {
+			System::set_block_number(1);
+			Timestamp::set_timestamp(1_000_000);
+			assert_ok!(DexModule::add_liquidity(Origin::signed(ALICE), MB, DOT, 1_000_000, 2_000_000, false));
+
+			// accumulators about to overflow
+			let near_max = CumulativePrice { price_0_cumulative: u128::MAX, price_1_cumulative: u128::MAX - 1, timestamp: 1_000 };
+			PriceCumulatives::<Runtime>::insert(MB_DOT_PAIR, near_max);
+			PriceObservations::<Runtime>::insert(MB_DOT_PAIR, 0, near_max);
+
+			Timestamp::set_timestamp(1_100_000);
+			assert_ok!(DexModule::do_swap_with_exact_supply(&BOB, &[MB, DOT], 1_000, 0, None));
+			assert!(DexModule::price_cumulatives(MB_DOT_PAIR).unwrap().price_0_cumulative < near_max.price_0_cumulative);
+			assert_eq!(
+				DexModule::get_twap(MB_DOT_PAIR, 100),
+				Some((Price::saturating_from_integer(2), Price::saturating_from_rational(1, 2)))
+			);
+		});
+}
+
+#[test]
+fn migrate_to_v2_work() {
+	ExtBuilder::default().build().execute_with(|| {
+		StorageVersion::<Runtime>::kill();
+		frame_support::storage::unhashed::put(
+			&LeveragePool::<Runtime>::hashed_key_for(MB_DOT_PAIR, ALICE),
+			&(100u128, 200u128),
+		);
+
+		assert!(migrations::migrate_to_v2::<Runtime>() > 0);
+		assert_eq!(DexModule::storage_version(), Releases::V2_0_0);
+		assert_eq!(LeveragePool::<Runtime>::iter().count(), 0);
+
+		// runs once
+		assert_eq!(migrations::migrate_to_v2::<Runtime>(), 0);
+	});
+}
+
+#[test]
+fn migrate_to_v3_work() {
+	ExtBuilder::default()
+		.initialize_enabled_trading_pairs()
+		.build()
+		.execute_with(|| {
+			assert_ok!(DexModule::disable_trading_pair(Origin::signed(WhiteListOrigin::get()), MB, ETH));
+			StorageVersion::<Runtime>::put(Releases::V2_0_0);
+			EnabledTradingPairs::<Runtime>::kill();
+
+			assert!(migrations::migrate_to_v3::<Runtime>() > 0);
+			assert_eq!(DexModule::storage_version(), Releases::V3_0_0);
+			let mut enabled_trading_pairs = DexModule::enabled_trading_pairs();
+			enabled_trading_pairs.sort();
+			assert_eq!(enabled_trading_pairs, vec![MB_DOT_PAIR, DOT_ETH_PAIR]);
+
+			// runs once
+			assert_eq!(migrations::migrate_to_v3::<Runtime>(), 0);
+		});
+}
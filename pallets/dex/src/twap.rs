@@ -0,0 +1,116 @@
+//! Time-weighted average prices: Uniswap v2 style price accumulators of the trading pairs.
+
+use super::*;
+
+#[derive(Encode, Decode, Clone, Copy, RuntimeDebug, PartialEq, Eq, Default)]
+pub struct CumulativePrice {
+	// wrapping sum over time of the inner value of the price of trading_pair.0 in trading_pair.1,
+	// times the seconds it held
+	pub price_0_cumulative: u128,
+
+	// wrapping sum over time of the inner value of the price of trading_pair.1 in trading_pair.0,
+	// times the seconds it held
+	pub price_1_cumulative: u128,
+
+	// unix time in seconds of the last accumulation
+	pub timestamp: u64,
+}
+
+impl<T: Config> Pallet<T> {
+	/// Accumulate the prices of the current reserves up to now, must run before every reserve change.
+	/// Snapshots the accumulator into the observations once every `ObservationPeriod`.
+	pub(crate) fn update_cumulative_price(trading_pair: TradingPair) {
+		let (pool_0, pool_1) = Self::liquidity_pool(trading_pair);
+		if pool_0.is_zero() || pool_1.is_zero() {
+			// an empty pool has no price, accumulate afresh from the liquidity about to be added
+			let cumulative = CumulativePrice {
+				timestamp: T::UnixTime::now().as_secs(),
+				..Default::default()
+			};
+			PriceCumulatives::<T>::insert(trading_pair, cumulative);
+			PriceObservations::<T>::remove_prefix(trading_pair);
+			PriceObservations::<T>::insert(trading_pair, 0, cumulative);
+			LatestObservationIndex::<T>::insert(trading_pair, 0);
+			return;
+		}
+
+		let cumulative = Self::current_cumulative_price(trading_pair);
+		PriceCumulatives::<T>::insert(trading_pair, cumulative);
+
+		// the observations are a ring buffer, the next one overwrites the oldest once it is full
+		let latest_index = Self::latest_observation_index(trading_pair);
+		let index = match Self::price_observations(trading_pair, latest_index) {
+			Some(latest) if cumulative.timestamp < latest.timestamp.saturating_add(T::ObservationPeriod::get()) => return,
+			Some(_) => latest_index.saturating_add(1) % T::ObservationLimit::get().max(1),
+			None => latest_index,
+		};
+		PriceObservations::<T>::insert(trading_pair, index, cumulative);
+		LatestObservationIndex::<T>::insert(trading_pair, index);
+	}
+
+	/// The accumulator brought up to now with the current reserves, without writing it.
+	pub fn current_cumulative_price(trading_pair: TradingPair) -> CumulativePrice {
+		let now = T::UnixTime::now().as_secs();
+		let (pool_0, pool_1) = Self::liquidity_pool(trading_pair);
+		let mut cumulative = match Self::price_cumulatives(trading_pair) {
+			Some(cumulative) => cumulative,
+			None => return CumulativePrice { timestamp: now, ..Default::default() },
+		};
+
+		let elapsed = u128::from(now.saturating_sub(cumulative.timestamp));
+		let (price_0, price_1) = if pool_0.is_zero() || pool_1.is_zero() {
+			(Zero::zero(), Zero::zero())
+		} else {
+			(
+				Price::saturating_from_rational(pool_1, pool_0).into_inner(),
+				Price::saturating_from_rational(pool_0, pool_1).into_inner(),
+			)
+		};
+		cumulative.price_0_cumulative = cumulative.price_0_cumulative.wrapping_add(price_0.wrapping_mul(elapsed));
+		cumulative.price_1_cumulative = cumulative.price_1_cumulative.wrapping_add(price_1.wrapping_mul(elapsed));
+		cumulative.timestamp = cumulative.timestamp.max(now);
+		cumulative
+	}
+
+	/// The observations of a trading pair, latest first.
+	pub fn latest_observations(trading_pair: TradingPair) -> Vec<CumulativePrice> {
+		let limit = T::ObservationLimit::get().max(1);
+		let latest_index = Self::latest_observation_index(trading_pair);
+		let mut observations = vec![];
+		for offset in 0..limit {
+			let index = latest_index.saturating_add(limit).saturating_sub(offset) % limit;
+			match Self::price_observations(trading_pair, index) {
+				Some(observation) => observations.push(observation),
+				None => break,
+			}
+		}
+		observations
+	}
+
+	/// Average prices of (trading_pair.0 in trading_pair.1, trading_pair.1 in trading_pair.0) over at
+	/// least the last `window` seconds, measured from the latest observation old enough to cover it.
+	/// None if the trading pair has no observation that old or either average is zero.
+	pub fn get_twap(trading_pair: TradingPair, window: u64) -> Option<(Price, Price)> {
+		let current = Self::current_cumulative_price(trading_pair);
+		let start = current.timestamp.checked_sub(window)?;
+		let observation = Self::latest_observations(trading_pair)
+			.into_iter()
+			.find(|observation| observation.timestamp <= start)?;
+
+		let elapsed = u128::from(current.timestamp.saturating_sub(observation.timestamp));
+		if elapsed.is_zero() {
+			return None;
+		}
+		// the accumulators wrap around, their difference is exact as long as it fits in a u128
+		let price_0 = Price::from_inner(
+			current.price_0_cumulative.wrapping_sub(observation.price_0_cumulative) / elapsed
+		);
+		let price_1 = Price::from_inner(
+			current.price_1_cumulative.wrapping_sub(observation.price_1_cumulative) / elapsed
+		);
+		if price_0.is_zero() || price_1.is_zero() {
+			return None;
+		}
+		Some((price_0, price_1))
+	}
+}
@@ -14,18 +14,26 @@ pub trait WeightInfo {
 	fn remove_liquidity() -> Weight;
 	fn swap_with_exact_supply() -> Weight;
 	fn swap_with_exact_target() -> Weight;
+	fn swap_with_best_route(p: u32) -> Weight;
+	fn swap_with_split_routes(r: u32) -> Weight;
+	fn swap_with_exact_supply_by_leverage() -> Weight;
+	fn swap_with_exact_target_by_leverage() -> Weight;
+	fn reduce_position() -> Weight;
+	fn add_margin() -> Weight;
+	fn liquidate_position() -> Weight;
+	fn set_operation_paused() -> Weight;
 }
 
 impl WeightInfo for () {
 	fn enable_trading_pair() -> Weight {
 		(10_000_000 as Weight)
-			.saturating_add(RocksDbWeight::get().reads(1 as Weight))
-			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+			.saturating_add(RocksDbWeight::get().reads(2 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(2 as Weight))
 	}
 	fn disable_trading_pair() -> Weight {
 		(10_000_000 as Weight)
-			.saturating_add(RocksDbWeight::get().reads(1 as Weight))
-			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+			.saturating_add(RocksDbWeight::get().reads(2 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(2 as Weight))
 	}
 	fn new_trading_pair() -> Weight {
 		(10_000_000 as Weight)
@@ -54,4 +62,46 @@ impl WeightInfo for () {
 			.saturating_add(RocksDbWeight::get().reads(10 as Weight))
 			.saturating_add(RocksDbWeight::get().writes(9 as Weight))
 	}
+	fn swap_with_best_route(p: u32) -> Weight {
+		(150_000_000 as Weight)
+			.saturating_add((20_000_000 as Weight).saturating_mul(p as Weight))
+			.saturating_add(RocksDbWeight::get().reads(10 as Weight))
+			.saturating_add(RocksDbWeight::get().reads((4 as Weight).saturating_mul(p as Weight)))
+			.saturating_add(RocksDbWeight::get().writes(9 as Weight))
+	}
+	fn swap_with_split_routes(r: u32) -> Weight {
+		(20_000_000 as Weight)
+			.saturating_add((100_000_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads((8 as Weight).saturating_mul(r as Weight)))
+			.saturating_add(RocksDbWeight::get().writes((7 as Weight).saturating_mul(r as Weight)))
+	}
+	fn swap_with_exact_supply_by_leverage() -> Weight {
+		(200_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(14 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(12 as Weight))
+	}
+	fn swap_with_exact_target_by_leverage() -> Weight {
+		(200_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(14 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(12 as Weight))
+	}
+	fn reduce_position() -> Weight {
+		(200_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(14 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(12 as Weight))
+	}
+	fn add_margin() -> Weight {
+		(100_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(6 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(5 as Weight))
+	}
+	fn liquidate_position() -> Weight {
+		(250_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(16 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(14 as Weight))
+	}
+	fn set_operation_paused() -> Weight {
+		(10_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
 }
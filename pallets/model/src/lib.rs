@@ -6,6 +6,9 @@ use serde::{Deserialize, Serialize};
 use sp_runtime::{FixedU128, RuntimeDebug};
 use sp_std::{prelude::*};
 
+pub mod traits;
+pub use traits::*;
+
 pub type Price = FixedU128;
 pub type ExchangeRate = FixedU128;
 pub type Ratio = FixedU128;
@@ -17,6 +20,17 @@ pub type Amount = i128;
 pub type Moment = u64;
 pub type TimeStampedPrice = orml_oracle::TimestampedValue<Price, Moment>;
 
+/// Where a price handed out by a `PriceProvider` came from.
+#[derive(Encode, Decode, Eq, PartialEq, Copy, Clone, RuntimeDebug)]
+pub enum PriceOrigin {
+	Locked,
+
+	Oracle,
+
+	// the dex TWAP, valued in the quote currency
+	Dex,
+}
+
 #[derive(Encode, Decode, Eq, PartialEq, Copy, Clone, RuntimeDebug, PartialOrd, Ord)]
 pub enum DataProviderId {
 	Aggregated = 0,
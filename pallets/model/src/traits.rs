@@ -0,0 +1,102 @@
+use frame_support::{Parameter, weights::Weight};
+use sp_runtime::{DispatchError, DispatchResult, traits::CheckedDiv};
+use sp_std::result::Result;
+
+use crate::{Amount, Balance, CurrencyId, Price, PriceOrigin, Ratio};
+
+/// Lending side of the asset pools, used by pallets that borrow on behalf of traders.
+pub trait AssetPoolManager<AccountId> {
+	/// Borrow (positive adjustment) or repay (negative adjustment) `currency_id` for `who`.
+	fn update_debit(who: &AccountId, currency_id: CurrencyId, debit_adjustment: Amount) -> DispatchResult;
+
+	/// Drop `amount` of the debit of `who` that its collateral could not repay, the asset pool
+	/// bears the loss.
+	fn write_off_debit(who: &AccountId, currency_id: CurrencyId, amount: Balance) -> DispatchResult;
+
+	/// real debit: debit * debit_rate
+	fn debit_rate(currency_id: CurrencyId) -> Ratio;
+}
+
+/// Source of token prices for the lending pallets.
+pub trait PriceProvider {
+	fn get_price(currency_id: CurrencyId) -> Option<Price>;
+
+	/// `get_price` and where the price came from, for callers that report the dex stepping in.
+	fn get_price_with_origin(currency_id: CurrencyId) -> Option<(Price, PriceOrigin)> {
+		Self::get_price(currency_id).map(|price| (price, PriceOrigin::Oracle))
+	}
+
+	fn get_relative_price(base_currency_id: CurrencyId, quote_currency_id: CurrencyId) -> Option<Price> {
+		if let (Some(base_price), Some(quote_price)) =
+		(Self::get_price(base_currency_id), Self::get_price(quote_currency_id))
+		{
+			base_price.checked_div(&quote_price)
+		} else {
+			None
+		}
+	}
+}
+
+/// Swapping side of the dex, used by pallets that need to sell collateral.
+pub trait DEXManager<AccountId> {
+	fn get_swap_target_amount(path: &[CurrencyId], supply_amount: Balance, price_impact_limit: Option<Ratio>) -> Option<Balance>;
+
+	fn get_swap_supply_amount(path: &[CurrencyId], target_amount: Balance, price_impact_limit: Option<Ratio>) -> Option<Balance>;
+
+	fn swap_with_exact_supply(
+		who: &AccountId,
+		path: &[CurrencyId],
+		supply_amount: Balance,
+		min_target_amount: Balance,
+		price_impact_limit: Option<Ratio>,
+	) -> Result<Balance, DispatchError>;
+
+	fn swap_with_exact_target(
+		who: &AccountId,
+		path: &[CurrencyId],
+		target_amount: Balance,
+		max_supply_amount: Balance,
+		price_impact_limit: Option<Ratio>,
+	) -> Result<Balance, DispatchError>;
+}
+
+/// Manipulation resistant prices from the dex trading pairs.
+pub trait TwapProvider {
+	/// Time-weighted average price of `base_currency_id` in `quote_currency_id` over at least
+	/// the last `window` seconds.
+	fn get_twap(base_currency_id: CurrencyId, quote_currency_id: CurrencyId, window: u64) -> Option<Price>;
+}
+
+impl TwapProvider for () {
+	fn get_twap(_base_currency_id: CurrencyId, _quote_currency_id: CurrencyId, _window: u64) -> Option<Price> {
+		None
+	}
+}
+
+/// Code run by the borrower of a flash loan while it holds the borrowed funds.
+pub trait FlashLoanReceiver<AccountId> {
+	/// What the borrower asks to be done with the funds, e.g. a call to dispatch.
+	type Payload: Parameter;
+
+	/// `who` holds `amount` of `currency_id` and must own `amount + fee` once this returns.
+	fn on_flash_loan(
+		who: &AccountId,
+		currency_id: CurrencyId,
+		amount: Balance,
+		fee: Balance,
+		payload: Self::Payload,
+	) -> DispatchResult;
+
+	/// Weight of running `payload`, on top of the flash loan itself.
+	fn payload_weight(_payload: &Self::Payload) -> Weight {
+		0
+	}
+}
+
+impl<AccountId> FlashLoanReceiver<AccountId> for () {
+	type Payload = ();
+
+	fn on_flash_loan(_who: &AccountId, _currency_id: CurrencyId, _amount: Balance, _fee: Balance, _payload: ()) -> DispatchResult {
+		Ok(())
+	}
+}
@@ -4,8 +4,10 @@ pub mod weights;
 
 use frame_support::{pallet_prelude::*, transactional};
 use frame_system::pallet_prelude::*;
+use sp_runtime::traits::StaticLookup;
 
-use model::{Amount, CurrencyId};
+use asset_pool::{CreditLoanId, FlashLoanPayloadOf};
+use model::{Amount, Balance, CurrencyId, FlashLoanReceiver};
 pub use weights::WeightInfo;
 pub use module::*;
 
@@ -28,6 +30,31 @@ pub mod module {
 	pub enum Event<T: Config> {
 		AdjustDepositSuccess(T::AccountId, CurrencyId, Amount),
 		AdjustDebitSuccess(T::AccountId, CurrencyId, Amount),
+		SetCollateralSuccess(T::AccountId, CurrencyId, bool),
+		/// [who, currency_id, amount, fee]
+		FlashLoanSuccess(T::AccountId, CurrencyId, Balance, Balance),
+		/// [payer, who, currency_id, amount]
+		DepositForSuccess(T::AccountId, T::AccountId, CurrencyId, Balance),
+		/// [payer, who, currency_id, amount]
+		RepayOnBehalfSuccess(T::AccountId, T::AccountId, CurrencyId, Balance),
+		/// [borrower, loan_id]
+		RequestCreditLoanSuccess(T::AccountId, CreditLoanId),
+		/// [guarantor, loan_id, amount]
+		GuaranteeCreditLoanSuccess(T::AccountId, CreditLoanId, Balance),
+		/// [guarantor, loan_id, amount]
+		WithdrawGuaranteeSuccess(T::AccountId, CreditLoanId, Balance),
+		/// [borrower, loan_id, amount]
+		DrawCreditLoanSuccess(T::AccountId, CreditLoanId, Balance),
+		/// [payer, loan_id, amount]
+		RepayCreditLoanSuccess(T::AccountId, CreditLoanId, Balance),
+		/// [borrower, loan_id]
+		CloseCreditLoanSuccess(T::AccountId, CreditLoanId),
+		/// [caller, loan_id]
+		DefaultCreditLoanSuccess(T::AccountId, CreditLoanId),
+		/// [who, currency_id, amount, term]
+		BorrowFixedTermSuccess(T::AccountId, CurrencyId, Balance, u64),
+		/// [who, currency_id, amount]
+		RepayFixedTermSuccess(T::AccountId, CurrencyId, Balance),
 	}
 
 	#[pallet::pallet]
@@ -63,5 +90,205 @@ pub mod module {
 			Self::deposit_event(Event::AdjustDebitSuccess(who.clone(), currency_id, debit_adjustment_amount));
 			Ok(().into())
 		}
+
+		/// Withdraw the whole deposit in `currency_id`, accrued interest included.
+		#[pallet::weight((<T as Config>::WeightInfo::withdraw_all(), DispatchClass::Operational))]
+		#[transactional]
+		pub fn withdraw_all(origin: OriginFor<T>, currency_id: CurrencyId) -> DispatchResultWithPostInfo {
+			let who = ensure_signed(origin)?;
+			let amount = <asset_pool::Module<T>>::withdraw_all(&who, currency_id)?;
+			Self::deposit_event(Event::AdjustDepositSuccess(who, currency_id, -(amount as Amount)));
+			Ok(().into())
+		}
+
+		/// Repay the whole debit in `currency_id`, accrued interest included.
+		#[pallet::weight((<T as Config>::WeightInfo::repay_all(), DispatchClass::Operational))]
+		#[transactional]
+		pub fn repay_all(origin: OriginFor<T>, currency_id: CurrencyId) -> DispatchResultWithPostInfo {
+			let who = ensure_signed(origin)?;
+			let amount = <asset_pool::Module<T>>::repay_all(&who, currency_id)?;
+			Self::deposit_event(Event::AdjustDebitSuccess(who, currency_id, -(amount as Amount)));
+			Ok(().into())
+		}
+
+		/// Use the deposit in `currency_id` as collateral for borrowing, or stop using it.
+		#[pallet::weight((<T as Config>::WeightInfo::set_collateral(), DispatchClass::Operational))]
+		#[transactional]
+		pub fn set_collateral(
+			origin: OriginFor<T>,
+			currency_id: CurrencyId,
+			enabled: bool,
+		) -> DispatchResultWithPostInfo {
+			let who = ensure_signed(origin)?;
+			<asset_pool::Module<T>>::set_collateral(&who, currency_id, enabled)?;
+			Self::deposit_event(Event::SetCollateralSuccess(who, currency_id, enabled));
+			Ok(().into())
+		}
+
+		/// Borrow `amount` of `currency_id` while the flash loan receiver runs `payload`, repaid with
+		/// the flash loan fee. Normal class, the payload may be any call of the signer.
+		#[pallet::weight(<T as Config>::WeightInfo::flash_loan().saturating_add(
+			<T as asset_pool::Config>::FlashLoanReceiver::payload_weight(payload)
+		))]
+		#[transactional]
+		pub fn flash_loan(
+			origin: OriginFor<T>,
+			currency_id: CurrencyId,
+			amount: Balance,
+			payload: FlashLoanPayloadOf<T>,
+		) -> DispatchResultWithPostInfo {
+			let who = ensure_signed(origin)?;
+			let fee = <asset_pool::Module<T>>::flash_loan(&who, currency_id, amount, payload)?;
+			Self::deposit_event(Event::FlashLoanSuccess(who, currency_id, amount, fee));
+			Ok(().into())
+		}
+
+		/// Deposit `amount` of `currency_id` from the signer into the loan of `who`.
+		#[pallet::weight((<T as Config>::WeightInfo::deposit_for(), DispatchClass::Operational))]
+		#[transactional]
+		pub fn deposit_for(
+			origin: OriginFor<T>,
+			who: <T::Lookup as StaticLookup>::Source,
+			currency_id: CurrencyId,
+			amount: Balance,
+		) -> DispatchResultWithPostInfo {
+			let payer = ensure_signed(origin)?;
+			let who = T::Lookup::lookup(who)?;
+			<asset_pool::Module<T>>::deposit_for(&payer, &who, currency_id, amount)?;
+			Self::deposit_event(Event::DepositForSuccess(payer, who, currency_id, amount));
+			Ok(().into())
+		}
+
+		/// Repay `amount` of the `currency_id` debit of `who` with funds of the signer.
+		#[pallet::weight((<T as Config>::WeightInfo::repay_on_behalf(), DispatchClass::Operational))]
+		#[transactional]
+		pub fn repay_on_behalf(
+			origin: OriginFor<T>,
+			who: <T::Lookup as StaticLookup>::Source,
+			currency_id: CurrencyId,
+			amount: Balance,
+		) -> DispatchResultWithPostInfo {
+			let payer = ensure_signed(origin)?;
+			let who = T::Lookup::lookup(who)?;
+			<asset_pool::Module<T>>::repay_on_behalf(&payer, &who, currency_id, amount)?;
+			Self::deposit_event(Event::RepayOnBehalfSuccess(payer, who, currency_id, amount));
+			Ok(().into())
+		}
+
+		/// Ask for a credit loan of at most `limit`, due `term` seconds after the first draw.
+		#[pallet::weight((<T as Config>::WeightInfo::request_credit_loan(), DispatchClass::Operational))]
+		#[transactional]
+		pub fn request_credit_loan(
+			origin: OriginFor<T>,
+			currency_id: CurrencyId,
+			limit: Balance,
+			term: u64,
+		) -> DispatchResultWithPostInfo {
+			let who = ensure_signed(origin)?;
+			let loan_id = <asset_pool::Module<T>>::request_credit_loan(&who, currency_id, limit, term)?;
+			Self::deposit_event(Event::RequestCreditLoanSuccess(who, loan_id));
+			Ok(().into())
+		}
+
+		/// Lock `amount` of the loan currency as a guarantee of the credit loan.
+		#[pallet::weight((<T as Config>::WeightInfo::guarantee_credit_loan(), DispatchClass::Operational))]
+		#[transactional]
+		pub fn guarantee_credit_loan(
+			origin: OriginFor<T>,
+			loan_id: CreditLoanId,
+			amount: Balance,
+		) -> DispatchResultWithPostInfo {
+			let who = ensure_signed(origin)?;
+			<asset_pool::Module<T>>::guarantee_credit_loan(&who, loan_id, amount)?;
+			Self::deposit_event(Event::GuaranteeCreditLoanSuccess(who, loan_id, amount));
+			Ok(().into())
+		}
+
+		/// Unlock the signer's guarantee of a credit loan that is not drawn yet.
+		#[pallet::weight((<T as Config>::WeightInfo::withdraw_guarantee(), DispatchClass::Operational))]
+		#[transactional]
+		pub fn withdraw_guarantee(origin: OriginFor<T>, loan_id: CreditLoanId) -> DispatchResultWithPostInfo {
+			let who = ensure_signed(origin)?;
+			let amount = <asset_pool::Module<T>>::withdraw_guarantee(&who, loan_id)?;
+			Self::deposit_event(Event::WithdrawGuaranteeSuccess(who, loan_id, amount));
+			Ok(().into())
+		}
+
+		/// Borrow `amount` on the signer's credit loan.
+		#[pallet::weight((<T as Config>::WeightInfo::draw_credit_loan(), DispatchClass::Operational))]
+		#[transactional]
+		pub fn draw_credit_loan(
+			origin: OriginFor<T>,
+			loan_id: CreditLoanId,
+			amount: Balance,
+		) -> DispatchResultWithPostInfo {
+			let who = ensure_signed(origin)?;
+			<asset_pool::Module<T>>::draw_credit_loan(&who, loan_id, amount)?;
+			Self::deposit_event(Event::DrawCreditLoanSuccess(who, loan_id, amount));
+			Ok(().into())
+		}
+
+		/// Repay `amount` of a credit loan, anyone can repay for the borrower.
+		#[pallet::weight((<T as Config>::WeightInfo::repay_credit_loan(), DispatchClass::Operational))]
+		#[transactional]
+		pub fn repay_credit_loan(
+			origin: OriginFor<T>,
+			loan_id: CreditLoanId,
+			amount: Balance,
+		) -> DispatchResultWithPostInfo {
+			let who = ensure_signed(origin)?;
+			<asset_pool::Module<T>>::repay_credit_loan(&who, loan_id, amount)?;
+			Self::deposit_event(Event::RepayCreditLoanSuccess(who, loan_id, amount));
+			Ok(().into())
+		}
+
+		/// Close the signer's repaid credit loan and unlock the guarantees.
+		#[pallet::weight((<T as Config>::WeightInfo::close_credit_loan(), DispatchClass::Operational))]
+		#[transactional]
+		pub fn close_credit_loan(origin: OriginFor<T>, loan_id: CreditLoanId) -> DispatchResultWithPostInfo {
+			let who = ensure_signed(origin)?;
+			<asset_pool::Module<T>>::close_credit_loan(&who, loan_id)?;
+			Self::deposit_event(Event::CloseCreditLoanSuccess(who, loan_id));
+			Ok(().into())
+		}
+
+		/// Settle an overdue credit loan with the guarantees, anyone can call it.
+		#[pallet::weight((<T as Config>::WeightInfo::default_credit_loan(), DispatchClass::Operational))]
+		#[transactional]
+		pub fn default_credit_loan(origin: OriginFor<T>, loan_id: CreditLoanId) -> DispatchResultWithPostInfo {
+			let who = ensure_signed(origin)?;
+			<asset_pool::Module<T>>::default_credit_loan(loan_id)?;
+			Self::deposit_event(Event::DefaultCreditLoanSuccess(who, loan_id));
+			Ok(().into())
+		}
+
+		/// Borrow `amount` of `currency_id` at a rate locked for `term` seconds.
+		#[pallet::weight((<T as Config>::WeightInfo::borrow_fixed_term(), DispatchClass::Operational))]
+		#[transactional]
+		pub fn borrow_fixed_term(
+			origin: OriginFor<T>,
+			currency_id: CurrencyId,
+			amount: Balance,
+			term: u64,
+		) -> DispatchResultWithPostInfo {
+			let who = ensure_signed(origin)?;
+			<asset_pool::Module<T>>::borrow_fixed_term(&who, currency_id, amount, term)?;
+			Self::deposit_event(Event::BorrowFixedTermSuccess(who, currency_id, amount, term));
+			Ok(().into())
+		}
+
+		/// Repay `amount` of the signer's fixed-term loan in `currency_id`.
+		#[pallet::weight((<T as Config>::WeightInfo::repay_fixed_term(), DispatchClass::Operational))]
+		#[transactional]
+		pub fn repay_fixed_term(
+			origin: OriginFor<T>,
+			currency_id: CurrencyId,
+			amount: Balance,
+		) -> DispatchResultWithPostInfo {
+			let who = ensure_signed(origin)?;
+			<asset_pool::Module<T>>::repay_fixed_term(&who, currency_id, amount)?;
+			Self::deposit_event(Event::RepayFixedTermSuccess(who, currency_id, amount));
+			Ok(().into())
+		}
 	}
 }
@@ -9,4 +9,19 @@ use sp_std::marker::PhantomData;
 pub trait WeightInfo {
 	fn adjust_deposit() -> Weight;
 	fn adjust_debit() -> Weight;
+	fn set_collateral() -> Weight;
+	fn withdraw_all() -> Weight;
+	fn repay_all() -> Weight;
+	fn flash_loan() -> Weight;
+	fn deposit_for() -> Weight;
+	fn repay_on_behalf() -> Weight;
+	fn request_credit_loan() -> Weight;
+	fn guarantee_credit_loan() -> Weight;
+	fn withdraw_guarantee() -> Weight;
+	fn draw_credit_loan() -> Weight;
+	fn repay_credit_loan() -> Weight;
+	fn close_credit_loan() -> Weight;
+	fn default_credit_loan() -> Weight;
+	fn borrow_fixed_term() -> Weight;
+	fn repay_fixed_term() -> Weight;
 }
@@ -3,11 +3,38 @@
 use frame_support::{pallet_prelude::*, transactional};
 use frame_system::pallet_prelude::*;
 use orml_traits::{DataFeeder, DataProvider};
-use sp_runtime::{traits::CheckedDiv};
+#[cfg(feature = "std")]
+use serde::{Deserialize, Serialize};
+use sp_runtime::{RuntimeDebug, traits::{CheckedDiv, CheckedMul}};
 
-use model::{CurrencyId, Price};
+use model::{CurrencyId, Price, PriceOrigin, PriceProvider, TwapProvider};
 pub use module::*;
 
+mod mock;
+mod test;
+
+/// Which price source to ask first for a currency: the aggregated oracle `T::Source` or the dex
+/// TWAP against `T::QuoteCurrencyId`. A locked price always wins over both.
+#[derive(Encode, Decode, Eq, PartialEq, Copy, Clone, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub enum PriceSourcePolicy {
+	OracleOnly,
+
+	// fall back to the dex TWAP when the oracle has no fresh price
+	OracleFirst,
+
+	// fall back to the oracle when the dex has no TWAP
+	DexFirst,
+
+	DexOnly,
+}
+
+impl Default for PriceSourcePolicy {
+	fn default() -> Self {
+		Self::OracleOnly
+	}
+}
+
 #[frame_support::pallet]
 pub mod module {
 	use super::*;
@@ -19,6 +46,16 @@ pub mod module {
 		type Source: DataProvider<CurrencyId, Price> + DataFeeder<CurrencyId, Price, Self::AccountId>;
 
 		type LockOrigin: EnsureOrigin<Self::Origin>;
+
+		type Dex: TwapProvider;
+
+		/// Currency the dex TWAPs are taken against, priced by the oracle.
+		#[pallet::constant]
+		type QuoteCurrencyId: Get<CurrencyId>;
+
+		/// Seconds the dex TWAPs average over at least.
+		#[pallet::constant]
+		type TwapWindow: Get<u64>;
 	}
 
 	#[pallet::event]
@@ -26,12 +63,17 @@ pub mod module {
 	pub enum Event<T: Config> {
 		LockPrice(CurrencyId, Price),
 		UnlockPrice(CurrencyId),
+		PriceSourcePolicyUpdated(CurrencyId, PriceSourcePolicy),
 	}
 
 	#[pallet::storage]
 	#[pallet::getter(fn locked_price)]
 	pub type LockedPrice<T: Config> = StorageMap<_, Twox64Concat, CurrencyId, Price, OptionQuery>;
 
+	#[pallet::storage]
+	#[pallet::getter(fn price_source_policies)]
+	pub type PriceSourcePolicies<T: Config> = StorageMap<_, Twox64Concat, CurrencyId, PriceSourcePolicy, ValueQuery>;
+
 	#[pallet::pallet]
 	pub struct Pallet<T>(PhantomData<T>);
 
@@ -59,6 +101,19 @@ pub mod module {
 			Self::deposit_event(Event::UnlockPrice(currency_id));
 			Ok(().into())
 		}
+
+		#[pallet::weight(10_000_000 + T::DbWeight::get().writes(1))]
+		#[transactional]
+		pub fn set_price_source_policy(
+			origin: OriginFor<T>,
+			currency_id: CurrencyId,
+			policy: PriceSourcePolicy,
+		) -> DispatchResultWithPostInfo {
+			T::LockOrigin::ensure_origin(origin)?;
+			PriceSourcePolicies::<T>::insert(currency_id, policy);
+			Self::deposit_event(Event::PriceSourcePolicyUpdated(currency_id, policy));
+			Ok(().into())
+		}
 	}
 }
 
@@ -74,6 +129,51 @@ impl<T: Config> Pallet<T> {
 	}
 
 	fn get_price(currency_id: CurrencyId) -> Option<Price> {
-		Self::locked_price(currency_id).or_else(|| T::Source::get(&currency_id))
+		Self::get_price_with_origin(currency_id).map(|(price, _)| price)
+	}
+
+	fn get_price_with_origin(currency_id: CurrencyId) -> Option<(Price, PriceOrigin)> {
+		if let Some(price) = Self::locked_price(currency_id) {
+			return Some((price, PriceOrigin::Locked));
+		}
+
+		let oracle = || Self::oracle_price(currency_id).map(|price| (price, PriceOrigin::Oracle));
+		let dex = || Self::dex_price(currency_id).map(|price| (price, PriceOrigin::Dex));
+		match Self::price_source_policies(currency_id) {
+			PriceSourcePolicy::OracleOnly => oracle(),
+			PriceSourcePolicy::OracleFirst => oracle().or_else(dex),
+			PriceSourcePolicy::DexFirst => dex().or_else(oracle),
+			PriceSourcePolicy::DexOnly => dex(),
+		}
+	}
+
+	fn oracle_price(currency_id: CurrencyId) -> Option<Price> {
+		T::Source::get(&currency_id)
+	}
+
+	/// TWAP of the currency in the quote currency, valued at the locked or oracle price of the quote currency.
+	fn dex_price(currency_id: CurrencyId) -> Option<Price> {
+		let quote_currency_id = T::QuoteCurrencyId::get();
+		if currency_id == quote_currency_id {
+			return None;
+		}
+
+		let twap = T::Dex::get_twap(currency_id, quote_currency_id, T::TwapWindow::get())?;
+		let quote_price = Self::locked_price(quote_currency_id).or_else(|| Self::oracle_price(quote_currency_id))?;
+		twap.checked_mul(&quote_price)
+	}
+}
+
+impl<T: Config> PriceProvider for Pallet<T> {
+	fn get_price(currency_id: CurrencyId) -> Option<Price> {
+		Self::get_price(currency_id)
+	}
+
+	fn get_price_with_origin(currency_id: CurrencyId) -> Option<(Price, PriceOrigin)> {
+		Self::get_price_with_origin(currency_id)
+	}
+
+	fn get_relative_price(base_currency_id: CurrencyId, quote_currency_id: CurrencyId) -> Option<Price> {
+		Self::get_relative_price(base_currency_id, quote_currency_id)
 	}
 }
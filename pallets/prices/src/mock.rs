@@ -0,0 +1,146 @@
+#![cfg(test)]
+
+use std::{cell::RefCell, collections::HashMap};
+
+use frame_support::{construct_runtime, ord_parameter_types, parameter_types};
+use frame_system::EnsureSignedBy;
+use sp_core::H256;
+use sp_runtime::{DispatchResult, testing::Header, traits::IdentityLookup};
+
+use model::{CurrencyId, Price, TwapProvider};
+
+use super::*;
+
+pub type AccountId = u128;
+pub type BlockNumber = u64;
+
+pub const ALICE: AccountId = 1;
+
+pub const MB: CurrencyId = CurrencyId::Token(0);
+pub const DOT: CurrencyId = CurrencyId::Token(1);
+
+mod prices {
+	pub use super::super::*;
+}
+
+parameter_types! {
+	pub const BlockHashCount: u64 = 250;
+}
+
+impl frame_system::Config for Runtime {
+	type Origin = Origin;
+	type Index = u64;
+	type BlockNumber = BlockNumber;
+	type Call = Call;
+	type Hash = H256;
+	type Hashing = ::sp_runtime::traits::BlakeTwo256;
+	type AccountId = AccountId;
+	type Lookup = IdentityLookup<Self::AccountId>;
+	type Header = Header;
+	type Event = Event;
+	type BlockHashCount = BlockHashCount;
+	type BlockWeights = ();
+	type BlockLength = ();
+	type Version = ();
+	type PalletInfo = PalletInfo;
+	type AccountData = ();
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type DbWeight = ();
+	type BaseCallFilter = ();
+	type SystemWeightInfo = ();
+	type SS58Prefix = ();
+}
+
+/// Blocks an oracle value stays fresh for, like the `ExpiresIn` of the aggregated oracle.
+pub const ORACLE_EXPIRES_IN: BlockNumber = 10;
+
+thread_local! {
+	static ORACLE_VALUES: RefCell<HashMap<CurrencyId, (Price, BlockNumber)>> = RefCell::new(HashMap::new());
+	static TWAPS: RefCell<HashMap<(CurrencyId, CurrencyId), Price>> = RefCell::new(HashMap::new());
+}
+
+pub struct MockOracle;
+
+impl DataProvider<CurrencyId, Price> for MockOracle {
+	fn get(currency_id: &CurrencyId) -> Option<Price> {
+		let now = System::block_number();
+		ORACLE_VALUES.with(|values| values.borrow().get(currency_id).copied())
+			.filter(|(_, fed_at)| now < fed_at + ORACLE_EXPIRES_IN)
+			.map(|(price, _)| price)
+	}
+}
+
+impl DataFeeder<CurrencyId, Price, AccountId> for MockOracle {
+	fn feed_value(_who: AccountId, currency_id: CurrencyId, price: Price) -> DispatchResult {
+		let now = System::block_number();
+		ORACLE_VALUES.with(|values| values.borrow_mut().insert(currency_id, (price, now)));
+		Ok(())
+	}
+}
+
+pub struct MockDex;
+
+impl MockDex {
+	pub fn set_twap(base_currency_id: CurrencyId, quote_currency_id: CurrencyId, twap: Option<Price>) {
+		TWAPS.with(|twaps| match twap {
+			Some(twap) => twaps.borrow_mut().insert((base_currency_id, quote_currency_id), twap),
+			None => twaps.borrow_mut().remove(&(base_currency_id, quote_currency_id)),
+		});
+	}
+}
+
+impl TwapProvider for MockDex {
+	fn get_twap(base_currency_id: CurrencyId, quote_currency_id: CurrencyId, _window: u64) -> Option<Price> {
+		TWAPS.with(|twaps| twaps.borrow().get(&(base_currency_id, quote_currency_id)).copied())
+	}
+}
+
+parameter_types! {
+	pub const QuoteCurrencyId: CurrencyId = DOT;
+	pub const TwapWindow: u64 = 60;
+}
+
+ord_parameter_types! {
+	pub const One: AccountId = 1;
+}
+
+impl Config for Runtime {
+	type Event = Event;
+	type Source = MockOracle;
+	type LockOrigin = EnsureSignedBy<One, AccountId>;
+	type Dex = MockDex;
+	type QuoteCurrencyId = QuoteCurrencyId;
+	type TwapWindow = TwapWindow;
+}
+
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Runtime>;
+type Block = frame_system::mocking::MockBlock<Runtime>;
+
+construct_runtime!(
+	pub enum Runtime where
+		Block = Block,
+		NodeBlock = Block,
+		UncheckedExtrinsic = UncheckedExtrinsic,
+	{
+		System: frame_system::{Module, Call, Storage, Config, Event<T>},
+		PricesModule: prices::{Module, Storage, Call, Event<T>},
+	}
+);
+
+#[derive(Default)]
+pub struct ExtBuilder;
+
+impl ExtBuilder {
+	pub fn build(self) -> sp_io::TestExternalities {
+		ORACLE_VALUES.with(|values| values.borrow_mut().clear());
+		TWAPS.with(|twaps| twaps.borrow_mut().clear());
+
+		let t = frame_system::GenesisConfig::default()
+			.build_storage::<Runtime>()
+			.unwrap();
+		let mut ext = sp_io::TestExternalities::new(t);
+		ext.execute_with(|| System::set_block_number(1));
+		ext
+	}
+}
@@ -0,0 +1,125 @@
+#![cfg(test)]
+
+use frame_support::{assert_noop, assert_ok};
+use sp_runtime::{FixedPointNumber, traits::{BadOrigin, One}};
+
+use mock::{ALICE, DOT, Event, ExtBuilder, MB, MockDex, MockOracle, ORACLE_EXPIRES_IN, Origin, PricesModule, System};
+
+use super::*;
+
+fn set_policy(currency_id: CurrencyId, policy: PriceSourcePolicy) {
+	assert_ok!(PricesModule::set_price_source_policy(Origin::signed(ALICE), currency_id, policy));
+}
+
+#[test]
+fn set_price_source_policy_work() {
+	ExtBuilder::default().build().execute_with(|| {
+		assert_eq!(PricesModule::price_source_policies(MB), PriceSourcePolicy::OracleOnly);
+		assert_noop!(
+			PricesModule::set_price_source_policy(Origin::signed(2), MB, PriceSourcePolicy::DexOnly),
+			BadOrigin
+		);
+
+		set_policy(MB, PriceSourcePolicy::DexFirst);
+		assert_eq!(PricesModule::price_source_policies(MB), PriceSourcePolicy::DexFirst);
+		let policy_event = Event::prices(crate::Event::PriceSourcePolicyUpdated(MB, PriceSourcePolicy::DexFirst));
+		assert!(System::events().iter().any(|record| record.event == policy_event));
+	});
+}
+
+#[test]
+fn price_source_policies_work() {
+	ExtBuilder::default().build().execute_with(|| {
+		assert_ok!(MockOracle::feed_value(ALICE, DOT, Price::one()));
+		assert_ok!(MockOracle::feed_value(ALICE, MB, Price::saturating_from_integer(2)));
+		MockDex::set_twap(MB, DOT, Some(Price::saturating_from_integer(3)));
+		let oracle_price = Some(Price::saturating_from_integer(2));
+		let dex_price = Some(Price::saturating_from_integer(3));
+
+		// both sources price MB
+		assert_eq!(PricesModule::get_price(MB), oracle_price);
+		set_policy(MB, PriceSourcePolicy::OracleFirst);
+		assert_eq!(PricesModule::get_price(MB), oracle_price);
+		set_policy(MB, PriceSourcePolicy::DexFirst);
+		assert_eq!(PricesModule::get_price(MB), dex_price);
+		set_policy(MB, PriceSourcePolicy::DexOnly);
+		assert_eq!(PricesModule::get_price(MB), dex_price);
+
+		// only the oracle prices MB
+		MockDex::set_twap(MB, DOT, None);
+		set_policy(MB, PriceSourcePolicy::OracleOnly);
+		assert_eq!(PricesModule::get_price(MB), oracle_price);
+		set_policy(MB, PriceSourcePolicy::OracleFirst);
+		assert_eq!(PricesModule::get_price(MB), oracle_price);
+		set_policy(MB, PriceSourcePolicy::DexFirst);
+		assert_eq!(PricesModule::get_price(MB), oracle_price);
+		set_policy(MB, PriceSourcePolicy::DexOnly);
+		assert_eq!(PricesModule::get_price(MB), None);
+
+		// the quote currency has no dex price of its own
+		set_policy(DOT, PriceSourcePolicy::DexOnly);
+		assert_eq!(PricesModule::get_price(DOT), None);
+		assert_eq!(PricesModule::get_relative_price(MB, DOT), None);
+		set_policy(DOT, PriceSourcePolicy::OracleOnly);
+		set_policy(MB, PriceSourcePolicy::OracleOnly);
+		assert_eq!(PricesModule::get_relative_price(MB, DOT), oracle_price);
+	});
+}
+
+#[test]
+fn expired_oracle_price_falls_back_to_dex_work() {
+	ExtBuilder::default().build().execute_with(|| {
+		assert_ok!(MockOracle::feed_value(ALICE, MB, Price::saturating_from_integer(2)));
+		MockDex::set_twap(MB, DOT, Some(Price::saturating_from_integer(3)));
+
+		System::set_block_number(1 + ORACLE_EXPIRES_IN);
+		// the quote currency is still fed, MB is not
+		assert_ok!(MockOracle::feed_value(ALICE, DOT, Price::saturating_from_rational(1, 2)));
+		assert_eq!(PricesModule::get_price(MB), None);
+
+		set_policy(MB, PriceSourcePolicy::OracleFirst);
+		System::reset_events();
+		assert_eq!(PricesModule::get_price(MB), Some(Price::saturating_from_rational(3, 2)));
+		// reading a price never writes
+		assert!(System::events().is_empty());
+		assert_eq!(
+			PricesModule::get_price_with_origin(MB),
+			Some((Price::saturating_from_rational(3, 2), PriceOrigin::Dex))
+		);
+
+		assert_ok!(MockOracle::feed_value(ALICE, MB, Price::saturating_from_integer(2)));
+		assert_eq!(PricesModule::get_price(MB), Some(Price::saturating_from_integer(2)));
+		assert_eq!(PricesModule::get_price_with_origin(MB), Some((Price::saturating_from_integer(2), PriceOrigin::Oracle)));
+	});
+}
+
+#[test]
+fn locked_price_takes_precedence_work() {
+	ExtBuilder::default().build().execute_with(|| {
+		assert_ok!(MockOracle::feed_value(ALICE, DOT, Price::one()));
+		assert_ok!(MockOracle::feed_value(ALICE, MB, Price::saturating_from_integer(2)));
+		MockDex::set_twap(MB, DOT, Some(Price::saturating_from_integer(3)));
+
+		assert_noop!(PricesModule::lock_price(Origin::signed(2), MB), BadOrigin);
+		assert_ok!(PricesModule::lock_price(Origin::signed(ALICE), MB));
+		assert_eq!(PricesModule::locked_price(MB), Some(Price::saturating_from_integer(2)));
+
+		assert_ok!(MockOracle::feed_value(ALICE, MB, Price::saturating_from_integer(5)));
+		for policy in [
+			PriceSourcePolicy::OracleOnly,
+			PriceSourcePolicy::OracleFirst,
+			PriceSourcePolicy::DexFirst,
+			PriceSourcePolicy::DexOnly,
+		].iter() {
+			set_policy(MB, *policy);
+			assert_eq!(PricesModule::get_price(MB), Some(Price::saturating_from_integer(2)));
+			assert_eq!(PricesModule::get_price_with_origin(MB), Some((Price::saturating_from_integer(2), PriceOrigin::Locked)));
+		}
+
+		// a locked quote price values the dex price, even once the oracle expired
+		assert_ok!(PricesModule::lock_price(Origin::signed(ALICE), DOT));
+		assert_ok!(PricesModule::unlock_price(Origin::signed(ALICE), MB));
+		System::set_block_number(1 + ORACLE_EXPIRES_IN);
+		assert_eq!(PricesModule::get_price(MB), Some(Price::saturating_from_integer(3)));
+	});
+}
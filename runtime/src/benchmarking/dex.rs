@@ -3,6 +3,7 @@ use crate::{
 	TradingPair, DOT, ETH, BTC, Ratio, DEX
 };
 
+use dex::DexOperation;
 use frame_benchmarking::account;
 use frame_system::RawOrigin;
 use orml_benchmarking::runtime_benchmarks;
@@ -60,6 +61,33 @@ runtime_benchmarks! {
 		let mut route: Vec<CurrencyId> = vec![DOT, ETH];
 	}: swap_with_exact_target(RawOrigin::Signed(taker), route, 10u128, 100u128, Ratio::saturating_from_rational(1, 1))
 
+	swap_with_best_route {
+		let p in 1 .. 3;
+
+		let maker: AccountId = account("maker", 0, SEED);
+		let taker: AccountId = account("taker", 0, SEED);
+		inject_liquidity(maker.clone(), DOT, ETH, 10_000u128, 10_000u128, false)?;
+		if p > 1 {
+			inject_liquidity(maker.clone(), DOT, BTC, 10_000u128, 10_000u128, false)?;
+		}
+		if p > 2 {
+			inject_liquidity(maker, ETH, BTC, 10_000u128, 10_000u128, false)?;
+		}
+
+		Tokens::update_balance(DOT, &taker, (10_000u128).unique_saturated_into())?;
+	}: swap_with_best_route(RawOrigin::Signed(taker), DOT, ETH, 100u128, 0, Ratio::saturating_from_rational(1, 1))
+
+	swap_with_split_routes {
+		let r in 1 .. 4;
+
+		let maker: AccountId = account("maker", 0, SEED);
+		let taker: AccountId = account("taker", 0, SEED);
+		inject_liquidity(maker, DOT, ETH, 10_000u128, 10_000u128, false)?;
+
+		Tokens::update_balance(DOT, &taker, (10_000u128).unique_saturated_into())?;
+		let routes: Vec<(Vec<CurrencyId>, Balance)> = (0..r).map(|_| (vec![DOT, ETH], 100u128)).collect();
+	}: swap_with_split_routes(RawOrigin::Signed(taker), routes, 0, Ratio::saturating_from_rational(1, 1))
+
 	add_liquidity {
 		let first_maker: AccountId = account("first_maker", 0, SEED);
 		let second_maker: AccountId = account("second_maker", 0, SEED);
@@ -104,4 +132,9 @@ runtime_benchmarks! {
 		let _ = DEX::disable_trading_pair(RawOrigin::Root.into(), currency_id_a, currency_id_b);
 	}: _(RawOrigin::Root, currency_id_a, currency_id_b, min_contribution_a, min_contribution_b, target_amount_a, target_amount_b, not_before)
 
+	set_operation_paused {
+		let currency_id_a = DOT;
+		let currency_id_b = ETH;
+	}: _(RawOrigin::Root, currency_id_a, currency_id_b, DexOperation::Swap, true)
+
 }
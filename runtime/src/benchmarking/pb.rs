@@ -3,9 +3,10 @@ use frame_system::RawOrigin;
 use orml_benchmarking::runtime_benchmarks;
 use orml_traits::{MultiCurrency, MultiCurrencyExtended};
 use sp_runtime::traits::SaturatedConversion;
+use sp_runtime::FixedPointNumber;
 use sp_std::prelude::*;
 
-use crate::{AccountId, Balance, CurrencyId, Tokens, AssetPool, DOT, Runtime};
+use crate::{AccountId, Balance, Call, CurrencyId, Price, Tokens, AssetPool, DOT, ETH, Runtime, Timestamp};
 
 use super::utils::set_balance;
 
@@ -22,7 +23,150 @@ runtime_benchmarks! {
 	adjust_debit {
 		let caller = account("caller", 0, 0);
 		let _ = Tokens::update_balance(DOT, &caller, 1000i128);
-		AssetPool::update_deposit(&caller, DOT, 100i128);
+		AssetPool::update_deposit(&caller, DOT, 100i128)?;
+		AssetPool::set_collateral(&caller, DOT, true)?;
+		prices::LockedPrice::<Runtime>::insert(DOT, Price::one());
 	}: _(RawOrigin::Signed(caller), DOT, 10i128)
 
+	set_collateral {
+		let caller: AccountId = account("caller", 0, 0);
+		let _ = Tokens::update_balance(DOT, &caller, 1000i128);
+		let _ = Tokens::update_balance(ETH, &caller, 1000i128);
+		prices::LockedPrice::<Runtime>::insert(DOT, Price::one());
+		prices::LockedPrice::<Runtime>::insert(ETH, Price::one());
+		AssetPool::update_deposit(&caller, DOT, 100i128)?;
+		AssetPool::update_deposit(&caller, ETH, 100i128)?;
+		AssetPool::set_collateral(&caller, DOT, true)?;
+		AssetPool::set_collateral(&caller, ETH, true)?;
+		AssetPool::update_debit(&caller, DOT, 10i128)?;
+	}: _(RawOrigin::Signed(caller), DOT, false)
+
+	withdraw_all {
+		let caller: AccountId = account("caller", 0, 0);
+		let _ = Tokens::update_balance(DOT, &caller, 1000i128);
+		AssetPool::update_deposit(&caller, DOT, 100i128)?;
+	}: _(RawOrigin::Signed(caller), DOT)
+
+	repay_all {
+		let caller: AccountId = account("caller", 0, 0);
+		let _ = Tokens::update_balance(DOT, &caller, 1000i128);
+		AssetPool::update_deposit(&caller, DOT, 100i128)?;
+		AssetPool::set_collateral(&caller, DOT, true)?;
+		prices::LockedPrice::<Runtime>::insert(DOT, Price::one());
+		AssetPool::update_debit(&caller, DOT, 10i128)?;
+	}: _(RawOrigin::Signed(caller), DOT)
+
+	flash_loan {
+		let depositor: AccountId = account("depositor", 0, 0);
+		let _ = Tokens::update_balance(DOT, &depositor, 1000i128);
+		AssetPool::update_deposit(&depositor, DOT, 1000i128)?;
+		let caller: AccountId = account("caller", 0, 0);
+		let _ = Tokens::update_balance(DOT, &caller, 10i128);
+		let call = Box::new(Call::System(frame_system::Call::remark(vec![])));
+	}: _(RawOrigin::Signed(caller), DOT, 1000, call)
+
+	deposit_for {
+		let caller: AccountId = account("caller", 0, 0);
+		let who: AccountId = account("who", 0, 0);
+		let _ = Tokens::update_balance(DOT, &caller, 100i128);
+	}: _(RawOrigin::Signed(caller), who.into(), DOT, 100)
+
+	repay_on_behalf {
+		let caller: AccountId = account("caller", 0, 0);
+		let who: AccountId = account("who", 0, 0);
+		let _ = Tokens::update_balance(DOT, &caller, 100i128);
+		let _ = Tokens::update_balance(DOT, &who, 1000i128);
+		AssetPool::update_deposit(&who, DOT, 100i128)?;
+		AssetPool::set_collateral(&who, DOT, true)?;
+		prices::LockedPrice::<Runtime>::insert(DOT, Price::one());
+		AssetPool::update_debit(&who, DOT, 50i128)?;
+	}: _(RawOrigin::Signed(caller), who.into(), DOT, 50)
+
+	request_credit_loan {
+		let caller: AccountId = account("caller", 0, 0);
+	}: _(RawOrigin::Signed(caller), DOT, 100, 1000)
+
+	guarantee_credit_loan {
+		let borrower: AccountId = account("borrower", 0, 0);
+		let caller: AccountId = account("caller", 0, 0);
+		let _ = Tokens::update_balance(DOT, &caller, 100i128);
+		let loan_id = AssetPool::request_credit_loan(&borrower, DOT, 100, 1000)?;
+	}: _(RawOrigin::Signed(caller), loan_id, 100)
+
+	withdraw_guarantee {
+		let borrower: AccountId = account("borrower", 0, 0);
+		let caller: AccountId = account("caller", 0, 0);
+		let _ = Tokens::update_balance(DOT, &caller, 100i128);
+		let loan_id = AssetPool::request_credit_loan(&borrower, DOT, 100, 1000)?;
+		AssetPool::guarantee_credit_loan(&caller, loan_id, 100)?;
+	}: _(RawOrigin::Signed(caller), loan_id)
+
+	draw_credit_loan {
+		let depositor: AccountId = account("depositor", 0, 0);
+		let guarantor: AccountId = account("guarantor", 0, 0);
+		let caller: AccountId = account("caller", 0, 0);
+		let _ = Tokens::update_balance(DOT, &depositor, 1000i128);
+		let _ = Tokens::update_balance(DOT, &guarantor, 100i128);
+		AssetPool::update_deposit(&depositor, DOT, 1000i128)?;
+		let loan_id = AssetPool::request_credit_loan(&caller, DOT, 100, 1000)?;
+		AssetPool::guarantee_credit_loan(&guarantor, loan_id, 100)?;
+	}: _(RawOrigin::Signed(caller), loan_id, 100)
+
+	repay_credit_loan {
+		let depositor: AccountId = account("depositor", 0, 0);
+		let guarantor: AccountId = account("guarantor", 0, 0);
+		let caller: AccountId = account("caller", 0, 0);
+		let _ = Tokens::update_balance(DOT, &depositor, 1000i128);
+		let _ = Tokens::update_balance(DOT, &guarantor, 100i128);
+		AssetPool::update_deposit(&depositor, DOT, 1000i128)?;
+		let loan_id = AssetPool::request_credit_loan(&caller, DOT, 100, 1000)?;
+		AssetPool::guarantee_credit_loan(&guarantor, loan_id, 100)?;
+		AssetPool::draw_credit_loan(&caller, loan_id, 100)?;
+	}: _(RawOrigin::Signed(caller), loan_id, 100)
+
+	close_credit_loan {
+		let guarantor: AccountId = account("guarantor", 0, 0);
+		let caller: AccountId = account("caller", 0, 0);
+		let _ = Tokens::update_balance(DOT, &guarantor, 100i128);
+		let loan_id = AssetPool::request_credit_loan(&caller, DOT, 100, 1000)?;
+		AssetPool::guarantee_credit_loan(&guarantor, loan_id, 100)?;
+	}: _(RawOrigin::Signed(caller), loan_id)
+
+	default_credit_loan {
+		let depositor: AccountId = account("depositor", 0, 0);
+		let guarantor: AccountId = account("guarantor", 0, 0);
+		let borrower: AccountId = account("borrower", 0, 0);
+		let caller: AccountId = account("caller", 0, 0);
+		let _ = Tokens::update_balance(DOT, &depositor, 1000i128);
+		let _ = Tokens::update_balance(DOT, &guarantor, 100i128);
+		AssetPool::update_deposit(&depositor, DOT, 1000i128)?;
+		let loan_id = AssetPool::request_credit_loan(&borrower, DOT, 100, 1000)?;
+		AssetPool::guarantee_credit_loan(&guarantor, loan_id, 100)?;
+		AssetPool::draw_credit_loan(&borrower, loan_id, 100)?;
+		Timestamp::set_timestamp(Timestamp::now() + 1000 * 1000);
+	}: _(RawOrigin::Signed(caller), loan_id)
+
+	borrow_fixed_term {
+		let depositor: AccountId = account("depositor", 0, 0);
+		let caller: AccountId = account("caller", 0, 0);
+		let _ = Tokens::update_balance(DOT, &depositor, 1000i128);
+		let _ = Tokens::update_balance(DOT, &caller, 1000i128);
+		AssetPool::update_deposit(&depositor, DOT, 1000i128)?;
+		AssetPool::update_deposit(&caller, DOT, 1000i128)?;
+		AssetPool::set_collateral(&caller, DOT, true)?;
+		prices::LockedPrice::<Runtime>::insert(DOT, Price::one());
+	}: _(RawOrigin::Signed(caller), DOT, 100, 1000)
+
+	repay_fixed_term {
+		let depositor: AccountId = account("depositor", 0, 0);
+		let caller: AccountId = account("caller", 0, 0);
+		let _ = Tokens::update_balance(DOT, &depositor, 1000i128);
+		let _ = Tokens::update_balance(DOT, &caller, 1000i128);
+		AssetPool::update_deposit(&depositor, DOT, 1000i128)?;
+		AssetPool::update_deposit(&caller, DOT, 500i128)?;
+		AssetPool::set_collateral(&caller, DOT, true)?;
+		prices::LockedPrice::<Runtime>::insert(DOT, Price::one());
+		AssetPool::borrow_fixed_term(&caller, DOT, 100, 1000)?;
+	}: _(RawOrigin::Signed(caller), DOT, 100)
+
 }
@@ -6,8 +6,8 @@ pub use frame_support::{
 	construct_runtime, parameter_types, StorageValue,
 	traits::{KeyOwnerProofSystem, Randomness},
 	weights::{
-		constants::{BlockExecutionWeight, ExtrinsicBaseWeight, RocksDbWeight, WEIGHT_PER_SECOND}, IdentityFee,
-		Weight,
+		constants::{BlockExecutionWeight, ExtrinsicBaseWeight, RocksDbWeight, WEIGHT_PER_SECOND}, GetDispatchInfo,
+		IdentityFee, Weight,
 	},
 };
 use frame_system::EnsureRoot;
@@ -30,13 +30,15 @@ pub use sp_runtime::{ModuleId, Perbill, Permill, traits::IdentityLookup};
 #[cfg(any(feature = "std", test))]
 pub use sp_runtime::BuildStorage;
 use sp_runtime::traits::{
-	AccountIdLookup, BlakeTwo256, Block as BlockT, IdentifyAccount, NumberFor, Verify,
+	AccountIdConversion, AccountIdLookup, BlakeTwo256, Block as BlockT, Dispatchable, IdentifyAccount, NumberFor,
+	Verify,
 };
 use sp_std::prelude::*;
 #[cfg(feature = "std")]
 use sp_version::NativeVersion;
 use sp_version::RuntimeVersion;
 
+use model::{DEXManager, FlashLoanReceiver};
 pub use model::{Amount, Balance, CurrencyId, DataProviderId,
 				DOT, ETH, FIL, MB, BTC, DOT_ETH_PAIR, Moment, Price, Ratio, TimeStampedPrice, TradingPair};
 
@@ -98,10 +100,10 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
 	spec_name: create_runtime_str!("antimatter.network Testnet"),
 	impl_name: create_runtime_str!("antimatter.network Testnet"),
 	authoring_version: 1,
-	spec_version: 104,
+	spec_version: 105,
 	impl_version: 1,
 	apis: RUNTIME_API_VERSIONS,
-	transaction_version: 1,
+	transaction_version: 2,
 };
 
 
@@ -330,9 +332,14 @@ impl orml_tokens::Config for Runtime {
 
 parameter_types! {
 	pub const LoansModuleId: ModuleId = ModuleId(*b"antimatterdc");
-	pub AssetPoolIds: Vec<CurrencyId> = vec![DOT, BTC, ETH, FIL];
-	pub BlockPercentEachYear: Ratio = Ratio::saturating_from_rational(1, 5256000); // 365*24*60*10
 	pub const UnsignedPriority: u64 = 1 << 20;
+	pub const TreasuryModuleId: ModuleId = ModuleId(*b"am/trsry");
+	pub TreasuryAccount: AccountId = TreasuryModuleId::get().into_account();
+	pub FlashLoanFee: Ratio = Ratio::saturating_from_rational(9, 10_000);
+	pub OverduePenaltyRate: Ratio = Ratio::saturating_from_rational(10, 100);
+	pub const OverdueGracePeriod: u64 = 7 * 24 * 60 * 60;
+	pub LiquidationCloseFactor: Ratio = Ratio::saturating_from_rational(50, 100);
+	pub MaxLiquidationBonus: Ratio = Ratio::saturating_from_rational(20, 100);
 }
 
 impl<C> frame_system::offchain::SendTransactionTypes<C> for Runtime
@@ -343,15 +350,38 @@ impl<C> frame_system::offchain::SendTransactionTypes<C> for Runtime
 	type Extrinsic = UncheckedExtrinsic;
 }
 
+/// Dispatches the call of the flash loan borrower while it holds the funds.
+pub struct DispatchFlashLoanCall;
+impl FlashLoanReceiver<AccountId> for DispatchFlashLoanCall {
+	type Payload = Box<Call>;
+
+	fn on_flash_loan(who: &AccountId, _currency_id: CurrencyId, _amount: Balance, _fee: Balance, call: Box<Call>) -> DispatchResult {
+		call.dispatch(Origin::signed(who.clone())).map(|_| ()).map_err(|e| e.error)
+	}
+
+	fn payload_weight(call: &Box<Call>) -> Weight {
+		call.get_dispatch_info().weight
+	}
+}
+
 impl asset_pool::Config for Runtime {
 	type Event = Event;
 	type Currency = Tokens;
 	type ModuleId = LoansModuleId;
 
-	type AssetPoolIds = AssetPoolIds;
-	type BlockPercentEachYear = BlockPercentEachYear;
+	type UnixTime = Timestamp;
 	type UnsignedPriority = UnsignedPriority;
 	type OnUpdateLoan = incentives::OnUpdateLoan<Runtime>;
+	type PriceSource = Prices;
+	type DEX = DEX;
+	type LiquidationPenaltyReceiver = TreasuryAccount;
+	type UpdateOrigin = EnsureRoot<AccountId>;
+	type FlashLoanReceiver = DispatchFlashLoanCall;
+	type FlashLoanFee = FlashLoanFee;
+	type OverduePenaltyRate = OverduePenaltyRate;
+	type OverdueGracePeriod = OverdueGracePeriod;
+	type LiquidationCloseFactor = LiquidationCloseFactor;
+	type MaxLiquidationBonus = MaxLiquidationBonus;
 }
 
 impl orml_rewards::Config for Runtime {
@@ -394,16 +424,32 @@ impl pallet_membership::Config<OperatorMembershipInstanceantimatter> for Runtime
 	type MembershipChanged = antimatterOracle;
 }
 
+parameter_types! {
+	pub const PriceQuoteCurrencyId: CurrencyId = DOT;
+	pub const TwapWindow: u64 = 30 * 60;
+}
+
 impl prices::Config for Runtime {
 	type Event = Event;
 	type Source = AggregatedDataProvider;
 	type LockOrigin = EnsureRoot<AccountId>;
+	type Dex = DEX;
+	type QuoteCurrencyId = PriceQuoteCurrencyId;
+	type TwapWindow = TwapWindow;
 }
 
 parameter_types! {
 	pub const GetExchangeFee: (u32, u32) = (1, 100);
 	pub const TradingPathLimit: u32 = 3;
+	pub const SplitRouteLimit: u32 = 4;
+	pub const RouteSearchLimit: u32 = 8;
+	pub const MaxEnabledTradingPairs: u32 = 256;
 	pub const DEXModuleId: ModuleId = ModuleId(*b"antimatterex");
+	pub MaxLeverage: Ratio = Ratio::saturating_from_rational(5, 1);
+	pub PositionLiquidationRatio: Ratio = Ratio::saturating_from_rational(110, 100);
+	pub PositionLiquidationPenalty: Ratio = Ratio::saturating_from_rational(5, 100);
+	pub const ObservationPeriod: u64 = 10 * 60;
+	pub const ObservationLimit: u32 = 144;
 }
 
 impl dex::Config for Runtime {
@@ -411,9 +457,20 @@ impl dex::Config for Runtime {
 	type Currency = Tokens;
 	type GetExchangeFee = GetExchangeFee;
 	type TradingPathLimit = TradingPathLimit;
+	type SplitRouteLimit = SplitRouteLimit;
+	type RouteSearchLimit = RouteSearchLimit;
+	type MaxEnabledTradingPairs = MaxEnabledTradingPairs;
 	type ModuleId = DEXModuleId;
 	type WhiteListOrigin = EnsureRoot<AccountId>;
+	type AssetPool = AssetPool;
+	type MaxLeverage = MaxLeverage;
+	type PriceSource = Prices;
+	type LiquidationRatio = PositionLiquidationRatio;
+	type LiquidationPenalty = PositionLiquidationPenalty;
 	type WeightInfo = weights::dex::WeightInfo<Runtime>;
+	type UnixTime = Timestamp;
+	type ObservationPeriod = ObservationPeriod;
+	type ObservationLimit = ObservationLimit;
 }
 
 // Create the runtime by composing the FRAME pallets that were previously configured.
@@ -652,6 +709,44 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl pallet_dex_rpc_runtime_api::DexApi<Block, AccountId, CurrencyId, TradingPair, Balance, Amount, Price> for Runtime {
+		fn get_swap_target_amount(path: Vec<CurrencyId>, supply_amount: Balance) -> Option<Balance> {
+			<DEX as DEXManager<AccountId>>::get_swap_target_amount(&path, supply_amount, None)
+		}
+
+		fn get_swap_supply_amount(path: Vec<CurrencyId>, target_amount: Balance) -> Option<Balance> {
+			<DEX as DEXManager<AccountId>>::get_swap_supply_amount(&path, target_amount, None)
+		}
+
+		fn get_best_target_route(
+			supply_currency_id: CurrencyId,
+			target_currency_id: CurrencyId,
+			supply_amount: Balance,
+		) -> Option<(Vec<CurrencyId>, Balance)> {
+			DEX::get_best_target_route(supply_currency_id, target_currency_id, supply_amount)
+		}
+
+		fn get_best_supply_route(
+			supply_currency_id: CurrencyId,
+			target_currency_id: CurrencyId,
+			target_amount: Balance,
+		) -> Option<(Vec<CurrencyId>, Balance)> {
+			DEX::get_best_supply_route(supply_currency_id, target_currency_id, target_amount)
+		}
+
+		fn get_liquidity_pool(trading_pair: TradingPair) -> (Balance, Balance) {
+			DEX::liquidity_pool(trading_pair)
+		}
+
+		fn get_twap(trading_pair: TradingPair, window: u64) -> Option<(Price, Price)> {
+			DEX::get_twap(trading_pair, window)
+		}
+
+		fn get_position_pnl(who: AccountId, trading_pair: TradingPair) -> Option<Amount> {
+			DEX::get_position_pnl(&who, trading_pair)
+		}
+	}
+
 	impl orml_oracle_rpc_runtime_api::OracleApi<Block, DataProviderId, CurrencyId, TimeStampedPrice> for Runtime {
 		fn get_value(provider_id: DataProviderId ,key: CurrencyId) -> Option<TimeStampedPrice> {
 			match provider_id {
@@ -43,6 +43,19 @@ impl<T: frame_system::Config> dex::WeightInfo for WeightInfo<T> {
 			.saturating_add(T::DbWeight::get().reads(7 as Weight))
 			.saturating_add(T::DbWeight::get().writes(6 as Weight))
 	}
+	fn swap_with_best_route(p: u32) -> Weight {
+		(178_000_000 as Weight)
+			.saturating_add((62_000_000 as Weight).saturating_mul(p as Weight))
+			.saturating_add(T::DbWeight::get().reads(7 as Weight))
+			.saturating_add(T::DbWeight::get().reads((4 as Weight).saturating_mul(p as Weight)))
+			.saturating_add(T::DbWeight::get().writes(8 as Weight))
+	}
+	fn swap_with_split_routes(r: u32) -> Weight {
+		(21_000_000 as Weight)
+			.saturating_add((185_000_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads((7 as Weight).saturating_mul(r as Weight)))
+			.saturating_add(T::DbWeight::get().writes((6 as Weight).saturating_mul(r as Weight)))
+	}
 	fn add_liquidity() -> Weight {
 		(250_000_000 as Weight)
 			.saturating_add(T::DbWeight::get().reads(9 as Weight))
@@ -55,17 +68,46 @@ impl<T: frame_system::Config> dex::WeightInfo for WeightInfo<T> {
 	}
 	fn enable_trading_pair() -> Weight {
 		(41_000_000 as Weight)
-			.saturating_add(T::DbWeight::get().reads(1 as Weight))
-			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+			.saturating_add(T::DbWeight::get().reads(2 as Weight))
+			.saturating_add(T::DbWeight::get().writes(2 as Weight))
 	}
 	fn disable_trading_pair() -> Weight {
 		(41_000_000 as Weight)
-			.saturating_add(T::DbWeight::get().reads(1 as Weight))
-			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+			.saturating_add(T::DbWeight::get().reads(2 as Weight))
+			.saturating_add(T::DbWeight::get().writes(2 as Weight))
 	}
 	fn new_trading_pair() -> Weight {
 		(50_000_000 as Weight)
 			.saturating_add(T::DbWeight::get().reads(2 as Weight))
 			.saturating_add(T::DbWeight::get().writes(1 as Weight))
 	}
+	fn swap_with_exact_supply_by_leverage() -> Weight {
+		(356_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(14 as Weight))
+			.saturating_add(T::DbWeight::get().writes(12 as Weight))
+	}
+	fn swap_with_exact_target_by_leverage() -> Weight {
+		(484_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(14 as Weight))
+			.saturating_add(T::DbWeight::get().writes(12 as Weight))
+	}
+	fn reduce_position() -> Weight {
+		(412_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(14 as Weight))
+			.saturating_add(T::DbWeight::get().writes(12 as Weight))
+	}
+	fn add_margin() -> Weight {
+		(168_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(6 as Weight))
+			.saturating_add(T::DbWeight::get().writes(5 as Weight))
+	}
+	fn liquidate_position() -> Weight {
+		(486_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(16 as Weight))
+			.saturating_add(T::DbWeight::get().writes(14 as Weight))
+	}
+	fn set_operation_paused() -> Weight {
+		(38_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
 }